@@ -0,0 +1,224 @@
+const ESCAPE: char = '\x1b';
+const BELL: char = '\x07';
+/// Single character form of `ESC [`
+const CSI: char = '\u{9b}';
+
+/// Sequence switching all colors and text styles off
+pub const RESET: &str = "\x1b[0m";
+
+/// Piece of a line: visible text, or an escape sequence which takes no room on the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Escape(&'a str),
+}
+
+/// Splits a line into its visible text and the ANSI escape sequences in between:
+/// CSI sequences (colors, cursor movement), OSC sequences (titles, hyperlinks) and two-character escapes.
+/// An escape sequence cut off by the end of the line is still recognized, so it is never taken for text.
+pub fn segments(line: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut position = 0;
+
+    while let Some(character) = line[position..].chars().next() {
+        if character != ESCAPE && character != CSI {
+            position += character.len_utf8();
+            continue;
+        }
+        if text_start < position {
+            segments.push(Segment::Text(&line[text_start..position]));
+        }
+        let end = escape_end(line, position);
+        segments.push(Segment::Escape(&line[position..end]));
+        position = end;
+        text_start = end;
+    }
+
+    if text_start < line.len() {
+        segments.push(Segment::Text(&line[text_start..]));
+    }
+    segments
+}
+
+/// Byte offset right after the escape sequence starting at `start`
+fn escape_end(line: &str, start: usize) -> usize {
+    let bytes = line.as_bytes();
+    let mut position = if line[start..].starts_with(CSI) {
+        start + CSI.len_utf8()
+    } else {
+        match bytes.get(start + 1) {
+            Some(b'[') => start + 2,
+            Some(b']') => return osc_end(line, start + 2),
+            // character set selection and similar: intermediate bytes, then a final byte
+            Some(0x20..=0x2f) => {
+                let mut position = start + 1;
+                while matches!(bytes.get(position), Some(0x20..=0x2f)) {
+                    position += 1;
+                }
+                return final_byte_end(bytes, position);
+            }
+            Some(_) => return final_byte_end(bytes, start + 1),
+            None => return line.len(),
+        }
+    };
+
+    // CSI: parameter and intermediate bytes, then the final byte
+    while matches!(bytes.get(position), Some(0x20..=0x3f)) {
+        position += 1;
+    }
+    final_byte_end(bytes, position)
+}
+
+/// Position after the final byte of a sequence, or the end of the line when it is missing
+fn final_byte_end(bytes: &[u8], position: usize) -> usize {
+    match bytes.get(position) {
+        Some(0x40..=0x7e) => position + 1,
+        _ => position.min(bytes.len()),
+    }
+}
+
+/// OSC sequences end with BEL or `ESC \`
+fn osc_end(line: &str, start: usize) -> usize {
+    let rest = &line[start..];
+    let bell = rest.find(BELL).map(|index| start + index + 1);
+    let terminator = rest.find("\x1b\\").map(|index| start + index + 2);
+    match (bell, terminator) {
+        (Some(bell), Some(terminator)) => bell.min(terminator),
+        (Some(end), None) | (None, Some(end)) => end,
+        (None, None) => line.len(),
+    }
+}
+
+/// Whether the line holds an escape sequence at all, most lines do not need to be parsed
+pub fn has_escapes(line: &str) -> bool {
+    line.contains([ESCAPE, CSI])
+}
+
+/// The line without any escape sequence, as it reads on the terminal
+pub fn strip_ansi(line: &str) -> String {
+    segments(line)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            Segment::Escape(_) => None,
+        })
+        .collect()
+}
+
+/// Number of visible characters of the line, escape sequences take no room
+pub fn visible_len(line: &str) -> usize {
+    segments(line)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.chars().count(),
+            Segment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// Line parsed into its text and escape sequences, for features which have to tell them apart,
+/// e.g. to count visible characters or to cut a line without splitting an escape sequence
+pub struct AnsiLine<'a> {
+    raw: &'a str,
+    segments: Vec<Segment<'a>>,
+}
+
+impl<'a> AnsiLine<'a> {
+    pub fn parse(raw: &'a str) -> Self {
+        Self {
+            raw,
+            segments: segments(raw),
+        }
+    }
+
+    /// The line as it was read, escape sequences included
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    pub fn segments(&self) -> &[Segment<'a>] {
+        &self.segments
+    }
+
+    /// The visible text without escape sequences
+    pub fn plain(&self) -> String {
+        strip_ansi(self.raw)
+    }
+
+    /// Number of visible characters
+    pub fn visible_len(&self) -> usize {
+        visible_len(self.raw)
+    }
+
+    /// Whether the line leaves a color or text style switched on at its end,
+    /// which would bleed into anything appended to it
+    pub fn ends_styled(&self) -> bool {
+        let last_style = self
+            .segments
+            .iter()
+            .rev()
+            .find_map(|segment| match segment {
+                Segment::Escape(escape) if is_style(escape) => Some(*escape),
+                _ => None,
+            });
+        last_style.is_some_and(|escape| !is_reset(escape))
+    }
+
+    /// The line cut after `width` visible characters. Escape sequences are never split,
+    /// and the ones after the cut are kept, so a style reset at the end still applies.
+    pub fn truncate(&self, width: usize) -> String {
+        let mut truncated = String::with_capacity(self.raw.len());
+        let mut remaining = width;
+        for segment in &self.segments {
+            match segment {
+                Segment::Escape(escape) => truncated.push_str(escape),
+                Segment::Text(text) => {
+                    let end = text
+                        .char_indices()
+                        .nth(remaining)
+                        .map_or(text.len(), |(index, _)| index);
+                    remaining -= text[..end].chars().count();
+                    truncated.push_str(&text[..end]);
+                }
+            }
+        }
+        truncated
+    }
+
+    /// The line with its trailing spaces and tabs replaced by `render(whitespace)`.
+    /// Whitespace is trailing when only escape sequences follow it, e.g. a closing color reset.
+    pub fn map_trailing_whitespace(&self, render: impl Fn(&str) -> String) -> String {
+        let mut trailing = true;
+        let mut pieces: Vec<String> = self
+            .segments
+            .iter()
+            .rev()
+            .map(|segment| match segment {
+                Segment::Escape(escape) => escape.to_string(),
+                Segment::Text(text) if trailing => {
+                    let body = text.trim_end_matches([' ', '\t']);
+                    trailing = body.is_empty();
+                    let whitespace = &text[body.len()..];
+                    if whitespace.is_empty() {
+                        body.to_string()
+                    } else {
+                        format!("{}{}", body, render(whitespace))
+                    }
+                }
+                Segment::Text(text) => text.to_string(),
+            })
+            .collect();
+        pieces.reverse();
+        pieces.concat()
+    }
+}
+
+/// Select Graphic Rendition sequence, which sets colors and text styles
+fn is_style(escape: &str) -> bool {
+    (escape.starts_with("\x1b[") || escape.starts_with(CSI)) && escape.ends_with('m')
+}
+
+fn is_reset(escape: &str) -> bool {
+    matches!(escape, "\x1b[m" | "\x1b[0m" | "\u{9b}m" | "\u{9b}0m")
+}
@@ -0,0 +1,155 @@
+use std::io::Read;
+
+use crate::errors::RicatError;
+
+/// Separator between the archive path and the member path, e.g. `logs.tar.gz::app/today.log`
+pub const MEMBER_SEPARATOR: &str = "::";
+
+/// Splits an `archive::member` argument, `None` when it does not name an archive member
+pub fn split_member_argument(argument: &str) -> Option<(&str, &str)> {
+    let (archive, member) = argument.split_once(MEMBER_SEPARATOR)?;
+    if archive.is_empty() || member.is_empty() {
+        return None;
+    }
+    Some((archive, member))
+}
+
+/// Member paths are compared without a leading `./`
+#[cfg(feature = "archive")]
+fn normalize_member_path(path: &str) -> &str {
+    path.trim_start_matches("./")
+}
+
+/// Streams a single member of a tar (possibly compressed) or zip archive, without extracting it to disk
+/// nor holding it in memory: the reader is left at the start of the member's data, which is read from there.
+#[cfg(feature = "archive")]
+pub fn open_member(archive: &str, member: &str) -> Result<Box<dyn Read>, RicatError> {
+    use std::fs::File;
+    use std::io::{Seek, SeekFrom};
+
+    let archive_error = |error: String| {
+        RicatError::ArchiveError(format!(
+            "{}{}{}: {}",
+            archive, MEMBER_SEPARATOR, member, error
+        ))
+    };
+
+    let mut file = File::open(archive).map_err(|error| {
+        RicatError::FileOpenError(format!("Failed to open {}: {}", archive, error))
+    })?;
+    let mut magic = [0_u8; 4];
+    let is_zip = file.read_exact(&mut magic).is_ok() && magic.starts_with(b"PK");
+    file.seek(SeekFrom::Start(0))?;
+
+    if is_zip {
+        let mut zip_archive =
+            zip::ZipArchive::new(file).map_err(|error| archive_error(error.to_string()))?;
+        let index = zip_archive
+            .index_for_name(normalize_member_path(member))
+            .ok_or_else(|| archive_error("no such member in the archive".to_string()))?;
+        let (method, data_start, size, crc32) = {
+            let zip_member = zip_archive
+                .by_index_raw(index)
+                .map_err(|error| archive_error(error.to_string()))?;
+            if zip_member.encrypted() {
+                return Err(archive_error(
+                    "encrypted members cannot be read".to_string(),
+                ));
+            }
+            let data_start = zip_member
+                .data_start()
+                .ok_or_else(|| archive_error("the member has no data".to_string()))?;
+            (
+                zip_member.compression(),
+                data_start,
+                zip_member.compressed_size(),
+                zip_member.crc32(),
+            )
+        };
+
+        let mut file = zip_archive.into_inner();
+        file.seek(SeekFrom::Start(data_start))?;
+        let data = file.take(size);
+        let content: Box<dyn Read> = match method {
+            zip::CompressionMethod::Stored => Box::new(data),
+            zip::CompressionMethod::Deflated => Box::new(flate2::read::DeflateDecoder::new(data)),
+            method => {
+                return Err(archive_error(format!(
+                    "members compressed with {} cannot be read",
+                    method
+                )))
+            }
+        };
+        return Ok(Box::new(CheckedMember::new(content, crc32)));
+    }
+
+    // tar archives can be compressed as a whole, e.g. `.tar.gz`
+    let reader = crate::decompression::decompress(Box::new(file), archive)?;
+    let mut tar_archive = tar::Archive::new(reader);
+    let mut size = None;
+    for entry in tar_archive
+        .entries()
+        .map_err(|error| archive_error(error.to_string()))?
+    {
+        let entry = entry.map_err(|error| archive_error(error.to_string()))?;
+        let path = entry
+            .path()
+            .map_err(|error| archive_error(error.to_string()))?;
+        if normalize_member_path(&path.to_string_lossy()) == normalize_member_path(member) {
+            // the data of a sparse file is not stored in one piece
+            if entry.header().entry_type().is_gnu_sparse() {
+                return Err(archive_error("sparse members cannot be read".to_string()));
+            }
+            size = Some(entry.size());
+            break;
+        }
+    }
+    // the entries read the archive up to the header of the member, its data follows
+    match size {
+        Some(size) => Ok(Box::new(tar_archive.into_inner().take(size))),
+        None => Err(archive_error("no such member in the archive".to_string())),
+    }
+}
+
+/// Data of a zip member, checked against the CRC-32 of the archive once it is read to the end
+#[cfg(feature = "archive")]
+struct CheckedMember<R> {
+    reader: R,
+    hasher: crc32fast::Hasher,
+    crc32: u32,
+}
+
+#[cfg(feature = "archive")]
+impl<R: Read> CheckedMember<R> {
+    fn new(reader: R, crc32: u32) -> Self {
+        Self {
+            reader,
+            hasher: crc32fast::Hasher::new(),
+            crc32,
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+impl<R: Read> Read for CheckedMember<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        if len == 0 && !buf.is_empty() && self.hasher.clone().finalize() != self.crc32 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the member does not match its checksum in the archive",
+            ));
+        }
+        Ok(len)
+    }
+}
+
+/// Archive members cannot be read without the `archive` cargo feature
+#[cfg(not(feature = "archive"))]
+pub fn open_member(archive: &str, member: &str) -> Result<Box<dyn Read>, RicatError> {
+    Err(RicatError::ArchiveError(format!(
+        "{}{}{}: ricat was built without the `archive` feature, reinstall with `cargo install ricat --features archive`",
+        archive, MEMBER_SEPARATOR, member
+    )))
+}
@@ -0,0 +1,219 @@
+use std::io;
+
+use memchr::memchr;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::errors::RicatError;
+use crate::line_reader::{
+    oversized_line_cut, strip_line_ending, warn_oversized_line, LineEnding, LineLimit, OversizeMode,
+};
+use crate::{
+    apply_record_features, at_line, decorate_records, output_ending, processing_error, ByteFeature,
+    LineContext, OutputOptions,
+};
+
+/// Processes input like `process_input_to`, reading and writing without blocking the thread, for services
+/// handling sockets and files on tokio. The features can be `Box<dyn ByteFeature + Send>`, so the future
+/// can be spawned on a multi-threaded runtime.
+///
+/// Lines longer than `line_limit` of the options are split into chunks or truncated like the blocking
+/// pipeline does. The features are not finished, more input may follow.
+pub async fn process_input_async<R, W, F>(
+    reader: R,
+    name: &str,
+    writer: &mut W,
+    features: &mut [Box<F>],
+    options: OutputOptions,
+) -> Result<(), RicatError>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + ?Sized,
+    F: ByteFeature + ?Sized,
+{
+    let mut records = AsyncRecords::new(reader, options.zero_terminated, options.line_limit);
+    let terminator = if options.zero_terminated { "\0" } else { "\n" };
+
+    let mut record = Vec::new();
+    let mut line_number = 0;
+    while let Some((ending, byte_offset)) = records.next_record(&mut record).await? {
+        line_number += 1;
+        let is_last = if options.streaming && ending != LineEnding::None {
+            false
+        } else {
+            records.at_end().await?
+        };
+
+        let context = LineContext {
+            file_name: name,
+            line_number,
+            byte_offset,
+            is_last,
+        };
+        let processed_records = apply_record_features(features, &record, &context)
+            .map_err(at_line(line_number))
+            .map_err(processing_error)?;
+        let processed_lines = decorate_records(&options, &context, processed_records);
+        let ending = output_ending(&options, ending);
+        for (index, curr_line) in processed_lines.iter().enumerate() {
+            // a line split by a feature keeps its pieces apart even when the input ended without a newline
+            let ending = if ending.is_empty() && index + 1 < processed_lines.len() {
+                terminator
+            } else {
+                ending
+            };
+            write_async(writer, curr_line).await?;
+            write_async(writer, ending.as_bytes()).await?;
+        }
+        if options.unbuffered {
+            flush_async(writer).await?;
+        }
+    }
+    flush_async(writer).await
+}
+
+/// Records of an async reader, like `LossyLines::next_record` with its line limit
+struct AsyncRecords<R> {
+    reader: BufReader<R>,
+    /// byte ending a line, `\n` or NUL
+    delimiter: u8,
+    limit: Option<LineLimit>,
+    /// start of the next chunk of an oversized line, read past the previous chunk
+    carry: Vec<u8>,
+    /// lines read whole so far
+    line_number: u64,
+    warned: bool,
+    /// bytes taken from the reader so far
+    consumed: u64,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRecords<R> {
+    fn new(reader: R, zero_terminated: bool, limit: Option<LineLimit>) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            delimiter: if zero_terminated { b'\0' } else { b'\n' },
+            limit,
+            carry: Vec::new(),
+            line_number: 0,
+            warned: false,
+            consumed: 0,
+        }
+    }
+
+    /// Reads the next record into `record` without its terminator. Returns the terminator and the byte offset
+    /// where the record starts, `None` at the end of the input.
+    async fn next_record(&mut self, record: &mut Vec<u8>) -> io::Result<Option<(LineEnding, u64)>> {
+        record.clear();
+        let offset = self.consumed - self.carry.len() as u64;
+        let Some(limit) = self.limit else {
+            let len = self.reader.read_until(self.delimiter, record).await?;
+            self.consumed += len as u64;
+            return Ok((len > 0).then(|| (strip_line_ending(record, self.delimiter), offset)));
+        };
+
+        // at most `max_bytes + 2` bytes without a line break, which leaves room for a `\r\n` terminator
+        record.append(&mut self.carry);
+        let max_len = limit.max_bytes + 2;
+        while !record.ends_with(&[self.delimiter]) && record.len() < max_len {
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                break;
+            }
+            let room = (max_len - record.len()).min(available.len());
+            let len = memchr(self.delimiter, &available[..room]).map_or(room, |end| end + 1);
+            record.extend_from_slice(&available[..len]);
+            self.reader.consume(len);
+            self.consumed += len as u64;
+        }
+        if record.is_empty() {
+            return Ok(None);
+        }
+
+        let terminator = if self.delimiter == b'\n' && record.ends_with(b"\r\n") {
+            2
+        } else {
+            usize::from(record.ends_with(&[self.delimiter]))
+        };
+        if record.len() - terminator <= limit.max_bytes {
+            self.line_number += 1;
+            return Ok(Some((strip_line_ending(record, self.delimiter), offset)));
+        }
+
+        let cut = oversized_line_cut(record, limit.max_bytes);
+        if !self.warned {
+            self.warned = true;
+            warn_oversized_line(self.line_number + 1, limit);
+        }
+        let ending = match limit.mode {
+            OversizeMode::Chunk => {
+                self.carry = record.split_off(cut);
+                LineEnding::None
+            }
+            OversizeMode::Truncate => {
+                let last_byte = record.last().copied();
+                let ending = strip_line_ending(record, self.delimiter);
+                record.truncate(cut);
+                if ending == LineEnding::None {
+                    self.skip_rest_of_line(last_byte).await?
+                } else {
+                    self.line_number += 1;
+                    ending
+                }
+            }
+        };
+        Ok(Some((ending, offset)))
+    }
+
+    /// Whether the input ended after the record read last, waits for more input on a socket
+    async fn at_end(&mut self) -> io::Result<bool> {
+        Ok(self.carry.is_empty() && self.reader.fill_buf().await?.is_empty())
+    }
+
+    /// Skips the rest of a truncated line without holding it in memory, returns its terminator
+    async fn skip_rest_of_line(&mut self, mut last_byte: Option<u8>) -> io::Result<LineEnding> {
+        loop {
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                return Ok(LineEnding::None);
+            }
+            match memchr(self.delimiter, available) {
+                Some(end) => {
+                    let carriage_return = match end {
+                        0 => last_byte == Some(b'\r'),
+                        _ => available[end - 1] == b'\r',
+                    };
+                    self.reader.consume(end + 1);
+                    self.consumed += end as u64 + 1;
+                    self.line_number += 1;
+                    return Ok(match (self.delimiter, carriage_return) {
+                        (b'\0', _) => LineEnding::Nul,
+                        (_, true) => LineEnding::CrLf,
+                        (_, false) => LineEnding::Lf,
+                    });
+                }
+                None => {
+                    last_byte = available.last().copied();
+                    let len = available.len();
+                    self.reader.consume(len);
+                    self.consumed += len as u64;
+                }
+            }
+        }
+    }
+}
+
+async fn write_async<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    bytes: &[u8],
+) -> Result<(), RicatError> {
+    writer
+        .write_all(bytes)
+        .await
+        .map_err(|error| RicatError::LineProcessingError(format!("Error writing line: {}", error)))
+}
+
+async fn flush_async<W: AsyncWrite + Unpin + ?Sized>(writer: &mut W) -> Result<(), RicatError> {
+    writer
+        .flush()
+        .await
+        .map_err(|error| RicatError::OutputFlushError(format!("Failed to flush output: {}", error)))
+}
@@ -0,0 +1,172 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::errors::RicatError;
+use crate::io_backend::copy_with_uring;
+use crate::progress::format_bytes;
+use crate::zero_copy::{copy_file_direct, DirectOutput};
+use crate::{
+    add_features_from_args, copy_mmap_with_progress, copy_with_progress, process_input_to,
+    processing_error, Buffering, Cli, OutputOptions,
+};
+
+/// A way of copying the file to the pipe, `false` when it is not available here
+type Strategy<'a> = (
+    &'a str,
+    Box<dyn Fn(&str, File) -> Result<bool, RicatError> + 'a>,
+);
+
+/// `ricat bench FILE`: copies the file into a pipe, drained by a thread like a program reading ricat's
+/// output would, with every I/O strategy and through the line pipeline, and writes a table of the fastest
+/// of `runs` runs of each. The first run reads the file into the page cache, the others read it from there.
+pub fn run_bench(
+    arguments: &Cli,
+    file_path: &str,
+    runs: usize,
+    writer: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let size = std::fs::metadata(file_path)
+        .map_err(|error| {
+            RicatError::FileOpenError(format!("Error opening file {}: {}", file_path, error))
+        })?
+        .len();
+    let open = |path: &str| {
+        File::open(path).map_err(|error| {
+            RicatError::FileOpenError(format!("Error opening file {}: {}", path, error))
+        })
+    };
+
+    let mut strategies: Vec<Strategy> = vec![
+        (
+            "buffered read",
+            Box::new(|path, pipe| {
+                copy_with_progress(open(path)?, pipe, Buffering::default(), None).map(|_| true)
+            }),
+        ),
+        (
+            "mmap",
+            Box::new(|path, pipe| {
+                copy_mmap_with_progress(path, pipe, Buffering::default(), None).map(|_| true)
+            }),
+        ),
+        (
+            "kernel copy (splice)",
+            Box::new(|path, pipe| {
+                let Some(output) = DirectOutput::file(&pipe) else {
+                    return Ok(false);
+                };
+                Ok(copy_file_direct(&open(path)?, &output, None)?)
+            }),
+        ),
+    ];
+    if cfg!(all(feature = "io-uring", target_os = "linux")) {
+        strategies.push((
+            "io_uring",
+            Box::new(|path, pipe| {
+                copy_with_uring(path, pipe, Buffering::default(), None).map(|_| true)
+            }),
+        ));
+    }
+    strategies.push((
+        "lines, no feature",
+        Box::new(|path, pipe| pipeline(path, pipe, &mut [])),
+    ));
+    if !add_features_from_args(arguments)?.is_empty() {
+        strategies.push((
+            "lines, given features",
+            Box::new(|path, pipe| pipeline(path, pipe, &mut add_features_from_args(arguments)?)),
+        ));
+    }
+
+    let write_error = |error: io::Error| {
+        RicatError::LineWriteError(format!("Error writing benchmark results: {}", error))
+    };
+    writeln!(
+        writer,
+        "{}: {}, fastest of {} runs",
+        file_path,
+        format_bytes(size as f64),
+        runs
+    )
+    .map_err(write_error)?;
+    writeln!(
+        writer,
+        "{:<24} {:>10} {:>14}",
+        "strategy", "time", "throughput"
+    )
+    .map_err(write_error)?;
+    for (name, strategy) in &strategies {
+        let mut fastest: Option<Duration> = None;
+        for _ in 0..runs {
+            match time_run(file_path, strategy)? {
+                Some(time) => fastest = Some(fastest.map_or(time, |fastest| fastest.min(time))),
+                None => break,
+            }
+        }
+        match fastest {
+            Some(time) => writeln!(
+                writer,
+                "{:<24} {:>8.3} s {:>12}/s",
+                name,
+                time.as_secs_f64(),
+                format_bytes(size as f64 / time.as_secs_f64().max(f64::EPSILON))
+            ),
+            None => writeln!(writer, "{:<24} {:>10} {:>14}", name, "-", "unavailable"),
+        }
+        .map_err(write_error)?;
+    }
+    Ok(())
+}
+
+/// Times one run of the strategy, from the start of the copy until the pipe is drained
+fn time_run(
+    file_path: &str,
+    strategy: &dyn Fn(&str, File) -> Result<bool, RicatError>,
+) -> Result<Option<Duration>, RicatError> {
+    let (mut reader, pipe) = io::pipe()?;
+    let drain = thread::spawn(move || {
+        let mut buffer = vec![0; 1024 * 1024];
+        while reader.read(&mut buffer).is_ok_and(|len| len > 0) {}
+    });
+
+    let start = Instant::now();
+    let copied = strategy(file_path, pipe_file(pipe));
+    // the pipe is closed once the strategy returns, the drain ends with the last byte
+    let _ = drain.join();
+    let time = start.elapsed();
+    Ok(copied?.then_some(time))
+}
+
+#[cfg(unix)]
+fn pipe_file(pipe: io::PipeWriter) -> File {
+    File::from(std::os::fd::OwnedFd::from(pipe))
+}
+
+#[cfg(windows)]
+fn pipe_file(pipe: io::PipeWriter) -> File {
+    File::from(std::os::windows::io::OwnedHandle::from(pipe))
+}
+
+/// Splits the file into lines and runs them through the features
+fn pipeline(
+    file_path: &str,
+    pipe: File,
+    features: &mut [Box<dyn crate::ByteFeature>],
+) -> Result<bool, RicatError> {
+    let file = File::open(file_path).map_err(|error| {
+        RicatError::FileOpenError(format!("Error opening file {}: {}", file_path, error))
+    })?;
+    let mut writer = BufWriter::new(pipe);
+    process_input_to(
+        file,
+        file_path,
+        &mut writer,
+        features,
+        OutputOptions::default(),
+    )
+    .map_err(processing_error)?;
+    writer.flush()?;
+    Ok(true)
+}
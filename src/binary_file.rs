@@ -0,0 +1,76 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::errors::RicatError;
+
+/// Number of bytes inspected at the start of the input to detect binary content
+pub const SNIFF_SIZE: usize = 8192;
+
+/// Binary Mode: what to do with input detected as binary
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryMode {
+    /// print a notice instead of the content
+    Skip,
+    /// show the content as a hex view
+    Hex,
+}
+
+/// Heuristic used by `grep` and `git`: text never contains NUL bytes
+pub fn looks_binary(block: &[u8]) -> bool {
+    block.contains(&0)
+}
+
+/// Peeks at the first block of the input to detect binary content.
+/// Returns whether the input looks binary, along with a reader which still yields the whole input.
+pub fn sniff(reader: Box<dyn Read>) -> Result<(bool, Box<dyn Read>), RicatError> {
+    let mut reader = BufReader::with_capacity(SNIFF_SIZE, reader);
+    let is_binary = looks_binary(reader.fill_buf()?);
+    Ok((is_binary, Box::new(reader)))
+}
+
+/// Writes the input as a hex view: offset, 16 bytes in hex and their printable ASCII characters
+pub fn hex_dump<R: Read, W: Write + ?Sized>(mut reader: R, writer: &mut W) -> Result<(), RicatError> {
+    const BYTES_PER_ROW: usize = 16;
+    let mut row = [0_u8; BYTES_PER_ROW];
+    let mut offset = 0;
+
+    loop {
+        let mut filled = 0;
+        while filled < BYTES_PER_ROW {
+            let len = reader.read(&mut row[filled..])?;
+            if len == 0 {
+                break;
+            }
+            filled += len;
+        }
+        if filled == 0 {
+            break;
+        }
+
+        let hex = (0..BYTES_PER_ROW)
+            .map(|index| match row[..filled].get(index) {
+                Some(byte) => format!("{:02x}", byte),
+                None => "  ".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = row[..filled]
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        writeln!(writer, "{:08x}  {}  |{}|", offset, hex, ascii).map_err(|error| {
+            RicatError::LineWriteError(format!("Error writing hex view: {}", error))
+        })?;
+
+        offset += filled;
+        if filled < BYTES_PER_ROW {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::io::{stdout, BufWriter, Read, Write};
+
+use crate::errors::RicatError;
+use crate::input_source::InputSource;
+use crate::{
+    begin_input, finish_features, paginate_output, process_input_ret_with, process_input_to,
+    processing_error, write_lines_to, ByteFeature, OutputOptions,
+};
+
+/// An input of the pipeline
+enum Input<'a> {
+    /// a file argument, read like on the command line: `-` for stdin, URLs, archive members, compressed files
+    Source(InputSource),
+    /// data from the program itself, with the name the features see
+    Reader(String, Box<dyn Read + 'a>),
+}
+
+/// A ricat pipeline for programs embedding ricat: the inputs, the features applied to their lines,
+/// and where the output goes. Built with `Ricat::builder()`:
+///
+/// ```no_run
+/// use ricat::{LineNumbering, Ricat};
+///
+/// Ricat::builder()
+///     .source("notes.txt")
+///     .feature(LineNumbering::new())
+///     .paginate(false)
+///     .write_to(std::io::stdout())
+///     .run()?;
+/// # Ok::<(), ricat::RicatError>(())
+/// ```
+pub struct Ricat<'a> {
+    inputs: Vec<Input<'a>>,
+    features: Vec<Box<dyn ByteFeature>>,
+    options: OutputOptions,
+    paginate: bool,
+    writer: Box<dyn Write + 'a>,
+}
+
+impl<'a> Ricat<'a> {
+    pub fn builder() -> RicatBuilder<'a> {
+        RicatBuilder::default()
+    }
+
+    /// Reads the inputs one after the other through the features and writes the result,
+    /// followed by the lines the features add at the end. Without inputs the standard input is read.
+    pub fn run(mut self) -> Result<(), RicatError> {
+        if self.inputs.is_empty() {
+            self.inputs.push(Input::Source(InputSource::Stdin));
+        }
+
+        if self.paginate {
+            let mut lines = Vec::new();
+            for input in std::mem::take(&mut self.inputs) {
+                let (name, reader) = open_input(&mut self.features, input)?;
+                lines.extend(
+                    process_input_ret_with(reader, &name, &mut self.features, self.options.clone())
+                        .map_err(processing_error)?,
+                );
+            }
+            lines.extend(finish_features(&mut self.features));
+            return paginate_output(lines, self.writer).map(|_| ());
+        }
+
+        let mut writer = BufWriter::new(&mut self.writer);
+        for input in std::mem::take(&mut self.inputs) {
+            let (name, reader) = open_input(&mut self.features, input)?;
+            process_input_to(reader, &name, &mut writer, &mut self.features, self.options.clone())
+                .map_err(processing_error)?;
+        }
+        write_lines_to(finish_features(&mut self.features), &mut writer)?;
+        writer.flush().map_err(|error| {
+            RicatError::OutputFlushError(format!("Failed to flush output: {}", error))
+        })
+    }
+}
+
+/// Opens an input and tells the features that it starts
+fn open_input<'a>(
+    features: &mut [Box<dyn ByteFeature>],
+    input: Input<'a>,
+) -> Result<(String, Box<dyn Read + 'a>), RicatError> {
+    match input {
+        Input::Source(source) => {
+            begin_input(features, &source)?;
+            Ok((source.name(), source.open_decompressed()?))
+        }
+        Input::Reader(name, reader) => {
+            begin_input(features, &InputSource::File(name.clone()))?;
+            Ok((name, reader))
+        }
+    }
+}
+
+/// Builder of a `Ricat` pipeline. By default it reads the standard input, applies no feature
+/// and writes to the standard output without paging.
+#[derive(Default)]
+pub struct RicatBuilder<'a> {
+    inputs: Vec<Input<'a>>,
+    features: Vec<Box<dyn ByteFeature>>,
+    options: OutputOptions,
+    paginate: bool,
+    writer: Option<Box<dyn Write + 'a>>,
+}
+
+impl<'a> RicatBuilder<'a> {
+    /// Adds a file argument, read like on the command line: `-` is the standard input,
+    /// URLs and archive members work with their cargo features, compressed files are decompressed
+    pub fn source(mut self, path: impl AsRef<str>) -> Self {
+        self.inputs
+            .push(Input::Source(InputSource::from_arg(path.as_ref())));
+        self
+    }
+
+    /// Adds an input read from the reader, the features see it under `name`
+    pub fn reader(mut self, name: &str, reader: impl Read + 'a) -> Self {
+        self.inputs
+            .push(Input::Reader(name.to_string(), Box::new(reader)));
+        self
+    }
+
+    /// Adds a feature after the ones added so far, the features apply to every line in that order
+    pub fn feature(mut self, feature: impl ByteFeature + 'static) -> Self {
+        self.features.push(Box::new(feature));
+        self
+    }
+
+    /// Adds features built elsewhere, e.g. by a `FeatureRegistry`
+    pub fn features(mut self, features: impl IntoIterator<Item = Box<dyn ByteFeature>>) -> Self {
+        self.features.extend(features);
+        self
+    }
+
+    /// How the lines are read and written: line endings, `-z` records, long lines, offsets
+    pub fn options(mut self, options: OutputOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Shows the output in the pager like `--pages`, for output to a terminal
+    pub fn paginate(mut self, paginate: bool) -> Self {
+        self.paginate = paginate;
+        self
+    }
+
+    /// Writes the output to the writer instead of the standard output
+    pub fn write_to(mut self, writer: impl Write + 'a) -> Self {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    pub fn build(self) -> Ricat<'a> {
+        Ricat {
+            inputs: self.inputs,
+            features: self.features,
+            options: self.options,
+            paginate: self.paginate,
+            writer: self.writer.unwrap_or_else(|| Box::new(stdout())),
+        }
+    }
+
+    /// Builds the pipeline and runs it, see `Ricat::run`
+    pub fn run(self) -> Result<(), RicatError> {
+        self.build().run()
+    }
+}
@@ -0,0 +1,128 @@
+use std::io::{self, Write};
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Checksum algorithms selectable with `--sha256`, `--md5` and `--crc32`, or by name with `--hash-lines`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha1,
+    Sha256,
+    Md5,
+    Crc32,
+}
+
+impl ChecksumAlgorithm {
+    /// Name used in the BSD style `SHA256 (file) = ...` lines
+    pub fn tag(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+            ChecksumAlgorithm::Md5 => "MD5",
+            ChecksumAlgorithm::Crc32 => "CRC32",
+        }
+    }
+}
+
+/// Running state of one checksum
+enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Md5(Md5),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+            ChecksumAlgorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Crc32(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Digest as lowercase hex
+    fn finalize(self) -> String {
+        match self {
+            Hasher::Sha1(hasher) => to_hex(&hasher.finalize()),
+            Hasher::Sha256(hasher) => to_hex(&hasher.finalize()),
+            Hasher::Md5(hasher) => to_hex(&hasher.finalize()),
+            Hasher::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Digest of the data as lowercase hex
+pub fn digest(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Writer feeding everything written to it into the hashers before passing it on,
+/// so the checksums are computed by the streaming copy without holding the data in memory
+pub struct DigestWriter<W: Write> {
+    inner: W,
+    hashers: Vec<(ChecksumAlgorithm, Hasher)>,
+}
+
+impl<W: Write> DigestWriter<W> {
+    pub fn new(inner: W, algorithms: &[ChecksumAlgorithm]) -> Self {
+        Self {
+            inner,
+            hashers: algorithms
+                .iter()
+                .map(|&algorithm| (algorithm, Hasher::new(algorithm)))
+                .collect(),
+        }
+    }
+
+    /// Digests of all the data written, in the order of the algorithms
+    pub fn finalize(self) -> Vec<(ChecksumAlgorithm, String)> {
+        self.hashers
+            .into_iter()
+            .map(|(algorithm, hasher)| (algorithm, hasher.finalize()))
+            .collect()
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        for (_, hasher) in &mut self.hashers {
+            hasher.update(&buf[..len]);
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Checksum lines of one input: `sha256sum` style `digest  name` for a single algorithm,
+/// BSD style `SHA256 (name) = digest` when several are computed, so the lines stay unambiguous
+pub fn checksum_lines(name: &str, digests: &[(ChecksumAlgorithm, String)]) -> Vec<String> {
+    match digests {
+        [(_, digest)] => vec![format!("{}  {}", digest, name)],
+        digests => digests
+            .iter()
+            .map(|(algorithm, digest)| format!("{} ({}) = {}", algorithm.tag(), name, digest))
+            .collect(),
+    }
+}
@@ -1,35 +1,609 @@
-use serde::Deserialize;
+use directories::{BaseDirs, ProjectDirs};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
-use std::fs::{create_dir_all, read_to_string};
-use std::path::PathBuf;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+
+use crate::errors::RicatError;
+use crate::io_backend::IoBackend;
+use crate::memory_budget::parse_byte_size;
+use crate::pager::LongLineMode;
+use crate::theme::ThemeSettings;
+use crate::NumberAlign;
+
+/// Pagination Mode: when the pager should be used for the output
+#[derive(Deserialize, Serialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PagesMode {
+    /// always paginate the output
+    Always,
+    /// paginate only when the output does not fit the terminal screen
+    Auto,
+    /// never paginate the output
+    Never,
+}
 
 /// Config struct
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
 pub struct RicatConfig {
     pub number_feature: bool,
     pub dollar_sign_feature: bool,
     pub tabs_feature: bool,
     pub compress_empty_line_feature: bool,
+    /// features enabled by name, like `--features`
+    pub features: Vec<String>,
+    /// order of the named features, like `--feature-order`
+    pub feature_order: Vec<String>,
+    pub pagination_mode: Option<PagesMode>,
+    pub pager_long_lines: Option<LongLineMode>,
+    /// defaults of `--number-width`, `--number-align`, `--number-separator` and `--number-start`,
+    /// the `[numbering]` section takes precedence
+    pub number_width: Option<usize>,
+    pub number_align: Option<NumberAlign>,
+    pub number_separator: Option<String>,
+    pub number_start: Option<usize>,
+    /// tab stop distance for `--expand-tabs` and `--unexpand` when the flag gives none, `[tabs] width` takes precedence
+    pub tab_width: Option<u32>,
+    /// how plain copies read the files, like `--io-backend`
+    pub io_backend: Option<IoBackend>,
+    /// size from which `--io-backend auto` maps files to memory, like `--mmap-threshold`
+    pub mmap_threshold: Option<u64>,
+    /// bytes of input and lines held in memory at most, like `--max-memory`
+    pub max_memory: Option<usize>,
+    /// `[numbering]` section
+    pub numbering: NumberingSettings,
+    /// `[squeeze]` section
+    pub squeeze: SqueezeSettings,
+    /// `[tabs]` section
+    pub tabs: TabsSettings,
+    /// `[theme]` section
+    pub theme: ThemeSettings,
+    /// settings applied over the others with `--profile NAME`, `[profiles.NAME]` sections
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, RicatConfig>,
+}
+
+/// Layout of the line numbers, `[numbering]`: defaults of `--number-start`, `--number-width`,
+/// `--number-align` and `--number-separator`
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct NumberingSettings {
+    pub start: Option<usize>,
+    pub width: Option<usize>,
+    pub align: Option<NumberAlign>,
+    pub separator: Option<String>,
+}
+
+/// Squeezing of blank lines by `-s`, `[squeeze]`
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SqueezeSettings {
+    /// blank lines kept in a row, like `--max-blank`
+    pub max_blank: Option<usize>,
+}
+
+/// Tab stops, `[tabs]`
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TabsSettings {
+    /// tab stop distance for `--expand-tabs` and `--unexpand` when the flag gives none
+    pub width: Option<u32>,
+}
+
+impl RicatConfig {
+    /// Number of the first line, from `[numbering]` or the top level
+    pub fn number_start(&self) -> Option<usize> {
+        self.numbering.start.or(self.number_start)
+    }
+
+    /// Minimum width of the line numbers, from `[numbering]` or the top level
+    pub fn number_width(&self) -> Option<usize> {
+        self.numbering.width.or(self.number_width)
+    }
+
+    /// Alignment of the line numbers, from `[numbering]` or the top level
+    pub fn number_align(&self) -> Option<NumberAlign> {
+        self.numbering.align.or(self.number_align)
+    }
+
+    /// Text between the line number and the line, from `[numbering]` or the top level
+    pub fn number_separator(&self) -> Option<String> {
+        self.numbering.separator.clone().or_else(|| self.number_separator.clone())
+    }
+
+    /// Tab stop distance, from `[tabs]` or the top level
+    pub fn tab_width(&self) -> Option<u32> {
+        self.tabs.width.or(self.tab_width)
+    }
+
+    /// Applies the settings of the profile over the others. A profile turns features on, it does not turn
+    /// those of the file off; its lists and values replace those of the file.
+    pub fn select_profile(&mut self, name: &str) -> Result<(), RicatError> {
+        let Some(profile) = self.profiles.remove(name) else {
+            let profiles: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            return Err(RicatError::ConfigReadError(match profiles.as_slice() {
+                [] => format!("no profile `{}`, the configuration has no [profiles.NAME] section", name),
+                _ => format!("no profile `{}`, the profiles are {}", name, profiles.join(", ")),
+            }));
+        };
+        if !profile.profiles.is_empty() {
+            return Err(RicatError::ConfigReadError(format!(
+                "the profile `{}` holds profiles of its own, they cannot be nested",
+                name
+            )));
+        }
+
+        self.number_feature |= profile.number_feature;
+        self.dollar_sign_feature |= profile.dollar_sign_feature;
+        self.tabs_feature |= profile.tabs_feature;
+        self.compress_empty_line_feature |= profile.compress_empty_line_feature;
+        self.pagination_mode = profile.pagination_mode.or(self.pagination_mode);
+        self.pager_long_lines = profile.pager_long_lines.or(self.pager_long_lines);
+        // set in the sections, which take precedence over the top level of the file
+        self.numbering.start = profile.number_start().or(self.numbering.start);
+        self.numbering.width = profile.number_width().or(self.numbering.width);
+        self.numbering.align = profile.number_align().or(self.numbering.align);
+        self.numbering.separator = profile.number_separator().or(self.numbering.separator.take());
+        self.tabs.width = profile.tab_width().or(self.tabs.width);
+        self.squeeze.max_blank = profile.squeeze.max_blank.or(self.squeeze.max_blank);
+        self.theme.apply(profile.theme);
+        if !profile.features.is_empty() {
+            self.features = profile.features;
+        }
+        if !profile.feature_order.is_empty() {
+            self.feature_order = profile.feature_order;
+        }
+        self.io_backend = profile.io_backend.or(self.io_backend);
+        self.mmap_threshold = profile.mmap_threshold.or(self.mmap_threshold);
+        self.max_memory = profile.max_memory.or(self.max_memory);
+        Ok(())
+    }
+}
+
+/// Name of the configuration file in the configuration directory
+const CONFIG_FILE_NAME: &str = "ricat_cfg.toml";
+
+/// Directory of the configuration: `$RICAT_CONFIG_DIR`, or the one of the platform: `$XDG_CONFIG_HOME/ricat`
+/// (`~/.config/ricat`) on Linux, `~/Library/Application Support/ricat` on macOS, `%APPDATA%\ricat\config`
+/// on Windows. The `~/.config/ricat` of earlier versions is kept while it holds the only configuration file.
+pub fn config_dir() -> PathBuf {
+    if let Some(config_dir) = env::var_os("RICAT_CONFIG_DIR") {
+        return PathBuf::from(config_dir);
+    }
+    let platform = ProjectDirs::from("", "", "ricat").map(|dirs| dirs.config_dir().to_path_buf());
+    let legacy = BaseDirs::new().map(|dirs| dirs.home_dir().join(".config/ricat"));
+    choose_config_dir(platform, legacy).expect("Failed to find home directory")
+}
+
+/// The platform directory, unless only the legacy one holds a configuration file
+pub fn choose_config_dir(platform: Option<PathBuf>, legacy: Option<PathBuf>) -> Option<PathBuf> {
+    match (platform, legacy) {
+        (Some(platform), Some(legacy))
+            if !platform.join(CONFIG_FILE_NAME).exists() && legacy.join(CONFIG_FILE_NAME).exists() =>
+        {
+            Some(legacy)
+        }
+        (Some(platform), _) => Some(platform),
+        (None, legacy) => legacy,
+    }
+}
+
+/// Path of the configuration file, `ricat_cfg.toml` in the configuration directory
+pub fn config_file() -> PathBuf {
+    config_dir().join(CONFIG_FILE_NAME)
+}
+
+/// Loading the config from ricat_cfg.toml in the configuration directory. The default file is created there
+/// on the first run; a home directory ricat cannot write to (sandboxes, read-only systems) is left as it is.
+/// A file which cannot be read or holds a mistake is an error, a typo must not silently leave a setting out.
+pub fn load_config() -> Result<RicatConfig, RicatError> {
+    let config_file = config_file();
+    if env::var_os("RICAT_CONFIG_DIR").is_none() && !config_file.exists() {
+        let _ = init_config(&config_file);
+    }
+
+    if !config_file.exists() {
+        return Ok(RicatConfig::default());
+    }
+    read_config(&config_file).map_err(|error| match error {
+        RicatError::ConfigReadError(message) => RicatError::ConfigReadError(format!(
+            "{}\nfix the file (`ricat config check` validates it), or run with --no-config to ignore it",
+            message
+        )),
+        error => error,
+    })
+}
+
+/// The `RICAT_*` variables of the environment, those which are not valid Unicode left out
+pub fn ricat_env_vars() -> Vec<(String, String)> {
+    env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(name, _)| name.starts_with("RICAT_"))
+        .collect()
+}
+
+/// Applies the settings of `RICAT_*` environment variables, named after the flags: `RICAT_NUMBERS=1`,
+/// `RICAT_PAGES=never`, `RICAT_NUMBER_WIDTH=6`. They override the configuration file, and the flags
+/// override them. Switches take `1`/`0`, `true`/`false`, `yes`/`no` or `on`/`off`, lists are separated by commas.
+pub fn apply_env_overrides(
+    config: &mut RicatConfig,
+    variables: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), RicatError> {
+    for (name, value) in variables {
+        let Some(setting) = name.strip_prefix("RICAT_") else {
+            continue;
+        };
+        let invalid = |expected: &str| {
+            RicatError::EnvironmentError(format!("{}={} is not {}", name, value, expected))
+        };
+        match setting {
+            // where the configuration is and the profile, chosen before the settings are applied
+            "CONFIG_DIR" | "PROFILE" => {}
+            "NUMBERS" => {
+                config.number_feature = parse_switch(&value).ok_or_else(|| invalid(SWITCH))?
+            }
+            "DOLLAR" => {
+                config.dollar_sign_feature = parse_switch(&value).ok_or_else(|| invalid(SWITCH))?
+            }
+            "TABS" => config.tabs_feature = parse_switch(&value).ok_or_else(|| invalid(SWITCH))?,
+            "SQUEEZE_BLANK" => {
+                config.compress_empty_line_feature =
+                    parse_switch(&value).ok_or_else(|| invalid(SWITCH))?
+            }
+            "FEATURES" => config.features = parse_list(&value),
+            "FEATURE_ORDER" => config.feature_order = parse_list(&value),
+            "PAGES" => {
+                config.pagination_mode =
+                    Some(parse_value_enum(&value).map_err(|values| invalid(&values))?)
+            }
+            "PAGER_LONG_LINES" => {
+                config.pager_long_lines =
+                    Some(parse_value_enum(&value).map_err(|values| invalid(&values))?)
+            }
+            "NUMBER_WIDTH" => {
+                config.numbering.width = Some(parse_number(&value).ok_or_else(|| invalid(NUMBER))?)
+            }
+            "NUMBER_ALIGN" => {
+                config.numbering.align =
+                    Some(parse_value_enum(&value).map_err(|values| invalid(&values))?)
+            }
+            "NUMBER_SEPARATOR" => config.numbering.separator = Some(value.clone()),
+            "NUMBER_START" => {
+                config.numbering.start = Some(parse_number(&value).ok_or_else(|| invalid(NUMBER))?)
+            }
+            "TAB_WIDTH" => {
+                config.tabs.width = Some(parse_number(&value).ok_or_else(|| invalid(NUMBER))?)
+            }
+            "MAX_BLANK" => {
+                config.squeeze.max_blank = Some(parse_number(&value).ok_or_else(|| invalid(NUMBER))?)
+            }
+            "THEME" => {
+                config.theme.name =
+                    Some(parse_value_enum(&value).map_err(|values| invalid(&values))?)
+            }
+            "IO_BACKEND" => {
+                config.io_backend =
+                    Some(parse_value_enum(&value).map_err(|values| invalid(&values))?)
+            }
+            "MMAP_THRESHOLD" => {
+                config.mmap_threshold = Some(parse_number(&value).ok_or_else(|| invalid(NUMBER))?)
+            }
+            "MAX_MEMORY" => {
+                config.max_memory = Some(
+                    parse_byte_size(value.trim())
+                        .map_err(|_| invalid("a size like 4096, 64M or 1G"))?,
+                )
+            }
+            _ => eprintln!(
+                "ricat: warning: {} is not a setting of ricat, it is ignored",
+                name
+            ),
+        }
+    }
+    Ok(())
+}
+
+const SWITCH: &str = "a switch, use 1 or 0";
+const NUMBER: &str = "a number";
+
+/// Parses a switch of an environment variable
+fn parse_switch(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" | "" => Some(false),
+        _ => None,
+    }
 }
 
-/// Loading the config from $HOME/.config/ricat/ricat_cfg.toml
-pub fn load_config() -> RicatConfig {
-    let config_dir = env::var("RICAT_CONFIG_DIR").unwrap_or_else(|_| {
-        let home_dir = dirs::home_dir().expect("Failed to find home directory");
-        let default_config_dir = home_dir.join(".config/ricat");
-        create_dir_all(&default_config_dir).expect("Failed to create config directory");
-        default_config_dir.to_str().unwrap().to_string()
-    });
+fn parse_number<T: FromStr>(value: &str) -> Option<T> {
+    value.trim().parse().ok()
+}
 
-    let config_file = PathBuf::from(config_dir).join("ricat_cfg.toml");
+/// Parses a comma separated list of names, leaving out empty ones
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
 
-    if config_file.exists() {
-        if let Ok(config_content) = read_to_string(config_file) {
-            if let Ok(config) = toml::from_str(&config_content) {
-                return config;
+/// Parses a value like the flag taking it does, or tells the values it takes
+fn parse_value_enum<T: ValueEnum>(value: &str) -> Result<T, String> {
+    T::from_str(value.trim(), true).map_err(|_| {
+        let values: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|variant| variant.to_possible_value())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        format!("one of {}", values.join(", "))
+    })
+}
+
+/// Reads the configuration file, failing when it cannot be read, is not valid TOML or holds a key ricat
+/// does not know. The error names the line and the key of the mistake.
+pub fn read_config(path: &Path) -> Result<RicatConfig, RicatError> {
+    let config_content = read_to_string(path)
+        .map_err(|error| RicatError::ConfigReadError(format!("{}: {}", path.display(), error)))?;
+    toml::from_str(&config_content)
+        .map_err(|error| RicatError::ConfigReadError(describe_error(path, &config_content, &error)))
+}
+
+/// The message of the error with the file, line, column and key it points to:
+/// `ricat_cfg.toml:4:1: \`numbering.wdth\`: unknown field ...`
+fn describe_error(path: &Path, text: &str, error: &toml::de::Error) -> String {
+    let message = error
+        .message()
+        .trim()
+        .lines()
+        .collect::<Vec<_>>()
+        .join(", ");
+    let Some(span) = error.span() else {
+        return format!("{}: {}", path.display(), message);
+    };
+    let offset = span.start.min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line = text[..offset].matches('\n').count() + 1;
+    let column = text[line_start..offset].chars().count() + 1;
+    match key_at(text, line_start) {
+        Some(key) => format!(
+            "{}:{}:{}: `{}`: {}",
+            path.display(),
+            line,
+            column,
+            key,
+            message
+        ),
+        None => format!("{}:{}:{}: {}", path.display(), line, column, message),
+    }
+}
+
+/// Full name of the key set on the line starting at `line_start`, with the table it is in, or the name
+/// of the table of a `[table]` line
+fn key_at(text: &str, line_start: usize) -> Option<String> {
+    let table_name = |line: &str| {
+        let name = line.trim().trim_start_matches('[').trim_end_matches(']');
+        name.split('.')
+            .map(|part| part.trim().trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+    let line = text[line_start..].lines().next()?.trim();
+    if line.starts_with('[') {
+        return Some(table_name(line));
+    }
+    let (key, _) = line.split_once('=')?;
+    let key = table_name(key);
+    let table = text[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('['))
+        .map(table_name);
+    Some(match table {
+        Some(table) => format!("{}.{}", table, key),
+        None => key,
+    })
+}
+
+/// Configuration file written by `ricat config init`: the feature presets, and every other setting
+/// commented out with an example value
+pub const DEFAULT_CONFIG: &str = r##"# ricat configuration, flags given on the command line take precedence
+
+# features enabled on every run
+number_feature = false
+dollar_sign_feature = false
+tabs_feature = false
+compress_empty_line_feature = false
+
+# features enabled by name, like `--features`, and their order, like `--feature-order`
+# features = ["numbering", "squeeze"]
+# feature_order = ["numbering", "search"]
+
+# when the pager is used: "always", "auto" or "never"
+# pagination_mode = "auto"
+# lines wider than the terminal in the pager: "wrap" or "scroll"
+# pager_long_lines = "wrap"
+
+# how plain copies read the files: "auto", "read", "mmap" or "uring", like `--io-backend`
+# io_backend = "auto"
+# size in bytes from which `auto` maps files to memory, like `--mmap-threshold`
+# mmap_threshold = 1048576
+# bytes held in memory at most by the modes needing the whole input, like `--max-memory`
+# max_memory = 67108864
+
+# layout of the line numbers, like `--number-start`, `--number-width`, `--number-align`
+# ("left", "right" or "zero") and `--number-separator`
+# [numbering]
+# start = 1
+# width = 6
+# align = "right"
+# separator = " "
+
+# blank lines `-s` keeps in a row, like `--max-blank`
+# [squeeze]
+# max_blank = 1
+
+# tab stops of `--expand-tabs` and `--unexpand` given without a width
+# [tabs]
+# width = 8
+
+# colors on a terminal: the built-in theme, "default", "solarized", "high-contrast" or "monochrome", like
+# `--theme`, and colors replacing its own. A color is made of words like "bold red", "bright-blue",
+# "#268bd2", "on-yellow" or "none"; the keys are also added, removed, modified, heading, hunk, dimmed
+# and trailing_whitespace
+# [theme]
+# name = "default"
+# line_number = "gray"
+# search_match = "bold red"
+# log_error = "red"
+# log_warn = "yellow"
+# log_info = "green"
+# log_debug = "gray"
+# pager_status = "reverse"
+
+# profiles, applied over the settings above with `--profile NAME` or `RICAT_PROFILE=NAME`
+# [profiles.logs]
+# features = ["squeeze"]
+# pagination_mode = "always"
+#
+# [profiles.code]
+# number_feature = true
+# tabs.width = 4
+"##;
+
+/// `ricat config` actions
+#[derive(clap::Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Create the configuration file with the default settings and the others commented out
+    Init {
+        /// replace the configuration file when there is one
+        #[clap(long)]
+        force: bool,
+    },
+    /// Print the configuration in effect: the file with the defaults of the settings it leaves out
+    Show,
+    /// Print the path of the configuration file
+    Path,
+    /// Open the configuration file in $VISUAL or $EDITOR, creating it first when there is none
+    Edit,
+    /// Validate the configuration file and the RICAT_* variables, naming the line and key of a mistake
+    Check,
+}
+
+/// Runs `ricat config ACTION`, writing what it prints to the writer
+pub fn run_config_command(action: &ConfigAction, writer: &mut dyn Write) -> Result<(), RicatError> {
+    let path = config_file();
+    let write_error = |error: std::io::Error| {
+        RicatError::LineWriteError(format!("Error writing the configuration: {}", error))
+    };
+    match action {
+        ConfigAction::Init { force } => {
+            if path.exists() && !force {
+                return Err(RicatError::ConfigWriteError(format!(
+                    "{} already exists, `ricat config init --force` replaces it",
+                    path.display()
+                )));
             }
+            init_config(&path)?;
+            writeln!(writer, "created {}", path.display()).map_err(write_error)
         }
+        ConfigAction::Show => {
+            let (source, config) = if path.exists() {
+                (path.display().to_string(), read_config(&path)?)
+            } else {
+                ("no configuration file, the defaults".to_string(), RicatConfig::default())
+            };
+            let config = toml::to_string(&config)
+                .map_err(|error| RicatError::ConfigReadError(error.to_string()))?;
+            write!(writer, "# {}\n{}", source, config).map_err(write_error)
+        }
+        ConfigAction::Path => writeln!(writer, "{}", path.display()).map_err(write_error),
+        ConfigAction::Edit => {
+            if !path.exists() {
+                init_config(&path)?;
+            }
+            edit_config(&path)
+        }
+        ConfigAction::Check => check_config(&path, ricat_env_vars(), writer),
+    }
+}
+
+/// Reads the configuration file like a run does and applies the variables and profile of the environment
+/// over it, failing on the first mistake. A missing file is valid, the defaults apply.
+pub fn check_config(
+    path: &Path,
+    variables: Vec<(String, String)>,
+    writer: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let (source, mut config) = if path.exists() {
+        (path.display().to_string(), read_config(path)?)
+    } else {
+        (
+            format!("no configuration file at {}, the defaults", path.display()),
+            RicatConfig::default(),
+        )
+    };
+    if let Some((name, _)) = config
+        .profiles
+        .iter()
+        .find(|(_, profile)| !profile.profiles.is_empty())
+    {
+        return Err(RicatError::ConfigReadError(format!(
+            "{}: the profile `{}` holds profiles of its own, they cannot be nested",
+            source, name
+        )));
     }
+    if let Some((_, profile)) = variables.iter().find(|(name, _)| name == "RICAT_PROFILE") {
+        config.select_profile(profile)?;
+    }
+    apply_env_overrides(&mut config, variables)?;
+    writeln!(writer, "{}: ok", source).map_err(|error| {
+        RicatError::LineWriteError(format!("Error writing the configuration check: {}", error))
+    })
+}
+
+/// Writes the default configuration file, creating its directory
+fn init_config(path: &Path) -> Result<(), RicatError> {
+    let write_error = |error: std::io::Error| {
+        RicatError::ConfigWriteError(format!("{}: {}", path.display(), error))
+    };
+    if let Some(directory) = path.parent() {
+        create_dir_all(directory).map_err(write_error)?;
+    }
+    write(path, DEFAULT_CONFIG).map_err(write_error)
+}
 
-    RicatConfig::default()
+/// Opens the file in the editor of $VISUAL or $EDITOR, which may carry arguments (`code --wait`), and
+/// waits for it to close
+fn edit_config(path: &Path) -> Result<(), RicatError> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let status = process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|error| {
+            RicatError::ConfigWriteError(format!("Failed to start the editor `{}`: {}", editor, error))
+        })?;
+    if !status.success() {
+        return Err(RicatError::ConfigWriteError(format!(
+            "the editor `{}` exited with {}",
+            editor, status
+        )));
+    }
+    Ok(())
 }
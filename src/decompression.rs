@@ -0,0 +1,113 @@
+use std::io::{BufRead, BufReader, Cursor, ErrorKind, Read};
+
+use crate::errors::RicatError;
+
+/// Compression formats recognized from the magic bytes at the start of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+/// Magic of the first block of a bzip2 stream (the digits of pi), after `BZh` and the block size
+const BZIP2_BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+
+/// Magic of the end of a bzip2 stream (the digits of the square root of pi), in place of the first block of an empty one
+const BZIP2_END_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+
+impl CompressionFormat {
+    /// Detects the compression format from the first bytes of the input
+    pub fn detect(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(CompressionFormat::Gzip)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(CompressionFormat::Zstd)
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(CompressionFormat::Xz)
+        } else if is_bzip2(magic) {
+            Some(CompressionFormat::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the cargo feature providing the codec
+    pub fn feature_name(&self) -> &'static str {
+        match self {
+            CompressionFormat::Gzip => "gzip",
+            CompressionFormat::Zstd => "zstd",
+            CompressionFormat::Xz => "xz",
+            CompressionFormat::Bzip2 => "bzip2",
+        }
+    }
+}
+
+/// `BZh`, the block size from `1` to `9`, then the magic of a block or of the end of the stream:
+/// text starting with `BZh` is not taken for bzip2
+fn is_bzip2(magic: &[u8]) -> bool {
+    match magic {
+        [b'B', b'Z', b'h', b'1'..=b'9', block @ ..] => {
+            block.starts_with(&BZIP2_BLOCK_MAGIC) || block.starts_with(&BZIP2_END_MAGIC)
+        }
+        _ => false,
+    }
+}
+
+/// Wraps the reader in a decoder when the input is compressed, like `zcat` does.
+/// Uncompressed input is returned unchanged, and so is input which only starts like compressed data:
+/// when the start of it does not decode, it is read as plain text.
+pub fn decompress(reader: Box<dyn Read>, name: &str) -> Result<Box<dyn Read>, RicatError> {
+    let mut reader = BufReader::new(reader);
+    let head = reader.fill_buf()?;
+    let format = CompressionFormat::detect(head).filter(|&format| starts_decoding(format, head));
+
+    match format {
+        None => Ok(Box::new(reader)),
+        Some(format) => decoder(format, reader)
+            .map_err(|error| RicatError::DecompressionError(format!("{}: {}", name, error))),
+    }
+}
+
+/// Whether the start of the input decodes in the format, running out of input is fine.
+/// Without the codec it is left to `decoder` to tell.
+fn starts_decoding(format: CompressionFormat, head: &[u8]) -> bool {
+    let Ok(mut probe) = decoder(format, Cursor::new(head.to_vec())) else {
+        return true;
+    };
+    let mut byte = [0; 1];
+    match probe.read_exact(&mut byte) {
+        Ok(()) => true,
+        Err(error) => error.kind() == ErrorKind::UnexpectedEof,
+    }
+}
+
+/// Decoder for the given format, fails when the codec is not compiled in
+#[cfg_attr(
+    not(any(feature = "gzip", feature = "zstd", feature = "xz", feature = "bzip2")),
+    allow(unused_variables)
+)]
+fn decoder<R: BufRead + 'static>(
+    format: CompressionFormat,
+    reader: R,
+) -> Result<Box<dyn Read>, String> {
+    match format {
+        #[cfg(feature = "gzip")]
+        CompressionFormat::Gzip => Ok(Box::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        CompressionFormat::Zstd => zstd::stream::read::Decoder::with_buffer(reader)
+            .map(|decoder| Box::new(decoder) as Box<dyn Read>)
+            .map_err(|error| error.to_string()),
+        #[cfg(feature = "xz")]
+        CompressionFormat::Xz => Ok(Box::new(xz2::bufread::XzDecoder::new_multi_decoder(reader))),
+        #[cfg(feature = "bzip2")]
+        CompressionFormat::Bzip2 => Ok(Box::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+        #[allow(unreachable_patterns)]
+        format => Err(format!(
+            "input is {:?} compressed, but ricat was built without the `{}` feature",
+            format,
+            format.feature_name()
+        )),
+    }
+}
@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+
+use similar::{ChangeTag, DiffOp, TextDiff};
+
+use crate::ansi::{visible_len, AnsiLine};
+use crate::theme::{Style, Theme};
+use crate::{ExpandTabs, LineTextFeature};
+
+/// Default number of unchanged lines shown around each change, like `diff -u`
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Unified diff of two texts, like `diff -u`, one entry per line.
+/// With a theme the lines are colored the way `git diff` does with the default one: bold file headers,
+/// cyan hunk headers, red removed and green added lines. Equal texts have no diff at all, not even the header.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    names: (&str, &str),
+    context: usize,
+    theme: Option<&Theme>,
+) -> Vec<String> {
+    if old == new {
+        return Vec::new();
+    }
+
+    let paint = |style: fn(&Theme) -> &Style, line: String| match theme {
+        Some(theme) => style(theme).paint(&line),
+        None => line,
+    };
+    let mut lines = vec![
+        paint(|theme| &theme.heading, format!("--- {}", names.0)),
+        paint(|theme| &theme.heading, format!("+++ {}", names.1)),
+    ];
+
+    let diff = TextDiff::from_lines(old, new);
+    for hunk in diff.unified_diff().context_radius(context).iter_hunks() {
+        lines.push(paint(|theme| &theme.hunk, hunk.header().to_string()));
+        for change in hunk.iter_changes() {
+            let text = change.value();
+            let line = format!(
+                "{}{}",
+                change.tag(),
+                text.strip_suffix('\n').unwrap_or(text)
+            );
+            lines.push(match change.tag() {
+                ChangeTag::Delete => paint(|theme| &theme.removed, line),
+                ChangeTag::Insert => paint(|theme| &theme.added, line),
+                ChangeTag::Equal => line,
+            });
+            if change.missing_newline() {
+                lines.push("\\ No newline at end of file".to_string());
+            }
+        }
+    }
+    lines
+}
+
+/// One row of the side by side view, the lines of both sides which belong together
+enum Row<'a> {
+    Equal(&'a str, &'a str),
+    Changed(&'a str, &'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Two texts in parallel columns fitting `width`, with their names on top, like `diff -y`.
+/// Equal lines are separated by `│`, changed ones by `|`, and lines only on the left or right by `<` or `>`.
+/// With a theme the differing lines are highlighted, red on the left and green on the right with the default one.
+pub fn side_by_side(
+    old: &str,
+    new: &str,
+    names: (&str, &str),
+    width: usize,
+    theme: Option<&Theme>,
+) -> Vec<String> {
+    let mut expand_tabs = ExpandTabs::default();
+    let old_lines: Vec<String> = old
+        .lines()
+        .filter_map(|line| expand_tabs.apply_feature(line).map(Cow::into_owned))
+        .collect();
+    let new_lines: Vec<String> = new
+        .lines()
+        .filter_map(|line| expand_tabs.apply_feature(line).map(Cow::into_owned))
+        .collect();
+
+    let mut rows = Vec::new();
+    for operation in TextDiff::from_lines(old, new).ops() {
+        match *operation {
+            DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } => rows.extend(
+                (0..len).map(|i| Row::Equal(&old_lines[old_index + i], &new_lines[new_index + i])),
+            ),
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => rows.extend(
+                old_lines[old_index..old_index + old_len]
+                    .iter()
+                    .map(|line| Row::Removed(line)),
+            ),
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => rows.extend(
+                new_lines[new_index..new_index + new_len]
+                    .iter()
+                    .map(|line| Row::Added(line)),
+            ),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                for i in 0..old_len.max(new_len) {
+                    rows.push(match (i < old_len, i < new_len) {
+                        (true, true) => {
+                            Row::Changed(&old_lines[old_index + i], &new_lines[new_index + i])
+                        }
+                        (true, false) => Row::Removed(&old_lines[old_index + i]),
+                        _ => Row::Added(&new_lines[new_index + i]),
+                    });
+                }
+            }
+        }
+    }
+
+    let column_width = (width.saturating_sub(3) / 2).max(1);
+    // a cell cut to the column, padded when something follows it
+    let cell = |text: &str, style: Option<&Style>, padded: bool| {
+        let text = AnsiLine::parse(text).truncate(column_width);
+        let padding = if padded {
+            " ".repeat(column_width.saturating_sub(visible_len(&text)))
+        } else {
+            String::new()
+        };
+        match style {
+            Some(style) => format!("{}{}", style.paint(&text), padding),
+            None => format!("{}{}", text, padding),
+        }
+    };
+    let (red, green, bold) = (
+        theme.map(|theme| &theme.removed),
+        theme.map(|theme| &theme.added),
+        theme.map(|theme| &theme.heading),
+    );
+
+    let mut lines = vec![format!(
+        "{} │ {}",
+        cell(names.0, bold, true),
+        cell(names.1, bold, false)
+    )];
+    lines.extend(rows.into_iter().map(|row| {
+        let (left, marker, right) = match row {
+            Row::Equal(left, right) => (cell(left, None, true), '│', cell(right, None, false)),
+            Row::Changed(left, right) => (cell(left, red, true), '|', cell(right, green, false)),
+            Row::Removed(left) => (cell(left, red, true), '<', String::new()),
+            Row::Added(right) => (cell("", None, true), '>', cell(right, green, false)),
+        };
+        format!("{} {} {}", left, marker, right)
+            .trim_end()
+            .to_string()
+    }));
+    lines
+}
@@ -1,22 +1,456 @@
 use base64::engine::general_purpose;
 use base64::prelude::*;
+use data_encoding::BASE32;
 
-/// DataEncoding Trait : for Encoding and Decoding Files
+/// DataEncoding Trait : for Encoding and Decoding Files.
+/// Codecs work on bytes, so binary data round-trips through the byte features; `encode`/`decode` are the text adapters.
 pub trait DataEncoding {
-    fn encode(data: &str) -> Option<String>;
-    fn decode(text: &str) -> Option<String>;
+    /// Encodes the bytes, `None` when the codec cannot encode them
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>>;
+    /// Decodes the bytes, `None` when they are not valid for the codec
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>>;
+
+    /// Encodes a line of text, `None` when the result is not UTF-8 text
+    fn encode(data: &str) -> Option<String> {
+        String::from_utf8(Self::encode_bytes(data.as_bytes())?).ok()
+    }
+
+    /// Decodes a line of text, `None` when the decoded bytes are not UTF-8 text
+    fn decode(text: &str) -> Option<String> {
+        String::from_utf8(Self::decode_bytes(text.as_bytes())?).ok()
+    }
+}
+
+/// Codec selected by name with `--encode CODEC` / `--decode CODEC`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Base64,
+    Base32,
+    Hex,
+    /// Adobe Ascii85, also accepted as `base85`
+    #[value(alias = "base85")]
+    Ascii85,
+    /// ZeroMQ Z85
+    Z85,
+    /// HTML entities
+    Html,
+    /// JSON string escapes
+    Json,
+}
+
+impl Codec {
+    pub fn encode_bytes(self, data: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Codec::Base64 => Base64::encode_bytes(data),
+            Codec::Base32 => Base32::encode_bytes(data),
+            Codec::Hex => Hex::encode_bytes(data),
+            Codec::Ascii85 => Ascii85::encode_bytes(data),
+            Codec::Z85 => Z85::encode_bytes(data),
+            Codec::Html => Html::encode_bytes(data),
+            Codec::Json => Json::encode_bytes(data),
+        }
+    }
+
+    pub fn decode_bytes(self, data: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Codec::Base64 => Base64::decode_bytes(data),
+            Codec::Base32 => Base32::decode_bytes(data),
+            Codec::Hex => Hex::decode_bytes(data),
+            Codec::Ascii85 => Ascii85::decode_bytes(data),
+            Codec::Z85 => Z85::decode_bytes(data),
+            Codec::Html => Html::decode_bytes(data),
+            Codec::Json => Json::decode_bytes(data),
+        }
+    }
+
+    pub fn encode(self, data: &str) -> Option<String> {
+        String::from_utf8(self.encode_bytes(data.as_bytes())?).ok()
+    }
+
+    pub fn decode(self, text: &str) -> Option<String> {
+        String::from_utf8(self.decode_bytes(text.as_bytes())?).ok()
+    }
+}
+
+/// Encodes the data with every codec in turn, the output of one codec is the input of the next
+pub fn encode_chain(codecs: &[Codec], data: &[u8]) -> Option<Vec<u8>> {
+    codecs
+        .iter()
+        .try_fold(data.to_vec(), |data, codec| codec.encode_bytes(&data))
+}
+
+/// Decodes the data with every codec in turn, in the given order: `hex,base64` undoes `base64,hex`
+pub fn decode_chain(codecs: &[Codec], data: &[u8]) -> Option<Vec<u8>> {
+    codecs
+        .iter()
+        .try_fold(data.to_vec(), |data, codec| codec.decode_bytes(&data))
 }
 
 /// Base64 Encoding-Decoding
 pub struct Base64;
 
 impl DataEncoding for Base64 {
-    fn encode(data: &str) -> Option<String> {
-        Some(general_purpose::STANDARD.encode(data.as_bytes()))
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        Some(general_purpose::STANDARD.encode(data).into_bytes())
     }
 
-    fn decode(text: &str) -> Option<String> {
-        let decoded_message = general_purpose::STANDARD.decode(text).ok()?;
-        String::from_utf8(decoded_message).ok()
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        general_purpose::STANDARD.decode(data).ok()
+    }
+}
+
+impl Base64 {
+    /// Width of the encoded lines of `encode_stream`, the default of the `base64` tool
+    pub const LINE_WIDTH: usize = 76;
+
+    /// Encodes the whole input as one byte stream, wrapped into lines of `LINE_WIDTH` characters like `base64` does
+    pub fn encode_stream(data: &[u8]) -> String {
+        let encoded = general_purpose::STANDARD.encode(data);
+        let mut wrapped =
+            String::with_capacity(encoded.len() + encoded.len() / Self::LINE_WIDTH + 1);
+        for line in encoded.as_bytes().chunks(Self::LINE_WIDTH) {
+            // base64 output is plain ASCII, every byte is one character
+            wrapped.extend(line.iter().map(|&byte| byte as char));
+            wrapped.push('\n');
+        }
+        wrapped
+    }
+
+    /// Decodes a whole Base64 stream, line breaks and other whitespace are ignored.
+    /// The decoded bytes are returned as they are, they do not need to be text.
+    pub fn decode_stream(text: &[u8]) -> Option<Vec<u8>> {
+        let encoded: Vec<u8> = text
+            .iter()
+            .copied()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        Self::decode_bytes(&encoded)
+    }
+}
+
+/// Base32 Encoding-Decoding (RFC 4648 alphabet, with padding)
+pub struct Base32;
+
+impl DataEncoding for Base32 {
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        Some(BASE32.encode(data).into_bytes())
+    }
+
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        BASE32.decode(data).ok()
+    }
+}
+
+/// Hex Encoding-Decoding: every byte as two lowercase hex digits
+pub struct Hex;
+
+impl DataEncoding for Hex {
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let encoded: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Some(encoded.into_bytes())
+    }
+
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        // whitespace between the bytes (`48 65 6c`) is allowed
+        let digits: Vec<u8> = data
+            .iter()
+            .copied()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        if !digits.len().is_multiple_of(2) {
+            return None;
+        }
+
+        digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+            .collect()
+    }
+}
+
+/// Ascii85 Encoding-Decoding, the Adobe variant used in PDF and PostScript streams: `<~` ... `~>`
+pub struct Ascii85;
+
+impl DataEncoding for Ascii85 {
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let mut encoded = String::from("<~");
+        for group in data.chunks(4) {
+            let mut padded = [0_u8; 4];
+            padded[..group.len()].copy_from_slice(group);
+            let value = u32::from_be_bytes(padded);
+
+            // a whole group of zero bytes is abbreviated as `z`
+            if value == 0 && group.len() == 4 {
+                encoded.push('z');
+                continue;
+            }
+            let digits = base85_digits(value);
+            // a partial group of n bytes needs only n + 1 characters
+            for digit in &digits[..group.len() + 1] {
+                encoded.push((digit + b'!') as char);
+            }
+        }
+        encoded.push_str("~>");
+        Some(encoded.into_bytes())
+    }
+
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let text = std::str::from_utf8(data).ok()?.trim();
+        let text = text.strip_prefix("<~").unwrap_or(text);
+        let text = text.strip_suffix("~>").unwrap_or(text);
+
+        let mut decoded_message = Vec::new();
+        let mut group = Vec::with_capacity(5);
+        for character in text.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+            match character {
+                b'z' if group.is_empty() => decoded_message.extend_from_slice(&[0; 4]),
+                b'!'..=b'u' => {
+                    group.push(character - b'!');
+                    if group.len() == 5 {
+                        decoded_message.extend_from_slice(&base85_value(&group)?.to_be_bytes());
+                        group.clear();
+                    }
+                }
+                _ => return None,
+            }
+        }
+        decode_partial_group(&group, &mut decoded_message)?;
+        Some(decoded_message)
+    }
+}
+
+/// Characters of the Z85 alphabet, in digit order
+const Z85_ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// Z85 Encoding-Decoding, the ZeroMQ variant (used for CURVE keys).
+/// Z85 has no padding: the input length must be a multiple of 4 bytes, the encoded length a multiple of 5.
+pub struct Z85;
+
+impl DataEncoding for Z85 {
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        if !data.len().is_multiple_of(4) {
+            return None;
+        }
+        let encoded = data
+            .chunks(4)
+            .flat_map(|group| {
+                base85_digits(u32::from_be_bytes([group[0], group[1], group[2], group[3]]))
+            })
+            .map(|digit| Z85_ALPHABET[digit as usize])
+            .collect();
+        Some(encoded)
+    }
+
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let text = data.trim_ascii();
+        if !text.len().is_multiple_of(5) {
+            return None;
+        }
+
+        let mut decoded_message = Vec::with_capacity(text.len() / 5 * 4);
+        for group in text.chunks(5) {
+            let digits = group
+                .iter()
+                .map(|character| {
+                    Z85_ALPHABET
+                        .iter()
+                        .position(|letter| letter == character)
+                        .map(|digit| digit as u8)
+                })
+                .collect::<Option<Vec<u8>>>()?;
+            decoded_message.extend_from_slice(&base85_value(&digits)?.to_be_bytes());
+        }
+        Some(decoded_message)
+    }
+}
+
+/// The five base-85 digits of a 32-bit group, most significant first
+fn base85_digits(mut value: u32) -> [u8; 5] {
+    let mut digits = [0_u8; 5];
+    for digit in digits.iter_mut().rev() {
+        *digit = (value % 85) as u8;
+        value /= 85;
+    }
+    digits
+}
+
+/// The 32-bit group of five base-85 digits, `None` when it overflows
+fn base85_value(digits: &[u8]) -> Option<u32> {
+    digits.iter().try_fold(0_u32, |value, &digit| {
+        value.checked_mul(85)?.checked_add(digit as u32)
+    })
+}
+
+/// Decodes a final group of fewer than five digits: it is padded with the highest digit
+/// and yields one byte less than its number of digits
+fn decode_partial_group(group: &[u8], decoded: &mut Vec<u8>) -> Option<()> {
+    match group.len() {
+        0 => Some(()),
+        1 => None,
+        len => {
+            let mut padded = [84; 5];
+            padded[..len].copy_from_slice(group);
+            decoded.extend_from_slice(&base85_value(&padded)?.to_be_bytes()[..len - 1]);
+            Some(())
+        }
+    }
+}
+
+/// HTML entity escaping: `&`, `<`, `>`, `"` and `'` are replaced by entities, unescaping also
+/// understands common named entities and numeric ones (`&#39;`, `&#x27;`). Unknown entities are kept as they are.
+pub struct Html;
+
+/// Named entities recognized when unescaping
+const HTML_ENTITIES: [(&str, &str); 12] = [
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", "\u{a0}"),
+    ("copy", "\u{a9}"),
+    ("reg", "\u{ae}"),
+    ("trade", "\u{2122}"),
+    ("hellip", "\u{2026}"),
+    ("ndash", "\u{2013}"),
+    ("mdash", "\u{2014}"),
+];
+
+impl DataEncoding for Html {
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        // a text codec: the bytes have to be UTF-8
+        let data = std::str::from_utf8(data).ok()?;
+        let mut escaped = String::with_capacity(data.len());
+        for character in data.chars() {
+            match character {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                _ => escaped.push(character),
+            }
+        }
+        Some(escaped.into_bytes())
+    }
+
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let text = std::str::from_utf8(data).ok()?;
+        let mut unescaped = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            unescaped.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let entity = rest[1..].find(';').and_then(|end| {
+                html_entity(&rest[1..end + 1]).map(|character| (character, end + 2))
+            });
+            match entity {
+                Some((character, len)) => {
+                    unescaped.push_str(&character);
+                    rest = &rest[len..];
+                }
+                None => {
+                    unescaped.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        unescaped.push_str(rest);
+        Some(unescaped.into_bytes())
+    }
+}
+
+/// Text of the entity named between `&` and `;`
+fn html_entity(name: &str) -> Option<String> {
+    let code_point = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X"))
+    {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        decimal.parse().ok()
+    } else {
+        return HTML_ENTITIES
+            .iter()
+            .find(|(entity, _)| *entity == name)
+            .map(|(_, text)| text.to_string());
+    };
+    char::from_u32(code_point?).map(String::from)
+}
+
+/// JSON string escaping of the text, without the surrounding quotes. Unescaping expands `\n`, `\"`,
+/// `\uXXXX` (surrogate pairs included) etc. into the characters they stand for, invalid escapes are kept as they are.
+pub struct Json;
+
+impl DataEncoding for Json {
+    fn encode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        // a text codec: the bytes have to be UTF-8
+        let data = std::str::from_utf8(data).ok()?;
+        let mut escaped = String::with_capacity(data.len());
+        for character in data.chars() {
+            match character {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                '\u{8}' => escaped.push_str("\\b"),
+                '\u{c}' => escaped.push_str("\\f"),
+                control if control.is_control() && (control as u32) < 0x20 => {
+                    escaped.push_str(&format!("\\u{:04x}", control as u32))
+                }
+                _ => escaped.push(character),
+            }
+        }
+        Some(escaped.into_bytes())
+    }
+
+    fn decode_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let text = std::str::from_utf8(data).ok()?;
+        let mut unescaped = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('\\') {
+            unescaped.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let (character, len) = match rest.as_bytes().get(1) {
+                Some(b'"') => ('"', 2),
+                Some(b'\\') => ('\\', 2),
+                Some(b'/') => ('/', 2),
+                Some(b'n') => ('\n', 2),
+                Some(b'r') => ('\r', 2),
+                Some(b't') => ('\t', 2),
+                Some(b'b') => ('\u{8}', 2),
+                Some(b'f') => ('\u{c}', 2),
+                Some(b'u') => json_unicode_escape(rest).unwrap_or(('\\', 1)),
+                _ => ('\\', 1),
+            };
+            unescaped.push(character);
+            rest = &rest[len..];
+        }
+        unescaped.push_str(rest);
+        Some(unescaped.into_bytes())
+    }
+}
+
+/// Character of the `\uXXXX` escape at the start of the text, or of a `\uD83D\uDE00` surrogate pair,
+/// along with the length of the escape
+fn json_unicode_escape(text: &str) -> Option<(char, usize)> {
+    let code_unit = |position: usize| {
+        let digits = text.get(position + 2..position + 6)?;
+        if !text[position..].starts_with("\\u")
+            || !digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        u32::from_str_radix(digits, 16).ok()
+    };
+
+    let high = code_unit(0)?;
+    if (0xD800..0xDC00).contains(&high) {
+        let low = code_unit(6).filter(|low| (0xDC00..0xE000).contains(low))?;
+        let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        return char::from_u32(code_point).map(|character| (character, 12));
     }
+    char::from_u32(high).map(|character| (character, 6))
 }
@@ -0,0 +1,132 @@
+use std::io::{Read, Write};
+
+use crate::errors::RicatError;
+
+/// Environment variable holding the passphrase for scripts, instead of the interactive prompt
+pub const PASSPHRASE_VARIABLE: &str = "RICAT_PASSPHRASE";
+
+/// Passphrase from `RICAT_PASSPHRASE`, or asked for on the terminal.
+/// With `confirm` the passphrase has to be typed twice, so a typo does not lock the data away.
+pub fn read_passphrase(confirm: bool) -> Result<String, RicatError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VARIABLE) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = prompt_passphrase("Passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err(RicatError::EncryptionError(
+            "the passphrase must not be empty".to_string(),
+        ));
+    }
+    if confirm && prompt_passphrase("Confirm passphrase: ")? != passphrase {
+        return Err(RicatError::EncryptionError(
+            "the passphrases do not match".to_string(),
+        ));
+    }
+    Ok(passphrase)
+}
+
+/// Reads a passphrase from the terminal without echoing it, the standard input stays free for the data
+#[cfg(feature = "encryption")]
+fn prompt_passphrase(prompt: &str) -> Result<String, RicatError> {
+    rpassword::prompt_password(prompt).map_err(|error| {
+        RicatError::EncryptionError(format!("Failed to read the passphrase: {}", error))
+    })
+}
+
+#[cfg(not(feature = "encryption"))]
+fn prompt_passphrase(_prompt: &str) -> Result<String, RicatError> {
+    Err(missing_feature())
+}
+
+/// Writer encrypting everything written to it with a passphrase (age format), or passing it through unchanged.
+/// `finish` has to be called once all output is written, it writes the last encrypted chunk.
+pub enum EncryptedWriter<W: Write> {
+    Plain(W),
+    #[cfg(feature = "encryption")]
+    Age(age::stream::StreamWriter<W>),
+}
+
+impl<W: Write> EncryptedWriter<W> {
+    /// Wraps the writer in an encryptor when a passphrase is given
+    pub fn new(writer: W, passphrase: Option<String>) -> Result<Self, RicatError> {
+        match passphrase {
+            None => Ok(EncryptedWriter::Plain(writer)),
+            #[cfg(feature = "encryption")]
+            Some(passphrase) => {
+                age::Encryptor::with_user_passphrase(age::secrecy::SecretString::from(passphrase))
+                    .wrap_output(writer)
+                    .map(EncryptedWriter::Age)
+                    .map_err(|error| RicatError::EncryptionError(error.to_string()))
+            }
+            #[cfg(not(feature = "encryption"))]
+            Some(_) => Err(missing_feature()),
+        }
+    }
+
+    /// Ends the encrypted stream and returns the inner writer
+    pub fn finish(self) -> Result<W, RicatError> {
+        match self {
+            EncryptedWriter::Plain(writer) => Ok(writer),
+            #[cfg(feature = "encryption")]
+            EncryptedWriter::Age(writer) => writer
+                .finish()
+                .map_err(|error| RicatError::EncryptionError(error.to_string())),
+        }
+    }
+}
+
+impl<W: Write> Write for EncryptedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            EncryptedWriter::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "encryption")]
+            EncryptedWriter::Age(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            EncryptedWriter::Plain(writer) => writer.flush(),
+            #[cfg(feature = "encryption")]
+            EncryptedWriter::Age(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Wraps the reader of a passphrase-encrypted age file in a decryptor.
+/// The whole input is authenticated chunk by chunk, tampered data fails with an error instead of yielding garbage.
+#[cfg(feature = "encryption")]
+pub fn decrypt(
+    reader: Box<dyn Read>,
+    passphrase: &str,
+    name: &str,
+) -> Result<Box<dyn Read>, RicatError> {
+    let error =
+        |error: age::DecryptError| RicatError::EncryptionError(format!("{}: {}", name, error));
+    let identity =
+        age::scrypt::Identity::new(age::secrecy::SecretString::from(passphrase.to_string()));
+
+    let decryptor = age::Decryptor::new(reader).map_err(error)?;
+    let reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .map_err(error)?;
+    Ok(Box::new(reader))
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn decrypt(
+    _reader: Box<dyn Read>,
+    _passphrase: &str,
+    _name: &str,
+) -> Result<Box<dyn Read>, RicatError> {
+    Err(missing_feature())
+}
+
+#[cfg(not(feature = "encryption"))]
+fn missing_feature() -> RicatError {
+    RicatError::EncryptionError(
+        "ricat was built without the `encryption` feature, reinstall with `cargo install ricat --features encryption`"
+            .to_string(),
+    )
+}
@@ -18,6 +18,13 @@ pub enum RicatError {
     #[error("Failed to open file: {0}")]
     FileOpenError(String),
 
+    /// Represents an input which is the file the output goes to.
+    ///
+    /// This error variant is used, like GNU cat's "input file is output file", instead of reading a file
+    /// which would grow with every line written, or be truncated before being read.
+    #[error("{0}: input file is output file")]
+    InputIsOutputError(String),
+
     /// Represents an error that occurs during the processing of a line.
     ///
     /// This error variant is used when there is an error while processing a line of text.
@@ -130,10 +137,143 @@ pub enum RicatError {
     #[error("Error writing data to writer via Memory Mapped IO: {0}")]
     MemoryMapWriteError(String),
 
+    /// Represents an error that occurs when expanding the file arguments.
+    ///
+    /// This error variant is used when a glob pattern is invalid, matches no file, or a directory cannot be walked.
+    /// It includes a string message providing more details about the error.
+    #[error("Error expanding input paths: {0}")]
+    PathExpansionError(String),
+
+    /// Represents an error that occurs when reading an `http://` or `https://` file argument.
+    ///
+    /// This error variant is used when the request fails, or when ricat is built without the `http` feature.
+    /// It includes a string message providing more details about the error.
+    #[error("Error reading URL: {0}")]
+    UrlReadError(String),
+
+    /// Represents an error that occurs when reading the clipboard with `--paste`.
+    ///
+    /// This error variant is used when the clipboard is unavailable or empty, or when ricat is built without the `clipboard` feature.
+    /// It includes a string message providing more details about the error.
+    #[error("Error reading clipboard: {0}")]
+    ClipboardError(String),
+
+    /// Represents an error that occurs when listening on a socket for input.
+    ///
+    /// This error variant is used when the socket cannot be bound, e.g. because the address is already in use.
+    /// It includes a string message providing more details about the error.
+    #[error("Error listening on socket: {0}")]
+    SocketError(String),
+
+    /// Represents an error that occurs when decompressing a compressed input.
+    ///
+    /// This error variant is used when the compressed data is invalid, or the codec is not compiled in.
+    /// It includes a string message providing more details about the error.
+    #[error("Error decompressing input: {0}")]
+    DecompressionError(String),
+
+    /// Represents an error that occurs when reading a member of a tar or zip archive.
+    ///
+    /// This error variant is used when the archive is invalid, the member does not exist, or the `archive` feature is missing.
+    /// It includes a string message providing more details about the error.
+    #[error("Error reading archive member: {0}")]
+    ArchiveError(String),
+
+    /// Represents an error that occurs when opening the output file.
+    ///
+    /// This error variant is used when the file given with `-o` or `--tee` cannot be created.
+    /// It includes a string message providing more details about the error.
+    #[error("Error opening output file: {0}")]
+    OutputFileError(String),
+
+    /// Represents an Error when compressing the output
+    ///
+    /// This error variant is used when the `--compress` encoder fails or its codec is not compiled in.
+    /// It includes a string message providing more details about the error.
+    #[error("Error compressing output: {0}")]
+    CompressionError(String),
+
+    /// Represents an Error when decoding the whole input with `--whole-file`
+    ///
+    /// This error variant is used when the input is not valid for the codec, e.g. invalid Base64.
+    /// It includes a string message providing more details about the error.
+    #[error("Error decoding input: {0}")]
+    DecodingError(String),
+
+    /// Represents an Error when encrypting or decrypting with `--encrypt`/`--decrypt`
+    ///
+    /// This error variant is used for a wrong passphrase, tampered input, or a missing `encryption` feature.
+    /// It includes a string message providing more details about the error.
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    /// Represents invalid UTF-8 found by `--validate-utf8`
+    ///
+    /// This error variant is used to fail once all inputs are scanned and at least one invalid sequence was reported.
+    /// It includes a string message providing more details about the error.
+    #[error("Invalid UTF-8: {0}")]
+    InvalidUtf8Error(String),
+
+    /// Represents an Error when parsing the input with `--yaml` or `--toml`
+    ///
+    /// This error variant is used when the input is not a valid document of the format.
+    /// It includes the input name and the line and column of the error, when known.
+    #[error("Parse error: {0}")]
+    StructuredParseError(String),
+
+    /// Represents an Error when computing the `--git` change markers
+    ///
+    /// This error variant is used when the repository cannot be read, or ricat is built without the `git` feature.
+    /// It includes a string message providing more details about the error.
+    #[error("Git error: {0}")]
+    GitError(String),
+
+    /// Represents an Error when comparing two inputs with `--diff` or `--side-by-side`
+    ///
+    /// This error variant is used when not exactly two inputs are given to compare.
+    /// It includes a string message providing more details about the error.
+    #[error("Comparison error: {0}")]
+    ComparisonError(String),
+
+    /// Represents an Error of the I/O backend chosen with `--io-backend`
+    ///
+    /// This error variant is used when the backend is not compiled in or not supported on the platform.
+    /// It includes a string message providing more details about the error.
+    #[error("I/O backend error: {0}")]
+    IoBackendError(String),
+
+    /// Represents an Error when more than `--max-memory` bytes would be held in memory
+    ///
+    /// This error variant is used when a mode needing the whole input meets an input larger than the limit.
+    /// It includes a string message providing more details about the error.
+    #[error("Memory limit exceeded: {0}")]
+    MemoryLimitError(String),
+
+    /// Represents the inputs ricat skipped because they could not be read
+    ///
+    /// This error variant is used at the end of a run which carried on past such inputs, each already reported.
+    /// It includes the number of inputs which failed.
+    #[error("{0} input(s) could not be read")]
+    InputsFailed(usize),
+
     /// Represents an Error when reading the config file
     #[error("Error reading config file: {0}")]
     ConfigReadError(String),
 
+    /// Represents an invalid value in a `RICAT_*` environment variable.
+    ///
+    /// This error variant is used when a setting taken from the environment cannot be parsed.
+    /// It includes a string message naming the variable and the values it takes.
+    #[error("Invalid environment variable: {0}")]
+    EnvironmentError(String),
+
+    /// Represents an error that occurs when writing or editing the config file.
+    ///
+    /// This error variant is used by `ricat config init` and `ricat config edit`.
+    /// It includes a string message providing more details about the error.
+    #[error("Error writing config file: {0}")]
+    ConfigWriteError(String),
+
     /// User Quits the Pagination Mode by pressing 'q'
     #[error("User Quit Pagination Mode")]
     UserQuit,
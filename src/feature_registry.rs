@@ -0,0 +1,171 @@
+use crate::errors::RicatError;
+use crate::{ByteFeature, Cli};
+
+/// Builds a feature from the command line, `None` when it has nothing to do with these options
+/// (a value it needs is missing, or it colors the output and the output is no terminal),
+/// an error when it cannot be built (a plugin which does not load)
+pub type Constructor = Box<dyn Fn(&Cli) -> Result<Option<Box<dyn ByteFeature>>, RicatError>>;
+
+/// A feature known by name
+struct RegisteredFeature {
+    name: String,
+    /// whether the command line enables the feature with an option of its own
+    enabled: Box<dyn Fn(&Cli) -> bool>,
+    constructor: Constructor,
+    /// option giving the value the feature needs, without it the feature cannot be enabled by name
+    value_option: Option<&'static str>,
+}
+
+/// Features by name, in the order they are applied to a line.
+/// A feature is used when its own option is given, or when it is requested by name
+/// (`--features numbering,search` or `features = [...]` in the configuration file).
+/// `--feature-order` changes the order of the features it names among themselves.
+#[derive(Default)]
+pub struct FeatureRegistry {
+    features: Vec<RegisteredFeature>,
+    /// pairs of features which cannot be used together, like the options enabling them
+    conflicts: Vec<(String, String)>,
+}
+
+impl FeatureRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a feature after the ones registered so far, enabled by the option `enabled` checks or by name
+    pub fn register(
+        &mut self,
+        name: &str,
+        enabled: impl Fn(&Cli) -> bool + 'static,
+        constructor: impl Fn(&Cli) -> Option<Box<dyn ByteFeature>> + 'static,
+    ) {
+        self.features.push(RegisteredFeature {
+            name: name.to_string(),
+            enabled: Box::new(enabled),
+            constructor: Box::new(move |arguments| Ok(constructor(arguments))),
+            value_option: None,
+        });
+    }
+
+    /// Adds a feature only enabled by name, whose constructor can fail (e.g. a plugin which does not load).
+    /// It is only built when requested, and its error stops `build`.
+    pub fn register_by_name(
+        &mut self,
+        name: &str,
+        constructor: impl Fn(&Cli) -> Result<Box<dyn ByteFeature>, RicatError> + 'static,
+    ) {
+        self.features.push(RegisteredFeature {
+            name: name.to_string(),
+            enabled: Box::new(|_| false),
+            constructor: Box::new(move |arguments| constructor(arguments).map(Some)),
+            value_option: None,
+        });
+    }
+
+    /// Adds a feature which needs the value of `value_option`, it is enabled whenever the value is given
+    pub fn register_with_value(
+        &mut self,
+        name: &str,
+        value_option: &'static str,
+        constructor: impl Fn(&Cli) -> Option<Box<dyn ByteFeature>> + 'static,
+    ) {
+        self.features.push(RegisteredFeature {
+            name: name.to_string(),
+            enabled: Box::new(|_| true),
+            constructor: Box::new(move |arguments| Ok(constructor(arguments))),
+            value_option: Some(value_option),
+        });
+    }
+
+    /// Refuses `first` and `second` together, whether enabled by their options or by name
+    pub fn conflict(&mut self, first: &str, second: &str) {
+        self.conflicts.push((first.to_string(), second.to_string()));
+    }
+
+    /// Names of all the features, in the order they are applied
+    pub fn names(&self) -> Vec<&str> {
+        self.features
+            .iter()
+            .map(|feature| feature.name.as_str())
+            .collect()
+    }
+
+    /// The features enabled by their options or requested by name, in the order of the registry.
+    /// The features named in `order` take the places of each other in that order, e.g. `numbering,search`
+    /// numbers the lines before the search drops some; the other features keep their places.
+    pub fn build(
+        &self,
+        arguments: &Cli,
+        requested: &[String],
+        order: &[String],
+    ) -> Result<Vec<Box<dyn ByteFeature>>, RicatError> {
+        if let Some(unknown) = requested
+            .iter()
+            .chain(order)
+            .find(|name| !self.features.iter().any(|feature| feature.name == **name))
+        {
+            return Err(RicatError::FeatureError(format!(
+                "unknown feature `{}`, the features are: {}",
+                unknown,
+                self.names().join(", ")
+            )));
+        }
+
+        let mut features = Vec::new();
+        for feature in &self.features {
+            let by_name = requested.contains(&feature.name);
+            if !by_name && !(feature.enabled)(arguments) {
+                continue;
+            }
+            match ((feature.constructor)(arguments)?, feature.value_option) {
+                (Some(built), _) => features.push((feature.name.as_str(), built)),
+                (None, Some(option)) if by_name => {
+                    return Err(RicatError::FeatureError(format!(
+                        "feature `{}` needs a value, enable it with {}",
+                        feature.name, option
+                    )))
+                }
+                (None, _) => {}
+            }
+        }
+
+        let built = |name: &String| features.iter().any(|(feature, _)| feature == name);
+        if let Some((first, second)) = self
+            .conflicts
+            .iter()
+            .find(|(first, second)| built(first) && built(second))
+        {
+            return Err(RicatError::FeatureError(format!(
+                "features `{}` and `{}` cannot be used together",
+                first, second
+            )));
+        }
+        Ok(reorder(features, order))
+    }
+}
+
+/// Puts the features named in `order` into the places they hold together, sorted by `order`
+fn reorder(
+    features: Vec<(&str, Box<dyn ByteFeature>)>,
+    order: &[String],
+) -> Vec<Box<dyn ByteFeature>> {
+    let mut slots: Vec<Option<Box<dyn ByteFeature>>> = Vec::with_capacity(features.len());
+    let mut places = Vec::new();
+    let mut ordered = Vec::new();
+    for (name, feature) in features {
+        match order.iter().position(|ordered| ordered == name) {
+            Some(rank) => {
+                places.push(slots.len());
+                ordered.push((rank, feature));
+                slots.push(None);
+            }
+            None => slots.push(Some(feature)),
+        }
+    }
+
+    ordered.sort_by_key(|(rank, _)| *rank);
+    for (place, (_, feature)) in places.into_iter().zip(ordered) {
+        slots[place] = Some(feature);
+    }
+    slots.into_iter().flatten().collect()
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use crate::errors::RicatError;
+use crate::theme::{Style, Theme};
+
+/// Change of a working tree line against HEAD, shown in the `--git` gutter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    /// the line is new
+    Added,
+    /// the line replaces lines of HEAD
+    Modified,
+    /// lines of HEAD were removed right above this line
+    RemovedAbove,
+}
+
+impl LineChange {
+    /// Gutter marker, like `bat` shows them
+    pub fn marker(&self) -> char {
+        match self {
+            LineChange::Added => '+',
+            LineChange::Modified => '~',
+            LineChange::RemovedAbove => '_',
+        }
+    }
+
+    /// Color of the marker on a terminal
+    pub fn style<'a>(&self, theme: &'a Theme) -> &'a Style {
+        match self {
+            LineChange::Added => &theme.added,
+            LineChange::Modified => &theme.modified,
+            LineChange::RemovedAbove => &theme.removed,
+        }
+    }
+}
+
+/// Last commit changing a line, shown in the `--blame` column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameLine {
+    /// abbreviated commit hash, all zeros for a line not committed yet
+    pub commit: String,
+    pub author: String,
+    /// commit date as `YYYY-MM-DD` in the committer's time zone, empty for a line not committed yet
+    pub date: String,
+}
+
+/// Repository containing the file, with the path of the file relative to its working tree.
+/// `None` when the file is outside of any working tree.
+#[cfg(feature = "git")]
+fn open_repository(
+    absolute_path: &std::path::Path,
+) -> Result<Option<(git2::Repository, std::path::PathBuf)>, RicatError> {
+    let directory = absolute_path.parent().unwrap_or(std::path::Path::new("/"));
+    let Ok(repository) = git2::Repository::discover(directory) else {
+        return Ok(None);
+    };
+    let Some(workdir) = repository.workdir() else {
+        return Ok(None);
+    };
+    let workdir = std::fs::canonicalize(workdir)?;
+    let Ok(relative_path) = absolute_path.strip_prefix(&workdir) else {
+        return Ok(None);
+    };
+    let relative_path = relative_path.to_path_buf();
+    Ok(Some((repository, relative_path)))
+}
+
+/// Content of the file in HEAD, `None` when HEAD does not know the file or there are no commits yet
+#[cfg(feature = "git")]
+fn head_content(
+    repository: &git2::Repository,
+    relative_path: &std::path::Path,
+) -> Result<Option<Vec<u8>>, git2::Error> {
+    let Ok(tree) = repository.head().and_then(|head| head.peel_to_tree()) else {
+        return Ok(None);
+    };
+    let Ok(entry) = tree.get_path(relative_path) else {
+        return Ok(None);
+    };
+    let blob = entry.to_object(repository)?.peel_to_blob()?;
+    Ok(Some(blob.content().to_vec()))
+}
+
+/// Number of lines of the content, a last line without newline counts too
+#[cfg(feature = "git")]
+fn count_lines(content: &[u8]) -> usize {
+    let newlines = content.iter().filter(|&&byte| byte == b'\n').count();
+    if content.is_empty() || content.ends_with(b"\n") {
+        newlines
+    } else {
+        newlines + 1
+    }
+}
+
+/// Changes of the lines of a file (numbered from 1) in the working tree, compared to its version in HEAD.
+/// A file unknown to HEAD is added as a whole, a file outside of any repository has no changes.
+#[cfg(feature = "git")]
+pub fn line_changes(path: &str) -> Result<HashMap<usize, LineChange>, RicatError> {
+    use git2::{DiffOptions, Patch};
+
+    let git_error = |error: git2::Error| RicatError::GitError(format!("{}: {}", path, error));
+    let absolute_path = std::fs::canonicalize(path)?;
+    let Some((repository, relative_path)) = open_repository(&absolute_path)? else {
+        return Ok(HashMap::new());
+    };
+
+    let head_content = head_content(&repository, &relative_path).map_err(git_error)?;
+    let current_content = std::fs::read(&absolute_path)?;
+
+    let Some(head_content) = head_content else {
+        return Ok((1..=count_lines(&current_content))
+            .map(|line| (line, LineChange::Added))
+            .collect());
+    };
+
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+    let patch = Patch::from_buffers(
+        &head_content,
+        None,
+        &current_content,
+        None,
+        Some(&mut options),
+    )
+    .map_err(git_error)?;
+
+    let mut changes = HashMap::new();
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(hunk_index).map_err(git_error)?;
+        let new_start = hunk.new_start() as usize;
+        let new_lines = hunk.new_lines() as usize;
+        let change = match (hunk.old_lines(), new_lines) {
+            (_, 0) => {
+                // nothing left of the hunk, the marker goes on the line after the removed ones
+                changes.insert(new_start + 1, LineChange::RemovedAbove);
+                continue;
+            }
+            (0, _) => LineChange::Added,
+            _ => LineChange::Modified,
+        };
+        for line in new_start..new_start + new_lines {
+            changes.insert(line, change);
+        }
+    }
+    Ok(changes)
+}
+
+/// Last commit of every line of a file in the working tree, uncommitted changes included.
+/// A file outside of any repository has no blame, a file unknown to HEAD is not committed at all.
+#[cfg(feature = "git")]
+pub fn line_blame(path: &str) -> Result<Vec<BlameLine>, RicatError> {
+    use std::collections::hash_map::Entry;
+
+    let git_error = |error: git2::Error| RicatError::GitError(format!("{}: {}", path, error));
+    let absolute_path = std::fs::canonicalize(path)?;
+    let Some((repository, relative_path)) = open_repository(&absolute_path)? else {
+        return Ok(Vec::new());
+    };
+    let current_content = std::fs::read(&absolute_path)?;
+    let line_count = count_lines(&current_content);
+
+    let uncommitted = BlameLine {
+        commit: "0".repeat(COMMIT_LENGTH),
+        author: "Not Committed Yet".to_string(),
+        date: String::new(),
+    };
+    if head_content(&repository, &relative_path)
+        .map_err(git_error)?
+        .is_none()
+    {
+        return Ok(vec![uncommitted; line_count]);
+    }
+
+    // blame HEAD, then lay the working tree content over it for the uncommitted changes
+    let committed_blame = repository
+        .blame_file(&relative_path, None)
+        .map_err(git_error)?;
+    let blame = committed_blame
+        .blame_buffer(&current_content)
+        .map_err(git_error)?;
+
+    // the signatures of the hunks laid over the working tree are not always filled in, the commits are
+    let mut commits: HashMap<git2::Oid, BlameLine> = HashMap::new();
+    let mut lines = Vec::with_capacity(line_count);
+    for line in 1..=line_count {
+        let commit_id = match blame.get_line(line) {
+            Some(hunk) if !hunk.final_commit_id().is_zero() => hunk.final_commit_id(),
+            _ => {
+                lines.push(uncommitted.clone());
+                continue;
+            }
+        };
+        let blame_line = match commits.entry(commit_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let commit = repository.find_commit(commit_id).map_err(git_error)?;
+                let author = commit.author();
+                let when = author.when();
+                entry.insert(BlameLine {
+                    commit: commit_id.to_string()[..COMMIT_LENGTH].to_string(),
+                    author: String::from_utf8_lossy(author.name_bytes()).into_owned(),
+                    date: format_date(when.seconds(), when.offset_minutes()),
+                })
+            }
+        };
+        lines.push(blame_line.clone());
+    }
+    Ok(lines)
+}
+
+/// Length of the abbreviated commit hashes
+#[cfg(feature = "git")]
+const COMMIT_LENGTH: usize = 8;
+
+/// Date of the Unix time as `YYYY-MM-DD` in the time zone `offset_minutes` east of UTC
+#[cfg(feature = "git")]
+fn format_date(seconds: i64, offset_minutes: i32) -> String {
+    let days = (seconds + i64::from(offset_minutes) * 60).div_euclid(86_400);
+    // civil date from the days since 1970-01-01, after Howard Hinnant's `civil_from_days`
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(not(feature = "git"))]
+pub fn line_changes(_path: &str) -> Result<HashMap<usize, LineChange>, RicatError> {
+    Err(missing_feature())
+}
+
+#[cfg(not(feature = "git"))]
+pub fn line_blame(_path: &str) -> Result<Vec<BlameLine>, RicatError> {
+    Err(missing_feature())
+}
+
+#[cfg(not(feature = "git"))]
+fn missing_feature() -> RicatError {
+    RicatError::GitError(
+        "ricat was built without the `git` feature, reinstall with `cargo install ricat --features git`"
+            .to_string(),
+    )
+}
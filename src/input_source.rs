@@ -0,0 +1,290 @@
+use std::fs::{read_dir, File};
+#[cfg(feature = "clipboard")]
+use std::io::Cursor;
+use std::io::{stdin, BufRead, BufReader, Read};
+use std::path::Path;
+
+use glob::glob;
+
+use crate::archive::{open_member, split_member_argument, MEMBER_SEPARATOR};
+use crate::decompression::decompress;
+use crate::errors::RicatError;
+
+/// Input source given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// `-` reads the standard input at that position, like `cat header.txt - footer.txt`
+    Stdin,
+    /// path of a file to read
+    File(String),
+    /// `http://` or `https://` URL, the response body is streamed (needs the `http` cargo feature)
+    Url(String),
+    /// single member of a tar or zip archive, given as `archive.tar.gz::path/inside.txt` (needs the `archive` cargo feature)
+    ArchiveMember { archive: String, member: String },
+    /// text contents of the system clipboard, read with `--paste` (needs the `clipboard` cargo feature)
+    Clipboard,
+}
+
+impl InputSource {
+    /// Parses a file argument, `-` stands for the standard input
+    pub fn from_arg(argument: &str) -> Self {
+        if argument == "-" {
+            InputSource::Stdin
+        } else if is_url(argument) {
+            InputSource::Url(argument.to_string())
+        } else if let Some((archive, member)) = split_member_argument(argument)
+            .filter(|(archive, _)| !Path::new(argument).exists() && Path::new(archive).is_file())
+        {
+            InputSource::ArchiveMember {
+                archive: archive.to_string(),
+                member: member.to_string(),
+            }
+        } else {
+            InputSource::File(argument.to_string())
+        }
+    }
+
+    /// Name of the source as given on the command line
+    pub fn name(&self) -> String {
+        match self {
+            InputSource::Stdin => "-".to_string(),
+            InputSource::Clipboard => "clipboard".to_string(),
+            InputSource::File(path) | InputSource::Url(path) => path.clone(),
+            InputSource::ArchiveMember { archive, member } => {
+                format!("{}{}{}", archive, MEMBER_SEPARATOR, member)
+            }
+        }
+    }
+
+    /// Banner printed before the content of the source with `--show-filenames`, like `head`/`tail` do
+    pub fn banner(&self) -> String {
+        match self {
+            InputSource::Stdin => "==> standard input <==".to_string(),
+            InputSource::Clipboard => "==> clipboard <==".to_string(),
+            source => format!("==> {} <==", source.name()),
+        }
+    }
+
+    /// Opens the source for reading
+    pub fn open(&self) -> Result<Box<dyn Read>, RicatError> {
+        match self {
+            InputSource::Stdin => Ok(Box::new(stdin())),
+            InputSource::File(path) => {
+                let file = File::open(path).map_err(|error| {
+                    RicatError::FileOpenError(format!("Failed to open {}: {}", path, error))
+                })?;
+                // a directory opens, but fails on the first read
+                if file.metadata().is_ok_and(|metadata| metadata.is_dir()) {
+                    return Err(RicatError::FileOpenError(format!(
+                        "Failed to open {}: Is a directory",
+                        path
+                    )));
+                }
+                Ok(Box::new(file))
+            }
+            InputSource::Url(url) => open_url(url),
+            InputSource::ArchiveMember { archive, member } => open_member(archive, member),
+            InputSource::Clipboard => open_clipboard(),
+        }
+    }
+}
+
+impl InputSource {
+    /// Opens the source for reading, compressed content (gzip, zstd, xz, bzip2) is decompressed on the fly
+    pub fn open_decompressed(&self) -> Result<Box<dyn Read>, RicatError> {
+        decompress(self.open()?, &self.name())
+    }
+}
+
+/// Whether the argument is an `http://` or `https://` URL
+fn is_url(argument: &str) -> bool {
+    argument.starts_with("http://") || argument.starts_with("https://")
+}
+
+/// Streams the body of the response to a GET request on the URL
+#[cfg(feature = "http")]
+fn open_url(url: &str) -> Result<Box<dyn Read>, RicatError> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|error| RicatError::UrlReadError(format!("{}: {}", url, error)))?;
+    Ok(Box::new(response.into_body().into_reader()))
+}
+
+/// URLs cannot be read without the `http` cargo feature
+#[cfg(not(feature = "http"))]
+fn open_url(url: &str) -> Result<Box<dyn Read>, RicatError> {
+    Err(RicatError::UrlReadError(format!(
+        "{}: ricat was built without the `http` feature, reinstall with `cargo install ricat --features http`",
+        url
+    )))
+}
+
+/// Reads the text currently held by the system clipboard
+#[cfg(feature = "clipboard")]
+fn open_clipboard() -> Result<Box<dyn Read>, RicatError> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|error| RicatError::ClipboardError(error.to_string()))?;
+    Ok(Box::new(Cursor::new(text.into_bytes())))
+}
+
+/// The clipboard cannot be read without the `clipboard` cargo feature
+#[cfg(not(feature = "clipboard"))]
+fn open_clipboard() -> Result<Box<dyn Read>, RicatError> {
+    Err(RicatError::ClipboardError(
+        "ricat was built without the `clipboard` feature, reinstall with `cargo install ricat --features clipboard`"
+            .to_string(),
+    ))
+}
+
+/// Input sources for all the file arguments, in the order given.
+///
+/// Arguments which do not exist but contain glob characters (`*`, `?`, `[`) are expanded, so patterns
+/// like `src/**/*.rs` work even where the shell does not expand them (Windows).
+/// With `recursive`, directories are walked and every file inside is read, in sorted order.
+pub fn sources_from_args(
+    files: &[String],
+    recursive: bool,
+) -> Result<Vec<InputSource>, RicatError> {
+    let mut sources = Vec::new();
+
+    for argument in files {
+        let path = Path::new(argument);
+        let source = InputSource::from_arg(argument);
+        let is_file_argument = matches!(source, InputSource::File(_));
+
+        if is_file_argument && !path.exists() && is_glob_pattern(argument) {
+            let mut matched = false;
+            let entries = glob(argument).map_err(|error| {
+                RicatError::PathExpansionError(format!("Invalid pattern {}: {}", argument, error))
+            })?;
+            for entry in entries {
+                let entry = entry.map_err(|error| {
+                    RicatError::PathExpansionError(format!("Error reading {}: {}", argument, error))
+                })?;
+                matched = true;
+                push_path(&entry, recursive, &mut sources)?;
+            }
+            if !matched {
+                return Err(RicatError::PathExpansionError(format!(
+                    "No files match the pattern {}",
+                    argument
+                )));
+            }
+        } else if is_file_argument && recursive && path.is_dir() {
+            push_path(path, recursive, &mut sources)?;
+        } else {
+            sources.push(source);
+        }
+    }
+    Ok(sources)
+}
+
+/// Whether the argument contains glob characters
+fn is_glob_pattern(argument: &str) -> bool {
+    argument.contains(['*', '?', '['])
+}
+
+/// Adds a path to the sources, walking directories when `recursive` is set
+fn push_path(
+    path: &Path,
+    recursive: bool,
+    sources: &mut Vec<InputSource>,
+) -> Result<(), RicatError> {
+    if !path.is_dir() {
+        sources.push(InputSource::File(path.to_string_lossy().into_owned()));
+        return Ok(());
+    }
+    if !recursive {
+        // directories matched by a glob pattern are skipped unless reading recursively
+        return Ok(());
+    }
+
+    let mut entries = read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|error| {
+            RicatError::PathExpansionError(format!(
+                "Error reading directory {}: {}",
+                path.display(),
+                error
+            ))
+        })?;
+    entries.sort();
+
+    for entry in entries {
+        // like `grep -r`, links to directories are not walked, one to a parent would loop forever
+        if entry.is_symlink() && entry.is_dir() {
+            continue;
+        }
+        push_path(&entry, recursive, sources)?;
+    }
+    Ok(())
+}
+
+/// Lazily reads the entries of a `--files-from` / `--files-from0` list, one source per entry.
+///
+/// Entries are separated by `delimiter` (`\n` or NUL), empty entries are skipped.
+/// Entries are taken literally: no glob expansion, and `-` names a file, not the standard input.
+pub struct FileList<R> {
+    reader: R,
+    delimiter: u8,
+    list_name: String,
+    buffer: Vec<u8>,
+}
+
+/// Opens the file list at `list_path` (`-` reads the list from the standard input)
+pub fn file_list(
+    list_path: &str,
+    delimiter: u8,
+) -> Result<FileList<BufReader<Box<dyn Read>>>, RicatError> {
+    let reader = InputSource::from_arg(list_path).open()?;
+    Ok(FileList {
+        reader: BufReader::new(reader),
+        delimiter,
+        list_name: list_path.to_string(),
+        buffer: Vec::new(),
+    })
+}
+
+impl<R: BufRead> Iterator for FileList<R> {
+    type Item = Result<InputSource, RicatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_until(self.delimiter, &mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(error) => {
+                    return Some(Err(RicatError::PathExpansionError(format!(
+                        "Error reading file list {}: {}",
+                        self.list_name, error
+                    ))))
+                }
+            }
+
+            if self.buffer.last() == Some(&self.delimiter) {
+                self.buffer.pop();
+            }
+            if self.delimiter == b'\n' && self.buffer.last() == Some(&b'\r') {
+                self.buffer.pop();
+            }
+            if self.buffer.is_empty() {
+                continue;
+            }
+
+            return Some(match String::from_utf8(self.buffer.clone()) {
+                Ok(path) => Ok(InputSource::File(path)),
+                Err(_) => Err(RicatError::PathExpansionError(format!(
+                    "File list {} contains a file name which is not valid UTF-8: {}",
+                    self.list_name,
+                    String::from_utf8_lossy(&self.buffer)
+                ))),
+            });
+        }
+    }
+}
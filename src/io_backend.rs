@@ -0,0 +1,247 @@
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use std::fs::File;
+use std::fs::Metadata;
+use std::io::Write;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use std::io::{self, Read};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+use crate::copy_with_progress;
+use crate::errors::RicatError;
+use crate::progress::Progress;
+use crate::Buffering;
+
+/// Files smaller than this are read rather than mapped to memory by `--io-backend auto`,
+/// setting up the mapping costs more than it saves
+pub const DEFAULT_MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+/// How plain copies read the files, `--io-backend` or `io_backend` in the configuration
+#[derive(Deserialize, Serialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IoBackend {
+    /// copied by the kernel when it can, otherwise small files are read and large ones mapped to memory
+    #[default]
+    Auto,
+    /// buffered reads
+    Read,
+    /// mapped to memory in windows
+    Mmap,
+    /// experimental: batched reads through io_uring, several in flight at once (Linux, `io-uring` feature)
+    Uring,
+}
+
+impl IoBackend {
+    /// The backend reading the file: `auto` maps regular files of at least `mmap_threshold` bytes and reads
+    /// the others. Special files (pipes, devices, `/proc` files reporting no size) are always read.
+    pub fn resolve(self, metadata: &Metadata, mmap_threshold: u64) -> IoBackend {
+        if !metadata.is_file() || metadata.len() == 0 {
+            return IoBackend::Read;
+        }
+        match self {
+            IoBackend::Auto if metadata.len() >= mmap_threshold => IoBackend::Mmap,
+            IoBackend::Auto => IoBackend::Read,
+            backend => backend,
+        }
+    }
+}
+
+/// Reads in flight at once with io_uring
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+const QUEUE_DEPTH: usize = 4;
+
+#[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+const MISSING_FEATURE: &str =
+    "ricat was built without the `io-uring` feature, reinstall with `cargo install ricat --features io-uring` (Linux only)";
+
+/// Copies the file reading it through io_uring, with reads of the buffer size. When the kernel refuses
+/// io_uring (older kernels, containers filtering its system calls) the file is read as usual, with a warning.
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub fn copy_with_uring<W: Write>(
+    file_path: &str,
+    writer: W,
+    buffering: Buffering,
+    progress: Option<&mut Progress>,
+) -> Result<(), RicatError> {
+    let file = File::open(file_path).map_err(|error| {
+        RicatError::FileOpenError(format!("Error opening file {}: {}", file_path, error))
+    })?;
+    match UringReader::new(file.try_clone()?, buffering.size) {
+        Ok(reader) => copy_with_progress(reader, writer, buffering, progress),
+        Err(error) => {
+            eprintln!(
+                "ricat: warning: io_uring is not available ({}), reading {} as usual",
+                error, file_path
+            );
+            copy_with_progress(file, writer, buffering, progress)
+        }
+    }
+}
+
+#[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+pub fn copy_with_uring<W: Write>(
+    _file_path: &str,
+    _writer: W,
+    _buffering: Buffering,
+    _progress: Option<&mut Progress>,
+) -> Result<(), RicatError> {
+    Err(RicatError::IoBackendError(MISSING_FEATURE.to_string()))
+}
+
+/// Reader of a file through io_uring: it keeps several reads of the following chunks in flight while
+/// the data of the first one is read, and returns the chunks in the order of the file. The file is read
+/// up to the size it has when the reader is created.
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub struct UringReader {
+    ring: io_uring::IoUring,
+    file: File,
+    size: u64,
+    chunk_size: usize,
+    slots: Vec<Slot>,
+    /// offset of the next read to submit
+    next_offset: u64,
+    /// slot the data is returned from, the next one in the file
+    head: usize,
+}
+
+/// Buffer of one read, the kernel writes to it while the read is in flight
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+struct Slot {
+    buffer: Vec<u8>,
+    offset: u64,
+    len: usize,
+    in_flight: bool,
+    /// bytes read once the read completed
+    read: Option<usize>,
+    /// bytes of the buffer returned so far
+    position: usize,
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+impl UringReader {
+    /// Sets up the ring and submits the first reads, fails when the kernel refuses io_uring
+    pub fn new(file: File, chunk_size: usize) -> io::Result<Self> {
+        let mut reader = Self {
+            ring: io_uring::IoUring::new(QUEUE_DEPTH as u32)?,
+            size: file.metadata()?.len(),
+            file,
+            chunk_size,
+            slots: (0..QUEUE_DEPTH)
+                .map(|_| Slot {
+                    buffer: vec![0; chunk_size],
+                    offset: 0,
+                    len: 0,
+                    in_flight: false,
+                    read: None,
+                    position: 0,
+                })
+                .collect(),
+            next_offset: 0,
+            head: 0,
+        };
+        for index in 0..QUEUE_DEPTH {
+            reader.submit(index)?;
+        }
+        Ok(reader)
+    }
+
+    /// Submits the read of the next chunk of the file into the slot, unless the whole file is submitted
+    fn submit(&mut self, index: usize) -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        let slot = &mut self.slots[index];
+        slot.read = None;
+        slot.position = 0;
+        if self.next_offset >= self.size {
+            return Ok(());
+        }
+        slot.offset = self.next_offset;
+        slot.len = (self.size - self.next_offset).min(self.chunk_size as u64) as usize;
+        self.next_offset += slot.len as u64;
+
+        let entry = io_uring::opcode::Read::new(
+            io_uring::types::Fd(self.file.as_raw_fd()),
+            slot.buffer.as_mut_ptr(),
+            slot.len as u32,
+        )
+        .offset(slot.offset)
+        .build()
+        .user_data(index as u64);
+        // the buffer stays in the slot, untouched, until the read completes
+        unsafe { self.ring.submission().push(&entry) }
+            .map_err(|_| io::Error::other("the io_uring submission queue is full"))?;
+        slot.in_flight = true;
+        self.ring.submit()?;
+        Ok(())
+    }
+
+    /// Waits for at least one read to complete and records the completed ones
+    fn wait(&mut self) -> io::Result<()> {
+        self.ring.submit_and_wait(1)?;
+        let mut failure = None;
+        for completion in self.ring.completion() {
+            let slot = &mut self.slots[completion.user_data() as usize];
+            slot.in_flight = false;
+            match completion.result() {
+                result if result >= 0 => slot.read = Some(result as usize),
+                error => failure = Some(io::Error::from_raw_os_error(-error)),
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+impl Read for UringReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::os::unix::fs::FileExt;
+
+        loop {
+            let slot = &self.slots[self.head];
+            if slot.in_flight {
+                self.wait()?;
+                continue;
+            }
+            let Some(read) = slot.read else {
+                // no read submitted: the whole file is returned
+                return Ok(0);
+            };
+            if read < slot.len {
+                // a short read, the rest is read right away
+                let slot = &mut self.slots[self.head];
+                self.file
+                    .read_exact_at(&mut slot.buffer[read..slot.len], slot.offset + read as u64)?;
+                slot.read = Some(slot.len);
+                continue;
+            }
+            if slot.position < slot.len {
+                let slot = &mut self.slots[self.head];
+                let len = buf.len().min(slot.len - slot.position);
+                buf[..len].copy_from_slice(&slot.buffer[slot.position..slot.position + len]);
+                slot.position += len;
+                return Ok(len);
+            }
+            self.submit(self.head)?;
+            self.head = (self.head + 1) % QUEUE_DEPTH;
+        }
+    }
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+impl Drop for UringReader {
+    /// Waits for the reads in flight, the kernel must not write to the buffers once they are freed
+    fn drop(&mut self) {
+        while self.slots.iter().any(|slot| slot.in_flight) {
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                // the buffers are leaked rather than freed under the reads
+                Err(_) => return std::mem::forget(std::mem::take(&mut self.slots)),
+            }
+            for completion in self.ring.completion() {
+                self.slots[completion.user_data() as usize].in_flight = false;
+            }
+        }
+    }
+}
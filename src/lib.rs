@@ -0,0 +1,4777 @@
+//! # ricat: A Rust-Based `cat` Command Implementation
+//!
+//! This project is a Rust-based reimagining of the classic Unix `cat` command, drawing inspiration from its original implementation in GNU Core Utilities. It demonstrates the power and flexibility of Rust for system utilities development.
+//!
+//! A key design principle of `ricat` is extensibility. By utilizing a trait, `LineTextFeature`, the application makes it straightforward to introduce new functionalities. Developers can add custom features by implementing the `apply_feature()` method for each line of text. The core logic of `ricat` seamlessly integrates these features without requiring additional modifications.
+//!
+//! ## Features
+//!
+//! - **Modular Design**: Easy to extend with new line-based text processing features.
+//! - **Trait-Based Feature Implementation**: Implement the `LineTextFeature` trait to create new features.
+//! - **Line Numbering**: Display line numbers for each line of the input.
+//! - **Dollar Symbol at End**: Append a `$` symbol at the end of each line.
+//! - **Replace Tab Spaces**: Replace tab spaces in the text with `^I`.
+//! - **Compress Empty Lines**: Compress multiple consecutive empty lines into a single empty line.
+//! - **Search Text**: Search for lines containing a specific text or regular expression pattern. Prefix the search text with 'reg:' to treat it as a regular expression, e.g., 'reg:\\d+' to search for digits.
+//! - **Case-Insensitive Search**: Perform case-insensitive search for lines containing a specific text.
+//! - **Base64 Encoding**: Encode the input text using Base64.
+//! - **Base64 Decoding**: Decode Base64 encoded text.
+//! - **Pagination**: Display the output in a paginated manner, allowing user to navigate through pages.
+//! - **Configuration File**: Load preset features from a configuration file (`ricat_cfg.toml`) located in the user's configuration directory (`~/.config/ricat` on Linux, `~/Library/Application Support/ricat` on macOS, `%APPDATA%\ricat\config` on Windows).
+//!
+//! ## Usage
+//!
+//! `ricat` supports various command-line options to enable different features and customize the output. Here are some common usage examples:
+//!
+//! ### Read a File directly
+//! ```bash
+//! ricat my_file.txt
+//! ```
+//!
+//! ### Read a File With Line Numbering Enabled
+//! ```bash
+//! ricat -n my_file.txt
+//! ```
+//!
+//! ### Read a File with `$` at End of Each Line
+//! ```bash
+//! ricat -d my_file.txt
+//! ```
+//!
+//! ### Replace Tab Spaces with `^I`
+//! ```bash
+//! ricat -t my_file.txt
+//! ```
+//!
+//! ### Compress Empty Lines
+//! ```bash
+//! ricat -s my_file.txt
+//! ```
+//!
+//! ### Search for Lines Containing a Specific Text
+//! ```bash
+//! ricat --search --text "search_text" my_file.txt
+//! ```
+//!
+//! ### Search for Lines Matching a Regular Expression
+//! ```bash
+//! ricat --search --text "reg:\\d+" my_file.txt
+//! ```
+//!
+//! ### Perform Case-Insensitive Search
+//! ```bash
+//! ricat --search --text "search_text" -i my_file.txt
+//! ```
+//!
+//! ### Encode Input Text Using Base64
+//! ```bash
+//! ricat --encode-base64 my_file.txt
+//! ```
+//!
+//! ### Decode Base64 Encoded Text
+//! ```bash
+//! ricat --decode-base64 my_encoded_file.txt
+//! ```
+//!
+//! ### Enable Pagination
+//! ```bash
+//! ricat --pages my_large_file.txt
+//! ```
+//!
+//! ### Paginate Only When the Output Does Not Fit the Screen
+//! ```bash
+//! ricat --pages=auto my_file.txt
+//! ```
+//!
+//! ### Scroll Long Lines Horizontally in the Pager
+//! ```bash
+//! ricat --pages --pager-long-lines scroll my_file.txt
+//! ```
+//!
+//! ## Configuration File
+//!
+//! `ricat` supports loading preset features from a configuration file (`ricat_cfg.toml`) located in the user's configuration directory (see above, or `$RICAT_CONFIG_DIR`). The configuration file is created on the first run, and `ricat config init` writes one listing every setting.
+//!
+//! The configuration file allows users to enable or disable specific features by setting the corresponding fields to `true` or `false`. Here's an example of the `ricat_cfg.toml` file:
+//!
+//! ```toml
+//! number_feature = true
+//! dollar_sign_feature = false
+//! tabs_feature = false
+//! compress_empty_line_feature = false
+//! ```
+//!
+//! When `ricat` is executed, it reads the configuration file and enables the specified features accordingly. This provides a convenient way for users to customize the behavior of `ricat` without having to specify the options every time they run the command.
+//!
+//! ## Benchmarks
+//!
+//! Added a benchmarking module to test the performance of the application. The benchmarks covers only the direct file read(without features).
+//! To run the benchmarks, use the following command:
+//! in src/
+//! ```bash
+//! ./benchmark_plot.sh
+//! ```
+//! The script will run the benchmarks and generate a plot using `matplotlib` Be sure to install the required dependencies before running the script.
+//! Dependency: `matplotlib`
+//!
+//!
+//! ## Library
+//!
+//! The pipeline of the command line is available to other programs through `Ricat::builder()`: add inputs and features,
+//! choose where the output goes, and `run()` it. Failures are returned as `RicatError`.
+//!
+//! ## Extending ricat
+//!
+//! Adding a new feature to `ricat` is as simple as implementing the `LineTextFeature` trait for any struct. This modular approach encourages experimentation and customization.
+//!
+//! For example, to add a feature that highlights TODO comments in your text files, define a struct implementing `LineTextFeature` that scans each line for the pattern and applies the desired formatting.
+//!
+//! ## Testing
+//!
+//! `ricat` includes a comprehensive test suite to ensure the correctness and reliability of its functionality. The tests cover various scenarios and edge cases, including:
+//!
+//! - Basic functionality of each feature
+//! - Interaction between multiple features
+//! - Handling of empty input
+//! - Proper resetting of state between input sources
+//! - Encoding and decoding of text using Base64
+//! - Case-insensitive search functionality
+//! - Loading and applying configuration from the `ricat_cfg.toml` file
+//!
+//! To run the tests, clone the repo and use the `cargo test` command.
+//!
+//! ## Contributing
+//!
+//! Contributions to `ricat` are welcome! If you have an idea for a new feature or improvement, please open an issue or submit a pull request on the project's GitHub repository.
+//!
+//! When contributing, please ensure that your changes are well-tested and adhere to the project's coding style and conventions.
+//!
+//! ## License
+//!
+//! `ricat` is open-source software licensed under the [MIT License](https://opensource.org/licenses/MIT).
+
+pub mod encoding_decoding_feature;
+pub mod ansi;
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod bench;
+pub mod binary_file;
+pub mod builder;
+pub mod checksum;
+pub mod errors;
+pub mod git_markers;
+pub mod config;
+pub mod decompression;
+pub mod diff;
+pub mod encryption;
+pub mod feature_registry;
+pub mod input_source;
+pub mod io_backend;
+pub mod line_reader;
+pub mod memory_budget;
+pub mod output;
+pub mod output_template;
+pub mod pager;
+pub mod parallel;
+pub mod plugins;
+pub mod scripting;
+pub mod wasm_plugins;
+pub mod zero_copy;
+pub mod sampling;
+pub mod transcoding;
+pub mod progress;
+pub mod socket_input;
+pub mod stats;
+pub mod structured_formats;
+pub mod table;
+pub mod theme;
+pub mod utf8_validation;
+mod tests;
+
+use clap::{CommandFactory, Parser, ValueEnum as _};
+#[cfg(unix)]
+use memmap2::Advice;
+use memmap2::MmapOptions;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use std::{
+    borrow::Cow, collections::{HashMap, HashSet, VecDeque}, fs::File, io::{stderr, stdin, stdout, BufRead, BufReader, BufWriter, IsTerminal, Read, Write}, sync::{atomic::{AtomicUsize, Ordering}, Arc}, time::{Duration, Instant}
+};
+use crate::ansi::{has_escapes, strip_ansi, AnsiLine, Segment, RESET};
+use crate::bench::run_bench;
+use crate::binary_file::{hex_dump, sniff, BinaryMode};
+use crate::checksum::{checksum_lines, digest, ChecksumAlgorithm, DigestWriter};
+use crate::config::{apply_env_overrides, load_config, ricat_env_vars, run_config_command, ConfigAction, PagesMode};
+pub use crate::decompression::{decompress, CompressionFormat};
+use crate::git_markers::{line_blame, line_changes, BlameLine, LineChange};
+use crate::input_source::{file_list, sources_from_args, InputSource};
+use crate::io_backend::{copy_with_uring, IoBackend, DEFAULT_MMAP_THRESHOLD};
+use crate::output::{
+    open_output_direct, output_identity, CompressedWriter, OpenedOutput, FileIdentity, OutputCompression, OutputFileOptions, OutputTarget, TypewriterUnit, TypewriterWriter,
+};
+use crate::memory_budget::{parse_byte_size, MemoryBudget};
+use crate::output_template::OutputTemplate;
+use crate::line_reader::{into_text, lossy_lines, EndingConversion, LossyLines, LineEnding, LineLimit, OversizeMode};
+use crate::diff::{side_by_side, unified_diff, DEFAULT_CONTEXT};
+use crate::encryption::{decrypt, read_passphrase, EncryptedWriter};
+use crate::progress::{format_bytes, Progress, LARGE_FILE_SIZE};
+use crate::sampling::{Random, Reservoir};
+use crate::socket_input::{listen_address, listen_tcp, listen_unix};
+use crate::stats::{stats_report, LengthHistogram, StatsMode, TextStats};
+use crate::feature_registry::FeatureRegistry;
+use crate::parallel::{
+    can_process_in_parallel, job_count, process_files_in_parallel, search_file_in_chunks, worth_processing_in_parallel,
+};
+use crate::plugins::register_plugins;
+use crate::scripting::{parse_script, parse_script_file, Script, ScriptFeature};
+use crate::wasm_plugins::register_wasm_plugins;
+use crate::zero_copy::{copy_file_direct, DirectOutput};
+use crate::structured_formats::{pretty_print, StructuredFormat};
+use crate::table::{parse_delimited, render_table};
+use crate::theme::{Style, Theme, ThemeName, ThemeSettings};
+use crate::transcoding::{transcode, SourceEncoding};
+use crate::utf8_validation::validate_utf8;
+pub use pager::{
+    get_terminal_width, page_size, paginate_output, paginate_output_with, pagination_requested, should_paginate, visible_slice, wrapped_rows, LongLineMode,
+};
+
+
+// Library API
+pub use builder::{Ricat, RicatBuilder};
+pub use errors::RicatError;
+#[cfg(feature = "async")]
+pub use async_io::process_input_async;
+
+// Encoding-Decoding Module
+pub use encoding_decoding_feature::{
+    decode_chain, encode_chain, Ascii85, Base32, Base64, Codec, DataEncoding as _, Hex, Html, Json, Z85,
+};
+
+/// Where a line comes from, handed to the features along with its text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineContext<'a> {
+    /// name of the input as given on the command line, `-` for stdin, empty for lines made from several inputs
+    pub file_name: &'a str,
+    /// 1-based number of the line within its input, the chunks of a split long line count as lines
+    pub line_number: usize,
+    /// byte offset in the input where the line starts
+    pub byte_offset: u64,
+    /// last line of the input. Input read as it arrives (stdin, sockets) is not read ahead,
+    /// there only a last line without terminator is known to be the last one
+    pub is_last: bool,
+}
+
+/// Trait defining a text feature that can be applied to lines of input.
+pub trait LineTextFeature {
+    /// Applies a specific feature to a line of text and returns the modified line or None to omit the line.
+    /// A line passed on unchanged is returned borrowed, so filters and no-ops copy nothing.
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>>;
+
+    /// Applies the feature like `apply_feature`, for features which can fail on a line instead of dropping it.
+    /// The error stops the processing and is reported with the number of the line.
+    fn try_apply_feature<'a>(&mut self, line: &'a str) -> Result<Option<Cow<'a, str>>, RicatError> {
+        Ok(self.apply_feature(line))
+    }
+
+    /// Applies the feature to a line holding ANSI escape sequences, parsed into text and escapes.
+    /// Features which place text by position override it to keep clear of the escapes, the others
+    /// get the raw line by default.
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        self.apply_feature(line.raw())
+    }
+
+    /// Applies the feature to a line, which may turn into any number of output lines.
+    /// By default a line stays one line or is omitted, features splitting lines (e.g. wrapping) override it.
+    fn apply_feature_lines<'a>(&mut self, line: &'a str) -> Result<Vec<Cow<'a, str>>, RicatError> {
+        let processed_line = if has_escapes(line) {
+            self.apply_feature_ansi(&AnsiLine::parse(line))
+        } else {
+            self.try_apply_feature(line)?
+        };
+        Ok(processed_line.into_iter().collect())
+    }
+
+    /// Applies the feature to a line along with where it comes from. Features which depend on the file, the line number
+    /// or the offset override it, the others get `apply_feature_lines` by default.
+    fn apply_feature_in_context<'a>(
+        &mut self,
+        line: &'a str,
+        _context: &LineContext,
+    ) -> Result<Vec<Cow<'a, str>>, RicatError> {
+        self.apply_feature_lines(line)
+    }
+
+    /// Called before the first line of every input, for features which depend on the file being read.
+    /// Most features carry on across inputs and keep the default, which does nothing.
+    fn begin_input(&mut self, _source: &InputSource) -> Result<(), RicatError> {
+        Ok(())
+    }
+
+    /// Whether the feature handles every line on its own, keeping nothing from one line to the next.
+    /// Several files are then processed in parallel, each thread with instances of its own.
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    /// Called once the last line of the last input went through the pipeline, for features which collect something
+    /// along the way, e.g. counts or summaries. The lines returned are written after the output as they are,
+    /// the other features do not see them. Most features have nothing to add and keep the default.
+    fn finish(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Trait for features working on the raw bytes of a record (a line, or a NUL-terminated record with `-z`),
+/// for binary-safe transforms like encodings, which must not see the input converted to UTF-8 first.
+/// This is what the pipeline runs: every line feature is one as well, it gets the record as text.
+pub trait ByteFeature {
+    /// Applies the feature to the bytes of a record without terminator, returns the resulting records:
+    /// none to omit it, several when the feature splits it, the record itself borrowed when it is kept as it is.
+    /// A `FeatureError` stops the processing.
+    fn apply_record<'a>(&mut self, record: &'a [u8], context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError>;
+
+    /// Called before the first record of every input, like `LineTextFeature::begin_input`.
+    fn begin_records(&mut self, _source: &InputSource) -> Result<(), RicatError> {
+        Ok(())
+    }
+
+    /// Whether the feature keeps nothing from one record to the next, like `LineTextFeature::is_stateless`.
+    fn is_stateless(&self) -> bool {
+        false
+    }
+
+    /// Records added once the last input ended, like `LineTextFeature::finish`.
+    fn finish_records(&mut self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
+}
+
+/// Bytes of a line made by a line feature, still borrowed from the record when the feature kept it
+fn text_to_bytes(line: Cow<str>) -> Cow<[u8]> {
+    match line {
+        Cow::Borrowed(line) => Cow::Borrowed(line.as_bytes()),
+        Cow::Owned(line) => Cow::Owned(line.into_bytes()),
+    }
+}
+
+/// Line features run on the record converted to text, invalid UTF-8 sequences become `U+FFFD`
+impl<F: LineTextFeature> ByteFeature for F {
+    fn apply_record<'a>(&mut self, record: &'a [u8], context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        match String::from_utf8_lossy(record) {
+            Cow::Borrowed(line) => Ok(self.apply_feature_in_context(line, context)?.into_iter().map(text_to_bytes).collect()),
+            // invalid UTF-8 was replaced in a copy of the line, the lines made of it are copied out before it goes
+            Cow::Owned(line) => {
+                let processed_lines = self.apply_feature_in_context(&line, context)?;
+                Ok(processed_lines.into_iter().map(|line| Cow::Owned(line.into_owned().into_bytes())).collect())
+            }
+        }
+    }
+
+    fn begin_records(&mut self, source: &InputSource) -> Result<(), RicatError> {
+        self.begin_input(source)
+    }
+
+    fn is_stateless(&self) -> bool {
+        LineTextFeature::is_stateless(self)
+    }
+
+    fn finish_records(&mut self) -> Vec<Vec<u8>> {
+        self.finish().into_iter().map(String::into_bytes).collect()
+    }
+}
+
+/// Subcommands, run instead of reading the files. A file named like one is read with `ricat ./bench`
+#[derive(clap::Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Time the ways of reading FILE (buffered reads, mmap, the kernel copy) and the line pipeline, with
+    /// the features enabled before `bench`, e.g. `ricat -n bench big.log`
+    Bench {
+        /// file to read, the larger the steadier the times
+        file: String,
+        /// runs of every strategy, the fastest is shown
+        #[clap(long, default_value_t = 3, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        runs: usize,
+    },
+    /// Write the completion script for SHELL to stdout, e.g. `ricat completions bash > /etc/bash_completion.d/ricat`
+    /// or `ricat completions zsh > "${fpath[1]}/_ricat"`
+    Completions {
+        /// shell to complete in
+        #[clap(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Create, show, edit or check the configuration file: `ricat config init|show|path|edit|check`
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+/// Alignment of the line numbers within their minimum width, like the `nl -n` formats
+#[derive(Deserialize, Serialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberAlign {
+    /// left aligned, padded with spaces (`nl -n ln`)
+    Left,
+    /// right aligned, padded with spaces (`nl -n rn`)
+    #[default]
+    Right,
+    /// right aligned, padded with zeros (`nl -n rz`)
+    Zero,
+}
+
+/// How `LineNumbering` lays out the numbers. The default is the plain `1 line` style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// minimum width of the number
+    pub width: usize,
+    pub align: NumberAlign,
+    /// text between the number and the line
+    pub separator: String,
+    /// number of the first line
+    pub start: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            align: NumberAlign::Right,
+            separator: " ".to_string(),
+            start: 1,
+        }
+    }
+}
+
+/// Feature: adding line numbers to each line of text.
+/// In relative mode the numbers count from 0 again at the start of every block of the given number of lines.
+pub struct LineNumbering {
+    current_line: usize,
+    format: NumberFormat,
+    relative_page: Option<usize>,
+    /// color of the numbers, plain by default
+    style: Style,
+}
+
+impl LineNumbering {
+    pub fn new() -> Self {
+        Self::with_format(NumberFormat::default())
+    }
+
+    pub fn with_format(format: NumberFormat) -> Self {
+        Self {
+            current_line: format.start,
+            format,
+            relative_page: None,
+            style: Style::default(),
+        }
+    }
+
+    /// Numbers relative to the first line of each fixed block of `page` lines: 0 at its start, then 1, 2, ...
+    /// The blocks follow the lines numbered, not the screens of the pager, whose pages move with the
+    /// lines wrapped to the terminal width and with scrolling.
+    pub fn relative(page: usize, format: NumberFormat) -> Self {
+        Self {
+            current_line: 0,
+            format,
+            relative_page: Some(page.max(1)),
+            style: Style::default(),
+        }
+    }
+
+    /// Colors the numbers, not the separator after them
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Default for LineNumbering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl LineTextFeature for LineNumbering {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        let NumberFormat { width, align, separator, .. } = &self.format;
+        let current_line = match self.relative_page {
+            Some(page) => self.current_line % page,
+            None => self.current_line,
+        };
+        let number = match align {
+            NumberAlign::Left => format!("{:<width$}", current_line, width = width),
+            NumberAlign::Right => format!("{:>width$}", current_line, width = width),
+            NumberAlign::Zero => format!("{:0width$}", current_line, width = width),
+        };
+        let result = Some(format!("{}{}{}", self.style.paint(&number), separator, line).into());
+        self.current_line += 1;
+        result
+    }
+}
+
+/// Feature: adding `$` at the last of the line
+pub struct DollarSymbolAtLast;
+
+impl DollarSymbolAtLast {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DollarSymbolAtLast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for DollarSymbolAtLast {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        Some(format!("{}$", line).into())
+    }
+
+    /// The `$` goes after the escapes closing the line, a color left switched on is reset first
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        if line.ends_styled() {
+            Some(format!("{}{}$", line.raw(), RESET).into())
+        } else {
+            self.apply_feature(line.raw())
+        }
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: adding `^I` in place of all the tab-spaces used in the text.
+pub struct ReplaceTabspaces;
+impl ReplaceTabspaces {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ReplaceTabspaces {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for ReplaceTabspaces {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if !line.contains('\t') {
+            return Some(line.into());
+        }
+        Some(line.replace('\t', "^I").into())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: drops the first `count` lines of every input, e.g. the header of a CSV file
+pub struct SkipHead {
+    count: usize,
+    skipped: usize,
+}
+
+impl SkipHead {
+    pub fn new(count: usize) -> Self {
+        Self { count, skipped: 0 }
+    }
+}
+
+impl LineTextFeature for SkipHead {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if self.skipped < self.count {
+            self.skipped += 1;
+            return None;
+        }
+        Some(line.into())
+    }
+
+    fn begin_input(&mut self, _source: &InputSource) -> Result<(), RicatError> {
+        self.skipped = 0;
+        Ok(())
+    }
+}
+
+/// Feature: drops the last `count` lines of every input, e.g. a trailing summary.
+/// Lines are held back in a ring buffer of `count` lines and only passed on once newer lines push them out,
+/// so the input is still streamed. The lines left in the buffer when the input ends are the dropped ones.
+pub struct SkipTail {
+    count: usize,
+    held_back: VecDeque<String>,
+}
+
+impl SkipTail {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            held_back: VecDeque::with_capacity(count),
+        }
+    }
+}
+
+impl LineTextFeature for SkipTail {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if self.count == 0 {
+            return Some(line.into());
+        }
+        self.held_back.push_back(line.to_string());
+        if self.held_back.len() > self.count {
+            self.held_back.pop_front().map(Cow::Owned)
+        } else {
+            None
+        }
+    }
+
+    fn begin_input(&mut self, _source: &InputSource) -> Result<(), RicatError> {
+        self.held_back.clear();
+        Ok(())
+    }
+}
+
+/// Feature: Compresses multiple consecutive empty lines into a single empty line, or at most `max_blank` of them
+pub struct CompressEmptyLines {
+    /// empty lines kept in a row
+    max_blank: usize,
+    /// empty lines in a row up to the current one
+    empty_lines: usize,
+}
+
+impl CompressEmptyLines {
+    pub fn new() -> Self {
+        Self::with_max_blank(1)
+    }
+
+    /// Keeps up to `max_blank` empty lines in a row, none with 0
+    pub fn with_max_blank(max_blank: usize) -> Self {
+        Self {
+            max_blank,
+            empty_lines: 0,
+        }
+    }
+}
+
+impl Default for CompressEmptyLines {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for CompressEmptyLines {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if line.trim().is_empty() {
+            self.empty_lines += 1;
+            if self.empty_lines > self.max_blank {
+                None
+            } else {
+                Some("".into()) // Return an empty string to indicate an empty line should be printed.
+            }
+        } else {
+            self.empty_lines = 0;
+            Some(line.into())
+        }
+    }
+}
+
+/// Bytes of distinct lines `--unique-global` remembers at most, later new lines are no longer deduplicated
+pub const UNIQUE_MEMORY_CAP: usize = 256 * 1024 * 1024;
+
+/// Feature: drops duplicate lines. Adjacent duplicates like `uniq`, or with `global` every line seen before,
+/// remembering the distinct lines up to a memory cap: once it is reached a warning is printed on stderr,
+/// and lines first seen after it are always kept.
+pub struct UniqueLines {
+    previous_line: Option<String>,
+    seen: Option<HashSet<String>>,
+    remembered_bytes: usize,
+    memory_cap: usize,
+    warned: bool,
+}
+
+impl UniqueLines {
+    /// Drops lines equal to the line right before them
+    pub fn new() -> Self {
+        Self {
+            previous_line: None,
+            seen: None,
+            remembered_bytes: 0,
+            memory_cap: 0,
+            warned: false,
+        }
+    }
+
+    /// Drops lines equal to any line before them, remembering up to `memory_cap` bytes of distinct lines
+    pub fn global(memory_cap: usize) -> Self {
+        Self {
+            seen: Some(HashSet::new()),
+            memory_cap,
+            ..Self::new()
+        }
+    }
+}
+
+impl Default for UniqueLines {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for UniqueLines {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        let Some(seen) = &mut self.seen else {
+            if self.previous_line.as_deref() == Some(line) {
+                return None;
+            }
+            self.previous_line = Some(line.to_string());
+            return Some(line.into());
+        };
+
+        if seen.contains(line) {
+            return None;
+        }
+        if self.remembered_bytes + line.len() <= self.memory_cap {
+            self.remembered_bytes += line.len();
+            seen.insert(line.to_string());
+        } else if !self.warned {
+            self.warned = true;
+            eprintln!(
+                "ricat: warning: --unique-global remembers at most {} of distinct lines, later duplicates are kept",
+                format_bytes(self.memory_cap as f64)
+            );
+        }
+        Some(line.into())
+    }
+}
+
+/// Regex of the search text: a regex after the `reg:` prefix, literal text otherwise
+fn search_pattern(text: &str, ignore_case: bool) -> String {
+    let (is_regex, clean_text) = match text.strip_prefix("reg:") {
+        Some(regex_text) => (true, regex_text), // Strip the prefix and treat the rest as a regex
+        None => (false, text),                  // literal text
+    };
+
+    if is_regex {
+        if ignore_case {
+            format!("(?i){}", clean_text)
+        } else {
+            clean_text.to_string()
+        }
+    } else {
+        let escaped_text = regex::escape(clean_text);
+        if ignore_case {
+            format!("(?i){}", escaped_text)
+        } else {
+            escaped_text
+        }
+    }
+}
+
+/// The line with the text the regex finds painted in the style, empty matches are left out
+fn highlight_matches(regex: &Regex, line: &str, style: &Style) -> String {
+    let mut highlighted = String::with_capacity(line.len() + 16);
+    let mut end = 0;
+    for found in regex.find_iter(line).filter(|found| !found.is_empty()) {
+        highlighted.push_str(&line[end..found.start()]);
+        highlighted.push_str(&style.paint(found.as_str()));
+        end = found.end();
+    }
+    highlighted.push_str(&line[end..]);
+    highlighted
+}
+
+/// Feature: Returns Lines which contain a given text/regex
+pub struct LineWithGivenText {
+    /// search pattern or string input
+    search_pattern: String,
+    /// ignore case for search
+    _ignore_case: bool,
+    /// compiled regex; is cached.
+    regex: Option<Regex>,
+    /// matches counted for `--summary`
+    summary: Option<SearchSummary>,
+    /// color of the text found in the lines, the lines are left as they are without
+    highlight: Option<Style>,
+}
+
+impl LineWithGivenText {
+    pub fn new(text: &str, ignore_case: bool) -> Self {
+        Self {
+            search_pattern: search_pattern(text, ignore_case),
+            _ignore_case: ignore_case,
+            regex: None,
+            summary: None,
+            highlight: None,
+        }
+    }
+
+    /// Counts the matching lines, for a summary footer once the input ends
+    pub fn with_summary(mut self) -> Self {
+        self.summary = Some(SearchSummary::default());
+        self
+    }
+
+    /// Colors the text found in the matching lines
+    pub fn with_highlight(mut self, style: Style) -> Self {
+        self.highlight = Some(style).filter(|style| !style.is_plain());
+        self
+    }
+}
+
+impl LineTextFeature for LineWithGivenText {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if self.regex.is_none() {
+            self.regex = Regex::new(&self.search_pattern)
+                .map_err(|err| RicatError::RegexCompilationError(format!("Invalid regex '{}': {}", self.search_pattern, err)))
+                .ok();
+        }
+
+        if let Some(ref regex) = self.regex {
+            if regex.is_match(line) {
+                return Some(match &self.highlight {
+                    Some(style) => highlight_matches(regex, line, style).into(),
+                    None => line.into(),
+                });
+            }
+        }
+        None
+    }
+
+    /// The pattern is matched against the visible text, a color change inside a word does not hide it.
+    /// The colors of the line are kept, the matches are not highlighted over them.
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        let highlight = self.highlight.take();
+        let matched = self.apply_feature(&line.plain()).is_some();
+        self.highlight = highlight;
+        matched.then(|| line.raw().into())
+    }
+
+    /// Matches are counted for the summary with the input and line they come from
+    fn apply_feature_in_context<'a>(
+        &mut self,
+        line: &'a str,
+        context: &LineContext,
+    ) -> Result<Vec<Cow<'a, str>>, RicatError> {
+        let processed_lines = self.apply_feature_lines(line)?;
+        if let Some(summary) = &mut self.summary {
+            if !processed_lines.is_empty() {
+                summary.record(context.file_name, context.line_number);
+            }
+        }
+        Ok(processed_lines)
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        self.summary.iter().map(SearchSummary::footer).collect()
+    }
+
+    /// Stateless unless it counts the matches for the summary
+    fn is_stateless(&self) -> bool {
+        self.summary.is_none()
+    }
+}
+
+/// Matching lines counted by the search for `--summary`
+#[derive(Debug, Default)]
+pub struct SearchSummary {
+    /// matching lines counted so far
+    count: usize,
+    /// inputs with matches
+    files: usize,
+    /// input of the last match, a match in another one counts one more file
+    last_file: Option<String>,
+    /// input name and line number of the first matches, those the footer lists
+    listed: Vec<(String, usize)>,
+}
+
+impl SearchSummary {
+    /// Lines listed in the footer at most
+    const LISTED_LINES: usize = 10;
+
+    pub fn record(&mut self, input_name: &str, line: usize) {
+        self.count += 1;
+        if self.last_file.as_deref() != Some(input_name) {
+            self.files += 1;
+            self.last_file = Some(input_name.to_string());
+        }
+        if self.listed.len() < Self::LISTED_LINES {
+            self.listed.push((input_name.to_string(), line));
+        }
+    }
+
+    /// Footer like `42 matches across 3 files (lines: a.txt:10, b.txt:88, …)`,
+    /// the lines are only prefixed with their file when the matches are in several files
+    pub fn footer(&self) -> String {
+        let (count, files) = (self.count, self.files);
+        let mut footer = format!(
+            "{} {} across {} {}",
+            count,
+            if count == 1 { "match" } else { "matches" },
+            files,
+            if files == 1 { "file" } else { "files" }
+        );
+
+        if count > 0 {
+            let mut lines: Vec<String> = self
+                .listed
+                .iter()
+                .map(|(name, line)| if files > 1 { format!("{}:{}", name, line) } else { line.to_string() })
+                .collect();
+            if count > Self::LISTED_LINES {
+                lines.push("…".to_string());
+            }
+            footer.push_str(&format!(" (lines: {})", lines.join(", ")));
+        }
+        footer
+    }
+}
+
+/// Base64 Encoding Feature Integration
+pub struct Base64Encoding;
+
+impl Base64Encoding {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for Base64Encoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteFeature for Base64Encoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        Ok(Base64::encode_bytes(record).into_iter().map(Cow::Owned).collect())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Base64 Decoding Feature Integration
+pub struct Base64Decoding;
+
+impl Base64Decoding {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for Base64Decoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteFeature for Base64Decoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let decoded = Base64::decode_bytes(record)
+            .ok_or_else(|| RicatError::FeatureError("invalid Base64 input".to_string()))?;
+        Ok(vec![decoded.into()])
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Base32 Encoding Feature Integration
+pub struct Base32Encoding;
+
+impl Base32Encoding {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for Base32Encoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteFeature for Base32Encoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        Ok(Base32::encode_bytes(record).into_iter().map(Cow::Owned).collect())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Base32 Decoding Feature Integration
+pub struct Base32Decoding;
+
+impl Base32Decoding {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for Base32Decoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteFeature for Base32Decoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let decoded = Base32::decode_bytes(record)
+            .ok_or_else(|| RicatError::FeatureError("invalid Base32 input".to_string()))?;
+        Ok(vec![decoded.into()])
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Hex Encoding Feature Integration
+pub struct HexEncoding;
+
+impl HexEncoding {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for HexEncoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteFeature for HexEncoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        Ok(Hex::encode_bytes(record).into_iter().map(Cow::Owned).collect())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Hex Decoding Feature Integration
+pub struct HexDecoding;
+
+impl HexDecoding {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for HexDecoding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteFeature for HexDecoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let decoded = Hex::decode_bytes(record)
+            .ok_or_else(|| RicatError::FeatureError("invalid hex input".to_string()))?;
+        Ok(vec![decoded.into()])
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Encoding Feature Integration for the codecs chosen with `--encode CODEC[,CODEC...]`, applied in the given order
+pub struct CodecEncoding {
+    codecs: Vec<Codec>,
+}
+
+impl CodecEncoding {
+    pub fn new(codec: Codec) -> Self {
+        Self::chain(vec![codec])
+    }
+
+    pub fn chain(codecs: Vec<Codec>) -> Self {
+        Self { codecs }
+    }
+}
+
+impl ByteFeature for CodecEncoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let encoded = encode_chain(&self.codecs, record).ok_or_else(|| {
+            RicatError::FeatureError(format!("cannot encode the line as {}", codec_names(&self.codecs)))
+        })?;
+        Ok(vec![encoded.into()])
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Decoding Feature Integration for the codecs chosen with `--decode CODEC[,CODEC...]`, applied in the given order
+pub struct CodecDecoding {
+    codecs: Vec<Codec>,
+}
+
+impl CodecDecoding {
+    pub fn new(codec: Codec) -> Self {
+        Self::chain(vec![codec])
+    }
+
+    pub fn chain(codecs: Vec<Codec>) -> Self {
+        Self { codecs }
+    }
+}
+
+impl ByteFeature for CodecDecoding {
+    fn apply_record<'a>(&mut self, record: &'a [u8], _context: &LineContext) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let decoded = decode_chain(&self.codecs, record).ok_or_else(|| {
+            RicatError::FeatureError(format!("invalid {} input", codec_names(&self.codecs)))
+        })?;
+        Ok(vec![decoded.into()])
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Names of the codecs as given on the command line, e.g. `base64,hex`
+fn codec_names(codecs: &[Codec]) -> String {
+    codecs
+        .iter()
+        .filter_map(|codec| codec.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Feature: replaces every line with its hash, or puts the hash in front of the line with `annotate`
+pub struct LineHashing {
+    algorithm: ChecksumAlgorithm,
+    annotate: bool,
+}
+
+impl LineHashing {
+    pub fn new(algorithm: ChecksumAlgorithm, annotate: bool) -> Self {
+        Self { algorithm, annotate }
+    }
+}
+
+impl LineTextFeature for LineHashing {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        let hash = digest(self.algorithm, line.as_bytes());
+        if self.annotate {
+            Some(format!("{}  {}", hash, line).into())
+        } else {
+            Some(hash.into())
+        }
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Tab stop distance used when neither the flags nor the config file give one
+pub const DEFAULT_TAB_WIDTH: usize = 8;
+
+/// Feature: replaces tabs with spaces up to the next tab stop, keeping the visual alignment (like `expand`)
+pub struct ExpandTabs {
+    width: usize,
+}
+
+impl ExpandTabs {
+    pub fn new(width: usize) -> Self {
+        Self { width: width.max(1) }
+    }
+
+    /// Expands the tabs of `text` starting at visible `column`, which is advanced past the text
+    fn expand(&self, text: &str, column: &mut usize) -> String {
+        let mut expanded = String::with_capacity(text.len());
+        for character in text.chars() {
+            if character == '\t' {
+                let spaces = self.width - *column % self.width;
+                expanded.push_str(&" ".repeat(spaces));
+                *column += spaces;
+            } else {
+                expanded.push(character);
+                *column += 1;
+            }
+        }
+        expanded
+    }
+}
+
+impl Default for ExpandTabs {
+    fn default() -> Self {
+        Self::new(DEFAULT_TAB_WIDTH)
+    }
+}
+
+impl LineTextFeature for ExpandTabs {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if !line.contains('\t') {
+            return Some(line.into());
+        }
+        Some(self.expand(line, &mut 0).into())
+    }
+
+    /// Escape sequences take no room, the tab stops are counted in visible characters
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        let mut column = 0;
+        let expanded = line.segments().iter().map(|segment| match segment {
+            Segment::Text(text) => self.expand(text, &mut column),
+            Segment::Escape(escape) => escape.to_string(),
+        });
+        Some(Cow::Owned(expanded.collect()))
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: converts the leading spaces of a line into tabs at the tab stops (like `unexpand`),
+/// spaces short of the next tab stop stay spaces. Whitespace after the indentation is left alone.
+pub struct UnexpandTabs {
+    width: usize,
+}
+
+impl UnexpandTabs {
+    pub fn new(width: usize) -> Self {
+        Self { width: width.max(1) }
+    }
+
+    fn unexpand(&self, text: &str) -> String {
+        let body = text.trim_start_matches([' ', '\t']);
+        let mut column = 0;
+        for character in text[..text.len() - body.len()].chars() {
+            column = match character {
+                '\t' => column + self.width - column % self.width,
+                _ => column + 1,
+            };
+        }
+        format!("{}{}{}", "\t".repeat(column / self.width), " ".repeat(column % self.width), body)
+    }
+}
+
+impl Default for UnexpandTabs {
+    fn default() -> Self {
+        Self::new(DEFAULT_TAB_WIDTH)
+    }
+}
+
+impl LineTextFeature for UnexpandTabs {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        Some(self.unexpand(line).into())
+    }
+
+    /// The indentation is the start of the first text, escape sequences in front of it are kept
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        let mut indented = false;
+        let unexpanded = line.segments().iter().map(|segment| match segment {
+            Segment::Text(text) if !indented => {
+                indented = true;
+                self.unexpand(text)
+            }
+            Segment::Text(text) | Segment::Escape(text) => text.to_string(),
+        });
+        Some(Cow::Owned(unexpanded.collect()))
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Width given with `--wrap`: a number of columns, or `auto` for the width of the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapWidth {
+    Columns(usize),
+    Auto,
+}
+
+impl WrapWidth {
+    /// The width in columns, `auto` falls back to 80 when there is no terminal
+    pub fn columns(&self) -> usize {
+        match self {
+            WrapWidth::Columns(columns) => *columns,
+            WrapWidth::Auto => get_terminal_width() as usize,
+        }
+    }
+}
+
+/// Parses `--wrap N|auto`
+fn parse_wrap_width(value: &str) -> Result<WrapWidth, String> {
+    if value == "auto" {
+        return Ok(WrapWidth::Auto);
+    }
+    match value.parse::<usize>() {
+        Ok(columns) if columns > 0 => Ok(WrapWidth::Columns(columns)),
+        _ => Err(format!("`{}` is neither a positive width nor `auto`", value)),
+    }
+}
+
+/// Feature: folds long lines at word boundaries like `fold -s`, every line turns into as many output lines
+/// as needed to fit the width. Words longer than the width are broken, escape sequences take no room.
+pub struct LineWrapping {
+    width: usize,
+}
+
+impl LineWrapping {
+    pub fn new(width: usize) -> Self {
+        Self { width: width.max(1) }
+    }
+
+    fn wrap(&self, line: &AnsiLine) -> Vec<String> {
+        let mut rows = Vec::new();
+        let mut row = String::new();
+        let mut row_width = 0;
+        // byte offset and width of the row right after its last blank, where it can be broken
+        let mut last_blank: Option<(usize, usize)> = None;
+
+        for segment in line.segments() {
+            let text = match segment {
+                Segment::Escape(escape) => {
+                    row.push_str(escape);
+                    continue;
+                }
+                Segment::Text(text) => text,
+            };
+            for character in text.chars() {
+                if row_width == self.width {
+                    match last_blank.take() {
+                        Some((offset, width)) => {
+                            let rest = row.split_off(offset);
+                            rows.push(std::mem::replace(&mut row, rest));
+                            row_width -= width;
+                        }
+                        None => {
+                            rows.push(std::mem::take(&mut row));
+                            row_width = 0;
+                        }
+                    }
+                }
+                row.push(character);
+                row_width += 1;
+                if character == ' ' || character == '\t' {
+                    last_blank = Some((row.len(), row_width));
+                }
+            }
+        }
+        rows.push(row);
+        rows
+    }
+}
+
+impl LineTextFeature for LineWrapping {
+    /// The folded rows joined with `\n`, the pipeline gets them apart from `apply_feature_lines`
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        Some(self.wrap(&AnsiLine::parse(line)).join("\n").into())
+    }
+
+    fn apply_feature_lines<'a>(&mut self, line: &'a str) -> Result<Vec<Cow<'a, str>>, RicatError> {
+        Ok(self.wrap(&AnsiLine::parse(line)).into_iter().map(Cow::Owned).collect())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Fields selected with `--fields`: ranges of 1-based field numbers, `N`, `N-M`, `N-` or `-M`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldList(Vec<(usize, usize)>);
+
+impl FieldList {
+    fn contains(&self, field: usize) -> bool {
+        self.0.iter().any(|&(first, last)| (first..=last).contains(&field))
+    }
+}
+
+/// Parses `--fields 1,3-5,7-`
+fn parse_field_list(value: &str) -> Result<FieldList, String> {
+    let number = |text: &str| match text.parse::<usize>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("`{}` is not a field number, fields count from 1", text)),
+    };
+    value
+        .split(',')
+        .map(|range| match range.split_once('-') {
+            None => number(range).map(|field| (field, field)),
+            Some(("", "")) => Err("`-` is not a field range".to_string()),
+            Some(("", last)) => Ok((1, number(last)?)),
+            Some((first, "")) => Ok((number(first)?, usize::MAX)),
+            Some((first, last)) => match (number(first)?, number(last)?) {
+                (first, last) if first <= last => Ok((first, last)),
+                _ => Err(format!("`{}` is a decreasing field range", range)),
+            },
+        })
+        .collect::<Result<_, _>>()
+        .map(FieldList)
+}
+
+/// Parses `--field-delim`, an empty delimiter would split nothing apart
+fn parse_field_delim(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Err("the field delimiter cannot be empty".to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// Feature: cuts the selected fields out of every line like `cut -f`, in their order in the line,
+/// joined by the output delimiter. Lines without the delimiter are left whole, as `cut` does.
+pub struct FieldExtraction {
+    fields: FieldList,
+    delimiter: String,
+    output_delimiter: String,
+}
+
+impl FieldExtraction {
+    pub fn new(fields: FieldList, delimiter: &str, output_delimiter: &str) -> Self {
+        Self {
+            fields,
+            delimiter: delimiter.to_string(),
+            output_delimiter: output_delimiter.to_string(),
+        }
+    }
+}
+
+impl LineTextFeature for FieldExtraction {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if !line.contains(&self.delimiter) {
+            return Some(line.into());
+        }
+        let selected: Vec<&str> = line
+            .split(&self.delimiter)
+            .enumerate()
+            .filter(|(index, _)| self.fields.contains(index + 1))
+            .map(|(_, field)| field)
+            .collect();
+        Some(selected.join(&self.output_delimiter).into())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Column of a `--columns` expression: `$0` the whole line, `$N` the N-th column,
+/// `$NF` the last one and `$(NF-N)` the N-th column before the last
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnRef {
+    Line,
+    Column(usize),
+    FromLast(usize),
+}
+
+/// Columns selected with `--columns`, in the order they are printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnList(Vec<ColumnRef>);
+
+/// Parses `--columns '$1 $NF'`, the references are separated by whitespace or commas like in awk's `print`
+fn parse_column_list(value: &str) -> Result<ColumnList, String> {
+    let column = |reference: &str| {
+        let invalid = || format!("`{}` is not a column, use `$N`, `$NF` or `$(NF-N)`", reference);
+        let name = reference.strip_prefix('$').ok_or_else(invalid)?;
+        if name == "NF" {
+            return Ok(ColumnRef::FromLast(0));
+        }
+        if let Some(offset) = name.strip_prefix("(NF-").and_then(|rest| rest.strip_suffix(')')) {
+            return offset.parse().map(ColumnRef::FromLast).map_err(|_| invalid());
+        }
+        match name.parse() {
+            Ok(0) => Ok(ColumnRef::Line),
+            Ok(number) => Ok(ColumnRef::Column(number)),
+            Err(_) => Err(invalid()),
+        }
+    };
+    let columns: Vec<ColumnRef> = value
+        .split(|character: char| character.is_whitespace() || character == ',')
+        .filter(|reference| !reference.is_empty())
+        .map(column)
+        .collect::<Result<_, _>>()?;
+    if columns.is_empty() {
+        return Err("no columns given".to_string());
+    }
+    Ok(ColumnList(columns))
+}
+
+/// Feature: prints the selected columns of every line like `awk '{print $1, $NF}'`: the line is split on runs
+/// of whitespace and the columns are joined by a space. Columns the line does not have are empty.
+pub struct ColumnSelection {
+    columns: ColumnList,
+}
+
+impl ColumnSelection {
+    pub fn new(columns: ColumnList) -> Self {
+        Self { columns }
+    }
+}
+
+impl LineTextFeature for ColumnSelection {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let selected: Vec<&str> = self
+            .columns
+            .0
+            .iter()
+            .map(|&column| match column {
+                ColumnRef::Line => line,
+                ColumnRef::Column(number) => words.get(number - 1).copied().unwrap_or(""),
+                ColumnRef::FromLast(offset) => words
+                    .len()
+                    .checked_sub(offset + 1)
+                    .map_or("", |index| words[index]),
+            })
+            .collect();
+        Some(selected.join(" ").into())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Parses `--sample-percent P`, a percentage above 0 and up to 100
+fn parse_percent(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(percent) if percent > 0.0 && percent <= 100.0 => Ok(percent),
+        _ => Err(format!("`{}` is not a percentage above 0 and up to 100", value)),
+    }
+}
+
+/// Feature: keeps every line with the given chance, a random subset of about `percent` of the lines
+pub struct SamplePercent {
+    fraction: f64,
+    random: Random,
+}
+
+impl SamplePercent {
+    pub fn new(percent: f64, random: Random) -> Self {
+        Self {
+            fraction: percent / 100.0,
+            random,
+        }
+    }
+}
+
+impl LineTextFeature for SamplePercent {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        (self.random.fraction() < self.fraction).then_some(line.into())
+    }
+}
+
+/// Feature: marks the lines added or modified since HEAD in a gutter column, like `bat`.
+/// The changes are looked up for every input file, other inputs get an empty gutter.
+pub struct GitMarkers {
+    changes: HashMap<usize, LineChange>,
+    current_line: usize,
+    colored: bool,
+    theme: Theme,
+}
+
+impl GitMarkers {
+    pub fn new(colored: bool) -> Self {
+        Self::with_changes(HashMap::new(), colored)
+    }
+
+    /// Sets the changes of the input directly, the lines count from 1
+    pub fn with_changes(changes: HashMap<usize, LineChange>, colored: bool) -> Self {
+        Self {
+            changes,
+            current_line: 0,
+            colored,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Draws the markers in the `added`, `modified` and `removed` colors of the theme
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl LineTextFeature for GitMarkers {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        self.current_line += 1;
+        let gutter = match self.changes.get(&self.current_line) {
+            Some(change) if self.colored => change.style(&self.theme).paint(&change.marker().to_string()),
+            Some(change) => change.marker().to_string(),
+            None => " ".to_string(),
+        };
+        Some(format!("{} {}", gutter, line).into())
+    }
+
+    /// The markers follow the number of the line in the input, a feature before (e.g. a search) may drop lines
+    fn apply_feature_in_context<'a>(
+        &mut self,
+        line: &'a str,
+        context: &LineContext,
+    ) -> Result<Vec<Cow<'a, str>>, RicatError> {
+        if context.line_number > 0 {
+            self.current_line = context.line_number - 1;
+        }
+        self.apply_feature_lines(line)
+    }
+
+    fn begin_input(&mut self, source: &InputSource) -> Result<(), RicatError> {
+        self.current_line = 0;
+        self.changes = match source {
+            InputSource::File(path) => line_changes(path)?,
+            _ => HashMap::new(),
+        };
+        Ok(())
+    }
+}
+
+/// Feature: prefixes every line with the abbreviated hash, author and date of the commit last changing it,
+/// like `git blame`. Lines without blame, e.g. of inputs outside of a repository, are left as they are.
+pub struct Blame {
+    lines: Vec<BlameLine>,
+    author_width: usize,
+    current_line: usize,
+    colored: bool,
+    theme: Theme,
+}
+
+impl Blame {
+    pub fn new(colored: bool) -> Self {
+        Self::with_lines(Vec::new(), colored)
+    }
+
+    /// Sets the blame of the input directly, one entry per line
+    pub fn with_lines(lines: Vec<BlameLine>, colored: bool) -> Self {
+        let author_width = lines.iter().map(|line| line.author.chars().count()).max().unwrap_or(0);
+        Self {
+            lines,
+            author_width,
+            current_line: 0,
+            colored,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Draws the annotations in the `dimmed` color of the theme
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl LineTextFeature for Blame {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        self.current_line += 1;
+        let Some(blame) = self.lines.get(self.current_line - 1) else {
+            return Some(line.into());
+        };
+        let annotation = format!(
+            "{} ({:<width$} {:>10})",
+            blame.commit,
+            blame.author,
+            blame.date,
+            width = self.author_width
+        );
+        if self.colored {
+            Some(format!("{} {}", self.theme.dimmed.paint(&annotation), line).into())
+        } else {
+            Some(format!("{} {}", annotation, line).into())
+        }
+    }
+
+    /// The blame follows the number of the line in the input, a feature before (e.g. a search) may drop lines
+    fn apply_feature_in_context<'a>(
+        &mut self,
+        line: &'a str,
+        context: &LineContext,
+    ) -> Result<Vec<Cow<'a, str>>, RicatError> {
+        if context.line_number > 0 {
+            self.current_line = context.line_number - 1;
+        }
+        self.apply_feature_lines(line)
+    }
+
+    fn begin_input(&mut self, source: &InputSource) -> Result<(), RicatError> {
+        let lines = match source {
+            InputSource::File(path) => line_blame(path)?,
+            _ => Vec::new(),
+        };
+        *self = Self::with_lines(lines, self.colored).with_theme(std::mem::take(&mut self.theme));
+        Ok(())
+    }
+}
+
+/// Feature: makes whitespace visible: spaces become `·`, tabs `→` and non-breaking spaces `␣`,
+/// drawn dimmed with `colored` so they stand apart from real dots and arrows in the text.
+pub struct ShowWhitespace {
+    colored: bool,
+    theme: Theme,
+}
+
+impl ShowWhitespace {
+    /// marker drawn in place of each kind of whitespace
+    const MARKERS: [(char, char); 4] = [(' ', '·'), ('\t', '→'), ('\u{a0}', '␣'), ('\u{202f}', '␣')];
+
+    pub fn new(colored: bool) -> Self {
+        Self {
+            colored,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Draws the markers in the `dimmed` color of the theme
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    fn render(&self, text: &str) -> String {
+        let mut rendered = String::with_capacity(text.len());
+        for character in text.chars() {
+            match Self::MARKERS.iter().find(|(whitespace, _)| *whitespace == character) {
+                Some((_, marker)) if self.colored => rendered.push_str(&self.theme.dimmed.paint(&marker.to_string())),
+                Some((_, marker)) => rendered.push(*marker),
+                None => rendered.push(character),
+            }
+        }
+        rendered
+    }
+}
+
+impl LineTextFeature for ShowWhitespace {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        Some(self.render(line).into())
+    }
+
+    /// Only the text is rendered, spaces inside escape sequences (e.g. window titles) stay as they are
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        let rendered = line.segments().iter().map(|segment| match segment {
+            Segment::Text(text) => self.render(text),
+            Segment::Escape(escape) => escape.to_string(),
+        });
+        Some(Cow::Owned(rendered.collect()))
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: paints trailing spaces and tabs red, or in the color of the theme, so they stand out in review
+pub struct HighlightTrailing {
+    style: Style,
+}
+
+impl HighlightTrailing {
+    pub fn new() -> Self {
+        Self::with_theme(&Theme::default())
+    }
+
+    pub fn with_theme(theme: &Theme) -> Self {
+        Self {
+            style: theme.trailing_whitespace.clone(),
+        }
+    }
+}
+
+impl Default for HighlightTrailing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for HighlightTrailing {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        self.apply_feature_ansi(&AnsiLine::parse(line))
+    }
+
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        Some(line.map_trailing_whitespace(|whitespace| self.style.paint(whitespace)).into())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: removes trailing spaces and tabs
+pub struct TrimTrailing;
+
+impl TrimTrailing {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for TrimTrailing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for TrimTrailing {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        Some(line.trim_end_matches([' ', '\t']).into())
+    }
+
+    /// Escape sequences after the whitespace are kept, the color reset closing a line must not get lost
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        Some(line.map_trailing_whitespace(|_| String::new()).into())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: removes ANSI escape sequences (colors, cursor movement, titles), leaving the text as it reads on the terminal
+pub struct StripAnsi;
+
+impl StripAnsi {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for StripAnsi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for StripAnsi {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        if !has_escapes(line) {
+            return Some(line.into());
+        }
+        Some(strip_ansi(line).into())
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: reverses the characters of every line like `rev`. Grapheme clusters stay whole, so accented letters,
+/// emoji sequences and `\r\n` survive, and lines with escape sequences are reversed as the text they show.
+pub struct LineReversal;
+
+impl LineReversal {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for LineReversal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for LineReversal {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        Some(Cow::Owned(line.graphemes(true).rev().collect()))
+    }
+
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        Some(Cow::Owned(line.plain().graphemes(true).rev().collect()))
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: prefixes every line with the time elapsed since the previous line reached it, like `+0.250s`.
+/// The first line is timed from the start, slow spots of a pipeline replayed through stdin stand out.
+pub struct LineDelta {
+    last_line: Instant,
+}
+
+impl LineDelta {
+    pub fn new() -> Self {
+        Self {
+            last_line: Instant::now(),
+        }
+    }
+}
+
+impl Default for LineDelta {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for LineDelta {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_line);
+        self.last_line = now;
+        Some(format!("{:>10} {}", format!("+{:.3}s", elapsed.as_secs_f64()), line).into())
+    }
+}
+
+/// Feature: colors the level of log lines, the first `ERROR`, `WARN`, `INFO`, `DEBUG`, ... word of the line,
+/// with the colors of the theme. Lines without a level are left as they are.
+pub struct LogLevels {
+    levels: Regex,
+    /// styles of the errors, warnings, infos and debug messages
+    styles: [Style; 4],
+}
+
+impl LogLevels {
+    pub fn new(theme: &Theme) -> Self {
+        Self {
+            levels: Regex::new(r"\b(FATAL|CRITICAL|ERROR|WARNING|WARN|INFO|NOTICE|DEBUG|TRACE)\b").expect("valid level regex"),
+            styles: [theme.log_error.clone(), theme.log_warn.clone(), theme.log_info.clone(), theme.log_debug.clone()],
+        }
+    }
+}
+
+impl LogLevels {
+    /// The text with its level colored, `None` when it holds none
+    fn color(&self, text: &str) -> Option<String> {
+        let level = self.levels.find(text)?;
+        let style = match level.as_str() {
+            "FATAL" | "CRITICAL" | "ERROR" => &self.styles[0],
+            "WARNING" | "WARN" => &self.styles[1],
+            "INFO" | "NOTICE" => &self.styles[2],
+            _ => &self.styles[3],
+        };
+        Some(format!("{}{}{}", &text[..level.start()], style.paint(level.as_str()), &text[level.end()..]))
+    }
+}
+
+impl LineTextFeature for LogLevels {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        match self.color(line) {
+            Some(colored) => Some(colored.into()),
+            None => Some(line.into()),
+        }
+    }
+
+    /// The level is looked for in the text, escape sequences around it are kept
+    fn apply_feature_ansi<'a>(&mut self, line: &AnsiLine<'a>) -> Option<Cow<'a, str>> {
+        let mut colored = false;
+        let rendered = line.segments().iter().map(|segment| match segment {
+            Segment::Text(text) if !colored => match self.color(text) {
+                Some(text) => {
+                    colored = true;
+                    text
+                }
+                None => text.to_string(),
+            },
+            Segment::Text(text) => text.to_string(),
+            Segment::Escape(escape) => escape.to_string(),
+        });
+        Some(Cow::Owned(rendered.collect()))
+    }
+
+    fn is_stateless(&self) -> bool {
+        true
+    }
+}
+
+/// Feature: colors every character with a rolling rainbow gradient, like `lolcat`.
+/// The gradient moves along the characters of a line and shifts a little with every line.
+pub struct Rainbow {
+    current_line: usize,
+}
+
+impl Rainbow {
+    /// how fast the colors change from one character to the next
+    const FREQUENCY: f64 = 0.1;
+    /// how many characters a line shifts the gradient
+    const LINE_SHIFT: f64 = 3.0;
+
+    pub fn new() -> Self {
+        Self { current_line: 0 }
+    }
+
+    /// 24-bit color at the given position of the gradient
+    pub fn color_at(position: f64) -> (u8, u8, u8) {
+        let channel = |phase: f64| {
+            ((Self::FREQUENCY * position + phase).sin() * 127.0 + 128.0) as u8
+        };
+        let third = 2.0 * std::f64::consts::PI / 3.0;
+        (channel(0.0), channel(third), channel(2.0 * third))
+    }
+}
+
+impl Default for Rainbow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineTextFeature for Rainbow {
+    fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        let line_start = self.current_line as f64 * Self::LINE_SHIFT;
+        self.current_line += 1;
+        if line.is_empty() {
+            return Some("".into());
+        }
+
+        let mut colored = String::with_capacity(line.len() * 20);
+        for (column, character) in line.chars().enumerate() {
+            let (red, green, blue) = Self::color_at(line_start + column as f64);
+            colored.push_str(&format!("\x1b[38;2;{};{};{}m{}", red, green, blue, character));
+        }
+        colored.push_str("\x1b[0m");
+        Some(colored.into())
+    }
+}
+
+/// Command line arguments struct, parsed using `clap`.
+#[derive(Parser)]
+#[clap(
+    version = "0.4.5",
+    author = "Aditya Navphule <adityanav@duck.com>",
+    about = "ricat (Rust Implemented `cat`) : A custom implementation of cat command in Rust"
+)]
+pub struct Cli {
+    /// Enables line numbering for each line of the input.
+    #[clap(short = 'n', long, action = clap::ArgAction::SetTrue, help = "shows line numbers for each line")]
+    numbers: bool,
+
+    #[clap(
+        long = "git",
+        action = clap::ArgAction::SetTrue,
+        help = "Mark the lines added (+), modified (~) or with lines removed above them (_) since HEAD in a gutter, when the file is in a git repository"
+    )]
+    git: bool,
+
+    #[clap(
+        long = "blame",
+        action = clap::ArgAction::SetTrue,
+        help = "Prefix every line with the short hash, author and date of the commit last changing it, when the file is in a git repository"
+    )]
+    blame: bool,
+
+    #[clap(
+        long = "relative-numbers",
+        action = clap::ArgAction::SetTrue,
+        conflicts_with = "numbers",
+        help = "Number the lines from 0, again every block of a screen height of lines with the pager (or from the first line without it). The blocks are fixed, they do not follow the pager's scrolling or wrapped lines"
+    )]
+    relative_numbers: bool,
+
+    #[clap(long = "number-width", value_name = "WIDTH", help = "Minimum width of the line numbers, e.g. 6 like `nl`")]
+    number_width: Option<usize>,
+
+    #[clap(long = "number-align", value_enum, help = "Alignment of the line numbers within their width: left, right, or zero to pad with zeros")]
+    number_align: Option<NumberAlign>,
+
+    #[clap(long = "number-separator", value_name = "TEXT", help = "Text between the line number and the line, e.g. \" | \" (default: a space)")]
+    number_separator: Option<String>,
+
+    #[clap(long = "number-start", value_name = "N", help = "Number of the first line (default 1)")]
+    number_start: Option<usize>,
+
+    #[clap(short = 'd', long, action = clap::ArgAction::SetTrue, help = "adds `$` to mark end of each line")]
+    dollar: bool,
+
+    #[clap(short = 't', long, action = clap::ArgAction::SetTrue, help = "replaces the tab spaces in the text with ^I")]
+    tabs: bool,
+
+    #[clap(short = 's', long, action = clap::ArgAction::SetTrue, help = "suppress repeated empty output lines")]
+    squeeze_blank: bool,
+
+    #[clap(long = "max-blank", value_name = "N", help = "Blank lines -s keeps in a row (default 1), 0 drops them all")]
+    max_blank: Option<usize>,
+
+    #[clap(
+        long = "features",
+        value_name = "NAMES",
+        value_delimiter = ',',
+        help = "Enable features by name, e.g. numbering,dollar,squeeze; they apply in their usual order. An unknown name lists them all"
+    )]
+    feature_names: Vec<String>,
+
+    #[clap(
+        long = "feature-order",
+        value_name = "NAMES",
+        value_delimiter = ',',
+        help = "Apply the named features in this order, e.g. numbering,search to number the lines before the search; the other features keep their places"
+    )]
+    feature_order: Vec<String>,
+
+    #[clap(
+        long = "unique",
+        action = clap::ArgAction::SetTrue,
+        help = "Drop lines equal to the line right before them, like `uniq`"
+    )]
+    unique: bool,
+
+    #[clap(
+        long = "unique-global",
+        action = clap::ArgAction::SetTrue,
+        help = "Drop every line seen before anywhere in the input, remembering up to 256 MiB of distinct lines"
+    )]
+    unique_global: bool,
+
+    #[clap(
+        long = "skip-head",
+        value_name = "N",
+        help = "Drop the first N lines of every input, e.g. the header of a CSV file"
+    )]
+    skip_head: Option<usize>,
+
+    #[clap(
+        long = "skip-tail",
+        value_name = "N",
+        help = "Drop the last N lines of every input, e.g. a trailing summary. The input is still streamed"
+    )]
+    skip_tail: Option<usize>,
+
+    #[clap(
+        long = "sample",
+        value_name = "N",
+        conflicts_with = "sample_percent",
+        help = "Print N lines picked at random from the whole output in a single pass (reservoir sampling), in their order"
+    )]
+    sample: Option<usize>,
+
+    #[clap(
+        long = "sample-percent",
+        value_name = "P",
+        value_parser = parse_percent,
+        help = "Print a random subset of about P percent of the lines"
+    )]
+    sample_percent: Option<f64>,
+
+    #[clap(
+        long = "search", 
+        action = clap::ArgAction::SetTrue, 
+        help = "Search text inside the file. Returns all lines containing the text."
+    )]
+    search_flag: bool,
+    
+    #[clap(
+        long = "text",
+        help = "Search text: only considered when --search flag is used. Use 'reg:' prefix for regex search, e.g., 'reg:\\\\w+' for words."
+    )]
+    search_text: Option<String>,
+
+    #[clap(
+        long = "summary",
+        action = clap::ArgAction::SetTrue,
+        requires = "search_flag",
+        help = "After the search, append a footer counting the matches, the files they are in and their first lines"
+    )]
+    summary: bool,
+    
+    #[clap(
+        short = 'i',
+        long = "ignore-case",
+        help = "Ignore case for searching text: only considered when --search flag is used.",
+        action=clap::ArgAction::SetTrue,
+    )]
+    ignore_case: bool,
+    
+
+    #[clap(
+        long = "pages",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always",
+        help = "Apply Pagination to the output: `auto` paginates only when the output does not fit the screen"
+    )]
+    pagination: Option<PagesMode>,
+
+    #[clap(
+        long = "pager-long-lines",
+        value_enum,
+        help = "How the pager shows lines wider than the terminal: `wrap` them or `scroll` with left/right arrow keys"
+    )]
+    pager_long_lines: Option<LongLineMode>,
+
+    #[clap(
+        long = "keep-line-endings",
+        action = clap::ArgAction::SetTrue,
+        help = "Keep the original line endings (CRLF, missing final newline) when features are applied"
+    )]
+    keep_line_endings: bool,
+
+    #[clap(
+        long = "show-offsets",
+        value_enum,
+        value_name = "BASE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "dec",
+        help = "Prefix every line with the byte offset where it starts in the input, in `dec` (default) or `hex` like xxd"
+    )]
+    show_offsets: Option<OffsetFormat>,
+
+    #[clap(
+        long = "output-format",
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with = "summary",
+        help = "Write the lines as `text` (default) or as `json` lines, one object per line with the input name, line number and text"
+    )]
+    output_format: Option<OutputFormat>,
+
+    #[clap(
+        long = "format",
+        value_name = "TEMPLATE",
+        value_parser = OutputTemplate::parse,
+        conflicts_with_all = ["output_format", "show_offsets", "summary"],
+        help = "Lay out every line by the template, with the placeholders {file}, {line}, {offset} (in bytes), {text} and {match} (the text the search found), e.g. '{file}:{line}: {text}'"
+    )]
+    format: Option<OutputTemplate>,
+
+    #[clap(
+        long = "stats",
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "only",
+        help = "Count the lines, words, bytes and the longest line of every file and in total, like `wc`. `only` (default) prints just the counts, `summary` prints them after the content"
+    )]
+    stats: Option<StatsMode>,
+
+    #[clap(
+        long = "length-histogram",
+        value_name = "BUCKET_WIDTH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        conflicts_with = "stats",
+        help = "Print a histogram of the line lengths instead of the content, in buckets of BUCKET_WIDTH characters (10 by default)"
+    )]
+    length_histogram: Option<usize>,
+
+    #[clap(
+        short = 'z',
+        long = "zero-terminated",
+        action = clap::ArgAction::SetTrue,
+        help = "Lines are terminated by NUL instead of newline on input and output, like `grep -z`, e.g. for `find -print0` file lists"
+    )]
+    zero_terminated: bool,
+
+    #[clap(
+        long = "from-encoding",
+        value_enum,
+        value_name = "ENCODING",
+        help = "Transcode the input from this character encoding to UTF-8 before processing: utf-16le, utf-16be, latin1, or auto to detect it"
+    )]
+    from_encoding: Option<SourceEncoding>,
+
+    #[clap(
+        long = "max-line-bytes",
+        value_name = "BYTES",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "Never read more than BYTES of a single line at once, longer lines are split or truncated with a warning. Guards against huge files without line breaks"
+    )]
+    max_line_bytes: Option<usize>,
+
+    #[clap(
+        long = "oversized-lines",
+        value_enum,
+        default_value_t = OversizeMode::Chunk,
+        requires = "max_line_bytes",
+        help = "What happens to lines longer than --max-line-bytes: split them into chunks, or truncate them"
+    )]
+    oversized_lines: OversizeMode,
+
+    #[clap(
+        long = "validate-utf8",
+        action = clap::ArgAction::SetTrue,
+        help = "Report the line, column and byte offset of every invalid UTF-8 sequence instead of printing the content. Fails when one is found"
+    )]
+    validate_utf8: bool,
+
+    #[clap(
+        long = "yaml",
+        action = clap::ArgAction::SetTrue,
+        conflicts_with = "toml",
+        help = "Parse the input as YAML and print it well-formatted, parse errors are reported with line and column"
+    )]
+    yaml: bool,
+
+    #[clap(
+        long = "toml",
+        action = clap::ArgAction::SetTrue,
+        help = "Parse the input as TOML and print it well-formatted, parse errors are reported with line and column"
+    )]
+    toml: bool,
+
+    #[clap(
+        long = "table",
+        action = clap::ArgAction::SetTrue,
+        group = "delimited_mode",
+        help = "Render delimiter-separated input (CSV, TSV) as aligned columns, e.g. `ricat data.csv --table --pages`"
+    )]
+    table: bool,
+
+    #[clap(
+        long = "delimiter",
+        value_name = "CHAR",
+        requires = "delimited_mode",
+        help = "Field delimiter for --table and --interleave. Defaults to a tab for --interleave and `.tsv` tables, and `,` for other tables"
+    )]
+    delimiter: Option<char>,
+
+    #[clap(
+        long = "interleave",
+        action = clap::ArgAction::SetTrue,
+        group = "delimited_mode",
+        help = "Merge the files line by line, the equivalent of `paste(1)`: line 1 of every file joined by the delimiter, then line 2, ..."
+    )]
+    interleave: bool,
+
+    #[clap(
+        long = "table-header",
+        action = clap::ArgAction::SetTrue,
+        requires = "table",
+        help = "With --table, set the first row off as a header, drawn bold on a terminal"
+    )]
+    table_header: bool,
+
+    #[clap(
+        long = "diff",
+        action = clap::ArgAction::SetTrue,
+        help = "Compare two files and print a unified diff, colored on a terminal, e.g. `ricat --diff old.txt new.txt`"
+    )]
+    diff: bool,
+
+    #[clap(
+        long = "diff-context",
+        value_name = "LINES",
+        default_value_t = DEFAULT_CONTEXT,
+        help = "Number of unchanged lines shown around each change with --diff"
+    )]
+    diff_context: usize,
+
+    #[clap(
+        long = "side-by-side",
+        action = clap::ArgAction::SetTrue,
+        conflicts_with = "diff",
+        help = "Show two files in parallel columns sized to the terminal, differing lines highlighted, e.g. `ricat --side-by-side a.conf b.conf`"
+    )]
+    side_by_side: bool,
+
+    #[clap(
+        long = "crlf-to-lf",
+        action = clap::ArgAction::SetTrue,
+        conflicts_with = "lf_to_crlf",
+        help = "Convert CRLF (Windows) line endings to LF (dos2unix)"
+    )]
+    crlf_to_lf: bool,
+
+    #[clap(
+        long = "lf-to-crlf",
+        action = clap::ArgAction::SetTrue,
+        help = "Convert LF (Unix) line endings to CRLF (unix2dos)"
+    )]
+    lf_to_crlf: bool,
+
+    #[clap(
+        long = "show-filenames",
+        action = clap::ArgAction::SetTrue,
+        help = "Print a `==> filename <==` banner before the content of each file"
+    )]
+    show_filenames: bool,
+
+    #[clap(
+        short = 'R',
+        long = "recursive",
+        action = clap::ArgAction::SetTrue,
+        help = "Read all files under directories recursively, with a banner before each file. Glob patterns like 'src/**/*.rs' are expanded"
+    )]
+    recursive: bool,
+
+    #[clap(
+        short = 'j',
+        long = "jobs",
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "Process up to N files at the same time when every feature works on each line alone (search, -d, -t, the encodings, ...), one per core by default when two or more regular files are given. The output keeps the order of the files, the standard input and FIFOs are always streamed"
+    )]
+    jobs: Option<usize>,
+
+    #[clap(
+        long = "files-from",
+        value_name = "LIST",
+        conflicts_with = "files_from0",
+        help = "Read the names of the input files from LIST, one per line (`-` reads the list from standard input)"
+    )]
+    files_from: Option<String>,
+
+    #[clap(
+        long = "files-from0",
+        value_name = "LIST",
+        help = "Read NUL-separated names of the input files from LIST, e.g. the output of `find -print0`"
+    )]
+    files_from0: Option<String>,
+
+    #[clap(
+        long = "paste",
+        action = clap::ArgAction::SetTrue,
+        help = "Read the clipboard contents instead of standard input, before any file given (needs the `clipboard` feature)"
+    )]
+    paste: bool,
+
+    #[clap(
+        long = "listen-unix",
+        value_name = "SOCKET",
+        conflicts_with_all = ["files", "files_from", "files_from0", "paste"],
+        help = "Listen on a Unix domain socket and process the text streamed in by every client connecting to it"
+    )]
+    listen_unix: Option<String>,
+
+    #[clap(
+        long = "listen",
+        value_name = "[HOST:]PORT",
+        value_parser = listen_address,
+        conflicts_with_all = ["files", "files_from", "files_from0", "paste", "listen_unix"],
+        help = "Listen on a TCP port, like `nc -l`, and process the text streamed in by every client connecting to it. A port alone listens on 127.0.0.1 only, give a host like `0.0.0.0:9000` to accept other machines"
+    )]
+    listen: Option<String>,
+
+    #[clap(
+        long = "connect",
+        value_name = "HOST:PORT",
+        conflicts_with_all = ["output", "tee"],
+        help = "Send the output over a TCP connection to HOST:PORT instead of standard output"
+    )]
+    connect: Option<String>,
+
+    #[clap(
+        long = "progress",
+        action = clap::ArgAction::SetTrue,
+        help = "Show a progress bar with throughput and ETA on stderr while copying files. Shown by default for large files when the output is not a terminal"
+    )]
+    progress: bool,
+
+    #[clap(long = "sha256", action = clap::ArgAction::SetTrue, help = "Print the SHA-256 checksum of every input instead of its content, like sha256sum")]
+    sha256: bool,
+
+    #[clap(long = "md5", action = clap::ArgAction::SetTrue, help = "Print the MD5 checksum of every input instead of its content, like md5sum")]
+    md5: bool,
+
+    #[clap(long = "crc32", action = clap::ArgAction::SetTrue, help = "Print the CRC-32 checksum of every input instead of its content")]
+    crc32: bool,
+
+    #[clap(
+        long = "checksum-with-output",
+        action = clap::ArgAction::SetTrue,
+        help = "With --sha256/--md5/--crc32, print the content as usual, through the features, and the checksums of all the output written on stderr (before --compress and --encrypt)"
+    )]
+    checksum_with_output: bool,
+
+    #[clap(
+        long = "hash-lines",
+        value_enum,
+        value_name = "ALGORITHM",
+        help = "Replace every line with its hash: sha1, sha256, md5 or crc32"
+    )]
+    hash_lines: Option<ChecksumAlgorithm>,
+
+    #[clap(
+        long = "hash-lines-annotate",
+        action = clap::ArgAction::SetTrue,
+        requires = "hash_lines",
+        help = "With --hash-lines, keep the line and put its hash in front of it"
+    )]
+    hash_lines_annotate: bool,
+
+    #[clap(
+        long = "typewriter",
+        value_name = "DELAY_MS",
+        require_equals = true,
+        num_args = 0..=1,
+        default_missing_value = "30",
+        help = "Print the output slowly, pausing DELAY_MS milliseconds (default 30) after every character, for demos. Use `--typewriter=100`"
+    )]
+    typewriter: Option<u64>,
+
+    #[clap(
+        long = "typewriter-by",
+        value_enum,
+        default_value = "char",
+        help = "Pause after every `char` or every `line` in typewriter mode"
+    )]
+    typewriter_by: TypewriterUnit,
+
+    #[clap(
+        long = "decompress",
+        action = clap::ArgAction::SetTrue,
+        help = "Decompress compressed input (gzip, zstd, xz, bzip2) even when no feature is applied, like zcat. Input processed by features is always decompressed"
+    )]
+    decompress: bool,
+
+    #[clap(
+        long = "binary",
+        value_enum,
+        require_equals = true,
+        help = "How to handle binary files: `skip` prints a notice instead of the content, `hex` shows a hex view. Binary files are skipped by default when writing to a terminal"
+    )]
+    binary: Option<BinaryMode>,
+
+    #[clap(
+        long = "force-binary",
+        action = clap::ArgAction::SetTrue,
+        conflicts_with = "binary",
+        help = "Print binary files as they are, even to a terminal"
+    )]
+    force_binary: bool,
+
+    #[clap(
+        short = 'o',
+        long = "output",
+        value_name = "FILE",
+        conflicts_with = "tee",
+        help = "Write the output to FILE instead of standard output"
+    )]
+    output: Option<String>,
+
+    #[clap(
+        long = "tee",
+        value_name = "FILE",
+        help = "Write the output to FILE and to standard output"
+    )]
+    tee: Option<String>,
+
+    #[clap(
+        long = "append",
+        action = clap::ArgAction::SetTrue,
+        help = "Append to the file given by -o/--tee instead of overwriting it"
+    )]
+    append: bool,
+
+    #[clap(
+        long = "fsync",
+        action = clap::ArgAction::SetTrue,
+        help = "Sync the file given by -o/--tee to disk before exiting"
+    )]
+    fsync: bool,
+
+    #[clap(
+        short = 'u',
+        long = "unbuffered",
+        action = clap::ArgAction::SetTrue,
+        help = "Write every line out as soon as it is processed, and every read of a plain copy as soon as it is read, like POSIX `cat -u`"
+    )]
+    unbuffered: bool,
+
+    #[clap(
+        long = "buffer-size",
+        value_name = "BYTES",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=MAX_BUFFER_SIZE),
+        help = "Size of the reads of a plain copy and of the output buffer, 128 KiB by default and at most 1 GiB"
+    )]
+    buffer_size: Option<usize>,
+
+    #[clap(
+        long = "fail-fast",
+        action = clap::ArgAction::SetTrue,
+        help = "Stop at the first input which cannot be read, instead of reporting it and carrying on with the others like GNU cat"
+    )]
+    fail_fast: bool,
+
+    #[clap(
+        long = "no-config",
+        action = clap::ArgAction::SetTrue,
+        help = "Ignore the configuration file, for output which does not depend on who runs it. RICAT_* environment variables still apply"
+    )]
+    no_config: bool,
+
+    #[clap(
+        long = "profile",
+        value_name = "NAME",
+        help = "Apply the settings of the [profiles.NAME] section of the configuration over the others, e.g. `--profile logs` (or RICAT_PROFILE=NAME)"
+    )]
+    profile: Option<String>,
+
+    #[clap(
+        long = "max-memory",
+        value_name = "BYTES",
+        value_parser = parse_byte_size,
+        help = "Hold at most BYTES of input and lines in memory, e.g. `64M` or `1G`: the pager, --table, --yaml, --diff and --whole-file stop with an error past it, --unique-global stops deduplicating and --jobs processes one file at a time"
+    )]
+    max_memory: Option<usize>,
+
+    #[clap(
+        long = "io-backend",
+        value_enum,
+        value_name = "BACKEND",
+        help = "How plain copies read the files: `auto` copies them in the kernel when it can, otherwise reads small files and maps large ones to memory; `read`, `mmap`, or `uring` through io_uring (experimental, Linux, `io-uring` feature). Pipes and devices are always read"
+    )]
+    io_backend: Option<IoBackend>,
+
+    #[clap(
+        long = "mmap-threshold",
+        value_name = "BYTES",
+        help = "Size from which `--io-backend auto` maps files to memory instead of reading them, 1 MiB by default"
+    )]
+    mmap_threshold: Option<u64>,
+
+    #[clap(
+        long = "compress",
+        value_enum,
+        value_name = "FORMAT",
+        help = "Compress the output (to standard output or -o) with gzip or zstd"
+    )]
+    compress: Option<OutputCompression>,
+
+    #[clap(
+        long = "encrypt",
+        action = clap::ArgAction::SetTrue,
+        help = "Encrypt the output with a passphrase (age format), asked for on the terminal or taken from RICAT_PASSPHRASE (needs the `encryption` feature)"
+    )]
+    encrypt: bool,
+
+    #[clap(
+        long = "decrypt",
+        action = clap::ArgAction::SetTrue,
+        conflicts_with = "encrypt",
+        help = "Decrypt passphrase-encrypted input before processing it (needs the `encryption` feature)"
+    )]
+    decrypt: bool,
+
+    #[clap(long = "encode-base64", action = clap::ArgAction::SetTrue, help = "Encode the input text using Base64")]
+    encode: bool,
+
+    #[clap(long = "decode-base64", action = clap::ArgAction::SetTrue, help = "Decode the input text using Base64")]
+    decode: bool,
+
+    #[clap(
+        long = "whole-file",
+        action = clap::ArgAction::SetTrue,
+        help = "With --encode-base64/--decode-base64, treat the whole input as one byte stream instead of encoding every line, wrapping the encoded output at 76 columns like `base64`"
+    )]
+    whole_file: bool,
+
+    #[clap(
+        long = "encode",
+        value_enum,
+        value_name = "CODEC",
+        value_delimiter = ',',
+        help = "Encode every line with the given codecs, in order (e.g. `base64,hex`): base64, base32, hex, ascii85 (base85), z85, html or json"
+    )]
+    encode_codec: Vec<Codec>,
+
+    #[clap(
+        long = "decode",
+        value_enum,
+        value_name = "CODEC",
+        value_delimiter = ',',
+        help = "Decode every line with the given codecs, in order (e.g. `hex,base64`): base64, base32, hex, ascii85 (base85), z85, html or json"
+    )]
+    decode_codec: Vec<Codec>,
+
+    #[clap(long = "encode-base32", action = clap::ArgAction::SetTrue, help = "Encode the input text using Base32")]
+    encode_base32: bool,
+
+    #[clap(long = "decode-base32", action = clap::ArgAction::SetTrue, help = "Decode the input text using Base32")]
+    decode_base32: bool,
+
+    #[clap(long = "html-escape", action = clap::ArgAction::SetTrue, help = "Escape `&`, `<`, `>` and quotes as HTML entities")]
+    html_escape: bool,
+
+    #[clap(long = "html-unescape", action = clap::ArgAction::SetTrue, help = "Unescape HTML entities like `&lt;`, `&amp;` and `&#x27;`")]
+    html_unescape: bool,
+
+    #[clap(long = "json-escape", action = clap::ArgAction::SetTrue, help = "Escape every line as the content of a JSON string")]
+    json_escape: bool,
+
+    #[clap(long = "json-unescape", action = clap::ArgAction::SetTrue, help = "Expand JSON string escapes like `\\n`, `\\\"` and `\\uXXXX` into readable text")]
+    json_unescape: bool,
+
+    #[clap(long = "encode-hex", action = clap::ArgAction::SetTrue, help = "Encode the input text as hex, two digits per byte")]
+    encode_hex: bool,
+
+    #[clap(long = "decode-hex", action = clap::ArgAction::SetTrue, help = "Decode hex encoded text, whitespace between the bytes is allowed")]
+    decode_hex: bool,
+
+    #[clap(
+        long = "expand-tabs",
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Replace tabs with spaces up to the next tab stop every N columns (default 8, or `tab_width` from the config file). Use `--expand-tabs=4`"
+    )]
+    expand_tabs: Option<Option<u32>>,
+
+    #[clap(
+        long = "unexpand",
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        conflicts_with = "expand_tabs",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Convert the leading spaces of every line into tabs at tab stops every N columns (default 8, or `tab_width` from the config file)"
+    )]
+    unexpand: Option<Option<u32>>,
+
+    #[clap(
+        long = "wrap",
+        value_name = "N|auto",
+        value_parser = parse_wrap_width,
+        help = "Fold lines longer than N columns at word boundaries, like `fold -s`. `auto` uses the width of the terminal"
+    )]
+    wrap: Option<WrapWidth>,
+
+    #[clap(
+        long = "fields",
+        value_name = "LIST",
+        value_parser = parse_field_list,
+        help = "Cut the given fields out of every line like `cut -f`, e.g. `--fields 1,3-5 --field-delim ','`. Fields count from 1"
+    )]
+    fields: Option<FieldList>,
+
+    #[clap(
+        long = "field-delim",
+        value_name = "DELIM",
+        default_value = "\t",
+        requires = "fields",
+        value_parser = parse_field_delim,
+        help = "Delimiter between the fields for --fields"
+    )]
+    field_delim: String,
+
+    #[clap(
+        long = "output-delim",
+        value_name = "DELIM",
+        requires = "fields",
+        help = "Delimiter joining the fields cut with --fields, the --field-delim by default"
+    )]
+    output_delim: Option<String>,
+
+    #[clap(
+        long = "columns",
+        value_name = "EXPRESSION",
+        value_parser = parse_column_list,
+        conflicts_with = "fields",
+        help = "Print the given whitespace-separated columns of every line like awk, e.g. `--columns '$1 $NF'`. `$(NF-1)` is the column before the last"
+    )]
+    columns: Option<ColumnList>,
+
+    #[clap(
+        long = "rev",
+        action = clap::ArgAction::SetTrue,
+        help = "Reverse the characters of every line, like `rev`"
+    )]
+    rev: bool,
+
+    #[clap(
+        long = "script",
+        value_name = "SCRIPT",
+        value_parser = parse_script,
+        help = "Run a Rhai script on every line, e.g. `--script 'line.to_uppercase()'`. It gets `line`, `line_number` and `file`, and returns the new line, an array of lines, or nothing to drop the line"
+    )]
+    script: Option<Script>,
+
+    #[clap(
+        long = "script-file",
+        value_name = "FILE",
+        value_parser = parse_script_file,
+        conflicts_with = "script",
+        help = "Run the Rhai script in FILE on every line, like --script"
+    )]
+    script_file: Option<Script>,
+
+    #[clap(
+        long = "show-whitespace",
+        action = clap::ArgAction::SetTrue,
+        help = "Show spaces as `·`, tabs as `→` and non-breaking spaces as `␣`, dimmed on a terminal, to debug indentation"
+    )]
+    show_whitespace: bool,
+
+    #[clap(
+        long = "highlight-trailing",
+        action = clap::ArgAction::SetTrue,
+        help = "Paint trailing spaces and tabs red. Only applied when writing to a terminal"
+    )]
+    highlight_trailing: bool,
+
+    #[clap(long = "trim-trailing", action = clap::ArgAction::SetTrue, help = "Remove trailing spaces and tabs from every line")]
+    trim_trailing: bool,
+
+    #[clap(
+        long = "strip-ansi",
+        action = clap::ArgAction::SetTrue,
+        help = "Remove ANSI color and cursor escape sequences from every line, e.g. to clean colored CI logs before archiving"
+    )]
+    strip_ansi: bool,
+
+    #[clap(
+        long = "delta",
+        action = clap::ArgAction::SetTrue,
+        help = "Prefix every line with the time elapsed since the previous line was read, to spot slow spots when replaying logs through stdin"
+    )]
+    delta: bool,
+
+    #[clap(
+        long = "rainbow",
+        action = clap::ArgAction::SetTrue,
+        help = "Color the output with a rolling rainbow gradient, like lolcat. Only applied when writing to a terminal"
+    )]
+    rainbow: bool,
+
+    #[clap(
+        long = "log-levels",
+        action = clap::ArgAction::SetTrue,
+        help = "Color the level of log lines (ERROR, WARN, INFO, DEBUG, ...) with the colors of the theme. Only applied when writing to a terminal"
+    )]
+    log_levels: bool,
+
+    #[clap(
+        long = "theme",
+        value_enum,
+        value_name = "THEME",
+        help = "Colors of the output on a terminal: line numbers, search matches, log levels, the pager prompt, diffs, ... The [theme] section of the configuration changes single colors (or RICAT_THEME=THEME)"
+    )]
+    theme: Option<ThemeName>,
+
+    /// Optional file path to read from instead of standard input.
+    #[clap(help = "File(s) you want to read, multiple files will be appended one after another")]
+    files: Vec<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// inputs which could not be read and were skipped, ricat fails once the others are written
+    #[clap(skip)]
+    failed_inputs: AtomicUsize,
+
+    /// colors of the `[theme]` section, applied over the theme
+    #[clap(skip)]
+    theme_colors: ThemeSettings,
+
+    /// the regular file the output goes to, an input which is that file is refused
+    #[clap(skip)]
+    output_identity: Option<FileIdentity>,
+}
+
+// DEBUG print if each feature is enabled or not
+// fn see_features(arguments: &Cli) {
+//     println!("Features Enabled:");
+//     println!("Line Numbers: {}", arguments.numbers);
+//     println!("Dollar Symbol: {}", arguments.dollar);
+//     println!("Tabs: {}", arguments.tabs);
+//     println!("Compress Empty Lines: {}", arguments.squeeze_blank);
+//     println!("Search: {}", arguments.search_flag);
+//     println!("Pagination: {}", arguments.pagination);
+//     println!("Encode Base64: {}", arguments.encode);
+//     println!("Decode Base64: {}", arguments.decode);
+//     println!();
+// }
+
+/// Starts Executing Ricat with the command line arguments
+pub fn run() -> Result<(), RicatError> {
+    let mut arguments = Cli::parse();
+
+    // the subcommands about ricat itself run before the configuration is loaded, which creates it on the first run
+    match &arguments.command {
+        Some(Command::Completions { shell }) => return write_completions(*shell, &mut stdout().lock()),
+        Some(Command::Config { action }) => return run_config_command(action, &mut stdout().lock()),
+        _ => {}
+    }
+
+    // Load the configuration file, the environment overrides it
+    let mut configuration = if arguments.no_config { config::RicatConfig::default() } else { load_config()? };
+    if let Some(profile) = arguments.profile.clone().or_else(|| std::env::var("RICAT_PROFILE").ok()) {
+        configuration.select_profile(&profile)?;
+    }
+    apply_env_overrides(&mut configuration, ricat_env_vars())?;
+
+    enable_features_from_config(&configuration, &mut arguments);
+    if let Some(Command::Bench { file, runs }) = &arguments.command {
+        return run_bench(&arguments, file, *runs, &mut stdout().lock());
+    }
+    if arguments.recursive {
+        arguments.show_filenames = true;
+    }
+    // every JSON or templated line can name its input, no banner goes between them
+    if arguments.output_format == Some(OutputFormat::Json) || arguments.format.is_some() {
+        arguments.show_filenames = false;
+    }
+    let mut features = add_features_from_args(&arguments)?; // stores the implemented features
+       
+
+    // line endings, offsets, JSON and templated output are only handled by the line pipeline, even when no feature is enabled
+    let needs_line_pipeline = !features.is_empty()
+        || ending_conversion_from_args(&arguments).is_some()
+        || arguments.from_encoding.is_some()
+        || arguments.show_offsets.is_some()
+        || arguments.output_format.is_some()
+        || arguments.format.is_some();
+
+    let target = output_target(&arguments);
+    if target != OutputTarget::Stdout
+        || arguments.compress.is_some()
+        || arguments.encrypt
+        || arguments.typewriter.is_some()
+        || arguments.zero_terminated
+        || arguments.checksum_with_output
+    {
+        // the pager only makes sense when plain output goes to the terminal alone
+        arguments.pagination = None;
+    }
+    arguments.output_identity = output_identity(&target);
+    refuse_output_among_inputs(&arguments, &target)?;
+    let file_options = OutputFileOptions {
+        append: arguments.append,
+        fsync: arguments.fsync,
+        buffer_size: arguments.buffer_size,
+    };
+    let OpenedOutput { writer: mut output, direct: direct_output, sync: output_sync } =
+        open_output_direct(&target, file_options)?;
+    // compression, encryption and the typewriter change the output or its pace, the kernel must not write past them,
+    // nor past the checksums of `--checksum-with-output`
+    let direct_output = direct_output.filter(|_| {
+        arguments.compress.is_none()
+            && !arguments.encrypt
+            && arguments.typewriter.is_none()
+            && !arguments.checksum_with_output
+    });
+    if let Some(delay_ms) = arguments.typewriter {
+        output = Box::new(TypewriterWriter::new(output, Duration::from_millis(delay_ms), arguments.typewriter_by));
+    }
+    // the passphrase is asked for before reading any input, the prompt must not wait behind a blocking read
+    let passphrase = if arguments.encrypt || arguments.decrypt {
+        Some(read_passphrase(arguments.encrypt)?)
+    } else {
+        None
+    };
+    let output = EncryptedWriter::new(output, passphrase.clone().filter(|_| arguments.encrypt))?;
+    let mut output_file = CompressedWriter::new(output, arguments.compress)?;
+    // `--checksum-with-output` hashes the output the pipeline writes, before it is compressed or encrypted
+    let algorithms = checksum_algorithms(&arguments);
+    let digested = if arguments.checksum_with_output { algorithms.clone() } else { Vec::new() };
+    let mut output = DigestWriter::new(&mut output_file, &digested);
+
+    // Determine the input source based on command line arguments
+    if let Some(socket_path) = &arguments.listen_unix {
+        let reader = listen_unix(socket_path)?;
+        handle_socket_input(reader, socket_path, &arguments, &mut features, &mut output)?;
+    } else if let Some(address) = &arguments.listen {
+        let reader = listen_tcp(address)?;
+        handle_socket_input(reader, address, &arguments, &mut features, &mut output)?;
+    } else if !algorithms.is_empty() && !arguments.checksum_with_output {
+        handle_checksums(&algorithms, &arguments, &mut output)?;
+    } else if arguments.validate_utf8 {
+        handle_utf8_validation(&arguments, &mut output)?;
+    } else if let Some(format) = structured_format(&arguments) {
+        handle_structured_input(format, &arguments, &mut features, &mut output)?;
+    } else if arguments.table {
+        handle_table(&arguments, &mut features, &mut output)?;
+    } else if let Some(mode) = arguments.stats {
+        handle_stats(mode, &arguments, &mut features, &mut output)?;
+    } else if let Some(bucket_width) = arguments.length_histogram {
+        handle_length_histogram(bucket_width, &arguments, &mut features, &mut output)?;
+    } else if let Some(size) = arguments.sample {
+        handle_sample(size, &arguments, &mut features, &mut output)?;
+    } else if arguments.interleave {
+        handle_interleave(&arguments, &mut features, &mut output)?;
+    } else if arguments.diff {
+        handle_diff(&arguments, &mut features, &mut output)?;
+    } else if arguments.side_by_side {
+        handle_side_by_side(&arguments, &mut features, &mut output)?;
+    } else if let Some(passphrase) = passphrase.filter(|_| arguments.decrypt) {
+        handle_decrypted_input(&passphrase, &arguments, &mut features, &mut output)?;
+    } else if arguments.whole_file && (arguments.encode || arguments.decode) {
+        handle_whole_stream_base64(&arguments, &mut features, &mut output)?;
+    } else {
+        match (!has_file_inputs(&arguments), needs_line_pipeline) {
+            (true, false) => handle_via_std_output(&arguments, &mut output)?,
+            (true, true) | (false, true) => handle_files_or_features(&arguments, &mut features, &mut output)?,
+            (false, false) => handle_files_without_features(&arguments, direct_output.as_ref(), &mut output)?,
+        }
+    }
+
+    let digests = output.finalize();
+    output_file.finish()?.finish()?.flush().map_err(|error| {
+        RicatError::OutputFlushError(format!("Error flushing output: {}", error))
+    })?;
+    if let Some(output_sync) = output_sync {
+        output_sync.sync()?;
+    }
+    if arguments.checksum_with_output {
+        write_lines_to(checksum_lines("-", &digests), &mut stderr())?;
+    }
+    match arguments.failed_inputs.load(Ordering::Relaxed) {
+        0 => Ok(()),
+        failed => Err(RicatError::InputsFailed(failed)),
+    }
+}
+
+/// Output destination given by `-o`/`--tee`/`--connect`
+fn output_target(arguments: &Cli) -> OutputTarget {
+    if let Some(address) = &arguments.connect {
+        return OutputTarget::Tcp(address.clone());
+    }
+    match (&arguments.output, &arguments.tee) {
+        (Some(path), _) => OutputTarget::File(path.clone()),
+        (None, Some(path)) => OutputTarget::Tee(path.clone()),
+        (None, None) => OutputTarget::Stdout,
+    }
+}
+
+/// Whether inputs other than the standard input are given: files, a file list or `--paste`
+fn has_file_inputs(arguments: &Cli) -> bool {
+    !arguments.files.is_empty()
+        || arguments.files_from.is_some()
+        || arguments.files_from0.is_some()
+        || arguments.paste
+}
+
+/// All input sources: the clipboard with `--paste`, the file arguments, then the entries of `--files-from`/`--files-from0`.
+/// File lists are read lazily, and each source is only opened when it is reached.
+fn input_sources(
+    arguments: &Cli,
+) -> Result<Box<dyn Iterator<Item = Result<InputSource, RicatError>>>, RicatError> {
+    let clipboard = arguments.paste.then_some(Ok(InputSource::Clipboard));
+    // every argument is expanded alone, a pattern matching nothing fails as that input only
+    let mut expanded = Vec::new();
+    for file in &arguments.files {
+        match sources_from_args(std::slice::from_ref(file), arguments.recursive) {
+            Ok(sources) => expanded.extend(sources.into_iter().map(Ok)),
+            Err(error) => expanded.push(Err(error)),
+        }
+    }
+    let sources = clipboard.into_iter().chain(expanded);
+
+    let list = match (&arguments.files_from, &arguments.files_from0) {
+        (Some(list_path), _) => Some(file_list(list_path, b'\n')?),
+        (None, Some(list_path)) => Some(file_list(list_path, b'\0')?),
+        (None, None) => None,
+    };
+
+    let sources: Box<dyn Iterator<Item = Result<InputSource, RicatError>>> = match list {
+        Some(list) => Box::new(sources.chain(list)),
+        None => Box::new(sources),
+    };
+    match arguments.output_identity {
+        Some(output) => Ok(Box::new(sources.map(move |source| refuse_output_file(source, output)))),
+        None => Ok(sources),
+    }
+}
+
+/// Refuses to open the `-o`/`--tee` file when it is one of the inputs, opening it would truncate the input
+/// before it is read. A list of `--files-from` read from the standard input cannot be read twice, only the
+/// file arguments are looked at then.
+fn refuse_output_among_inputs(arguments: &Cli, target: &OutputTarget) -> Result<(), RicatError> {
+    let truncated = matches!(target, OutputTarget::File(_) | OutputTarget::Tee(_)) && !arguments.append;
+    let Some(output) = arguments.output_identity.filter(|_| truncated) else {
+        return Ok(());
+    };
+    let list_from_stdin = [&arguments.files_from, &arguments.files_from0]
+        .iter()
+        .any(|list| list.as_deref() == Some("-"));
+    if list_from_stdin {
+        for file in &arguments.files {
+            let sources = sources_from_args(std::slice::from_ref(file), arguments.recursive).unwrap_or_default();
+            for source in sources {
+                refuse_output_file(Ok(source), output)?;
+            }
+        }
+        return Ok(());
+    }
+    // the inputs which cannot be read are reported once they are reached
+    if let Ok(sources) = input_sources(arguments) {
+        for source in sources {
+            if let Err(error @ RicatError::InputIsOutputError(_)) = source {
+                return Err(error);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Refuses a file input which is the output file, like GNU cat: reading it would follow the lines written to it.
+/// An empty file has nothing to read and is let through.
+fn refuse_output_file(
+    source: Result<InputSource, RicatError>,
+    output: FileIdentity,
+) -> Result<InputSource, RicatError> {
+    if let Ok(InputSource::File(path)) = &source {
+        if let Ok(metadata) = std::fs::metadata(path) {
+            if metadata.len() > 0 && FileIdentity::of(&metadata) == Some(output) {
+                return Err(RicatError::InputIsOutputError(path.clone()));
+            }
+        }
+    }
+    source
+}
+
+/// The input sources, or the standard input alone when no file is given
+fn input_sources_or_stdin(arguments: &Cli) -> Result<Vec<Result<InputSource, RicatError>>, RicatError> {
+    if has_file_inputs(arguments) {
+        Ok(input_sources(arguments)?.collect())
+    } else {
+        Ok(vec![Ok(InputSource::Stdin)])
+    }
+}
+
+/// Line ending conversion requested by `--crlf-to-lf` / `--lf-to-crlf`
+fn ending_conversion_from_args(arguments: &Cli) -> Option<EndingConversion> {
+    if arguments.crlf_to_lf {
+        Some(EndingConversion::CrLfToLf)
+    } else if arguments.lf_to_crlf {
+        Some(EndingConversion::LfToCrLf)
+    } else {
+        None
+    }
+}
+
+/// How the processed lines are read and written, from `--keep-line-endings`, the ending conversions, `--max-line-bytes`,
+/// `-z` and `--show-offsets`
+fn output_options(arguments: &Cli) -> OutputOptions {
+    OutputOptions {
+        keep_line_endings: arguments.keep_line_endings,
+        ending_conversion: ending_conversion_from_args(arguments),
+        line_limit: line_limit(arguments),
+        zero_terminated: arguments.zero_terminated,
+        offset_format: arguments.show_offsets,
+        // `-u` writes a line out before the next one arrives, it is not waited for to know whether the line is the last
+        streaming: arguments.unbuffered,
+        unbuffered: arguments.unbuffered,
+        output_format: arguments.output_format.unwrap_or_default(),
+        template: output_template(arguments).map(Arc::new),
+    }
+}
+
+/// The `--format` template, with the pattern of the search for `{match}`
+fn output_template(arguments: &Cli) -> Option<OutputTemplate> {
+    let template = arguments.format.clone()?;
+    let search = arguments.search_text.as_deref().filter(|_| arguments.search_flag);
+    match search.and_then(|text| Regex::new(&search_pattern(text.trim(), arguments.ignore_case)).ok()) {
+        Some(search) => Some(template.with_search(search)),
+        None => Some(template),
+    }
+}
+
+/// Writes the completion script of every flag and subcommand for the shell
+pub fn write_completions(shell: clap_complete::Shell, writer: &mut dyn Write) -> Result<(), RicatError> {
+    let mut buffer = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "ricat", &mut buffer);
+    writer
+        .write_all(&buffer)
+        .map_err(|error| RicatError::LineWriteError(format!("Error writing the completion script: {}", error)))
+}
+
+/// Output buffering requested with `--buffer-size` and `-u`
+fn buffering(arguments: &Cli) -> Buffering {
+    Buffering {
+        size: arguments.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE),
+        unbuffered: arguments.unbuffered,
+    }
+}
+
+/// Color of the line numbers, plain unless they go to a terminal as text
+fn number_style(arguments: &Cli) -> Style {
+    text_theme(arguments).map(|theme| theme.line_number).unwrap_or_default()
+}
+
+/// Layout of the line numbers from `--number-width`, `--number-align`, `--number-separator` and `--number-start`
+fn number_format(arguments: &Cli) -> NumberFormat {
+    let default = NumberFormat::default();
+    NumberFormat {
+        width: arguments.number_width.unwrap_or(default.width),
+        align: arguments.number_align.unwrap_or(default.align),
+        separator: arguments.number_separator.clone().unwrap_or(default.separator),
+        start: arguments.number_start.unwrap_or(default.start),
+    }
+}
+
+/// Line length limit requested with `--max-line-bytes`
+fn line_limit(arguments: &Cli) -> Option<LineLimit> {
+    arguments.max_line_bytes.map(|max_bytes| LineLimit {
+        max_bytes,
+        mode: arguments.oversized_lines,
+    })
+}
+
+/// handling empty files and features
+fn handle_via_std_output(arguments: &Cli, output: &mut dyn Write) -> Result<(), RicatError> {
+    if arguments.decompress {
+        copy(InputSource::Stdin.open_decompressed()?, output)?;
+    } else {
+        copy(stdin(), output)?;
+    }
+
+    Ok(())
+}
+
+/// handle text streamed in over a socket: lines are written as soon as they arrive, the pager is never used.
+/// Every line is flushed, a listener runs until it is interrupted and the `-o` or `--compress` output must not
+/// be left in a buffer then.
+fn handle_socket_input(
+    reader: Box<dyn Read>,
+    name: &str,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let options = OutputOptions { streaming: true, unbuffered: true, ..output_options(arguments) };
+
+    process_input_to(reader, name, output, features, options).map_err(processing_error)?;
+    write_lines_to(finish_features(features), output)
+}
+
+/// Checksums requested with `--sha256`, `--md5` and `--crc32`
+fn checksum_algorithms(arguments: &Cli) -> Vec<ChecksumAlgorithm> {
+    [
+        (arguments.sha256, ChecksumAlgorithm::Sha256),
+        (arguments.md5, ChecksumAlgorithm::Md5),
+        (arguments.crc32, ChecksumAlgorithm::Crc32),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, algorithm)| enabled.then_some(algorithm))
+    .collect()
+}
+
+/// handle checksums: every input is streamed through the hashers by `copy`, the data itself is discarded.
+/// With `--checksum-with-output` the usual pipeline runs instead, and the checksums of what it wrote follow on stderr
+fn handle_checksums(
+    algorithms: &[ChecksumAlgorithm],
+    arguments: &Cli,
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let sources = input_sources_or_stdin(arguments)?;
+
+    for source in sources {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        let Some(reader) = skip_unreadable(arguments, source.open())? else { continue };
+        let mut writer = DigestWriter::new(std::io::sink(), algorithms);
+        copy(reader, &mut writer)?;
+        write_lines_to(checksum_lines(&source.name(), &writer.finalize()), output)?;
+    }
+    Ok(())
+}
+
+/// handle `--validate-utf8`: every input is scanned for invalid sequences, which are reported
+/// instead of the content. Finding any of them makes ricat fail, so scripts can check the result
+fn handle_utf8_validation(arguments: &Cli, output: &mut dyn Write) -> Result<(), RicatError> {
+    let sources = input_sources_or_stdin(arguments)?;
+
+    let mut invalid = 0;
+    for source in sources {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        let Some(reader) = skip_unreadable(arguments, source.open())? else { continue };
+        let name = source.name();
+        let mut reports = Vec::new();
+        invalid += validate_utf8(BufReader::new(reader), |sequence| {
+            reports.push(sequence.report(&name))
+        })?;
+        write_lines_to(reports, output)?;
+    }
+
+    if invalid > 0 {
+        return Err(RicatError::InvalidUtf8Error(format!(
+            "{} invalid sequence(s) found",
+            invalid
+        )));
+    }
+    Ok(())
+}
+
+/// Format requested with `--yaml` / `--toml`
+fn structured_format(arguments: &Cli) -> Option<StructuredFormat> {
+    if arguments.yaml {
+        Some(StructuredFormat::Yaml)
+    } else if arguments.toml {
+        Some(StructuredFormat::Toml)
+    } else {
+        None
+    }
+}
+
+/// handle `--yaml`/`--toml`: every input is parsed as a whole and re-emitted well-formatted, then goes through the features.
+/// The first input which does not parse stops ricat with the position of the error.
+fn handle_structured_input(
+    format: StructuredFormat,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let sources = input_sources_or_stdin(arguments)?;
+    let mut budget = MemoryBudget::new(arguments.max_memory);
+
+    let mut all_processed_lines = Vec::new();
+    for (index, source) in sources.into_iter().enumerate() {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        let Some(reader) = skip_unreadable(arguments, open_text(&source, arguments))? else { continue };
+        let mut text = String::new();
+        budget.read_to_string(reader, &mut text)?;
+        let formatted = pretty_print(format, &text)
+            .map_err(|error| RicatError::StructuredParseError(error.report(&source.name())))?;
+
+        if arguments.show_filenames {
+            all_processed_lines.extend(banner_lines(&source, index));
+        }
+        all_processed_lines.extend(process_input_ret_within(formatted.as_bytes(), &source.name(), features, OutputOptions::default(), &mut budget)?);
+        budget.release(text.len());
+    }
+    all_processed_lines.extend(finish_features(features));
+    paginate_or_write(arguments, all_processed_lines, output)
+}
+
+/// handle `--table`: every input is parsed as delimiter-separated values and rendered as aligned columns,
+/// which then go through the features like any other lines
+fn handle_table(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let heading = (arguments.table_header && writes_terminal(arguments)).then(|| theme(arguments).heading);
+
+    let mut budget = MemoryBudget::new(arguments.max_memory);
+    let mut all_processed_lines = Vec::new();
+    for (index, source) in input_sources_or_stdin(arguments)?.into_iter().enumerate() {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        let Some(reader) = skip_unreadable(arguments, open_text(&source, arguments))? else { continue };
+        let mut text = String::new();
+        budget.read_to_string(reader, &mut text)?;
+        let delimiter = arguments.delimiter.unwrap_or(if source.name().ends_with(".tsv") { '\t' } else { ',' });
+
+        if arguments.show_filenames {
+            all_processed_lines.extend(banner_lines(&source, index));
+        }
+        let table = render_table(&parse_delimited(&text, delimiter), arguments.table_header, heading.as_ref());
+        let processed_lines = process_lines(features, &source.name(), table)?;
+        budget.hold(processed_lines.iter().map(String::len).sum())?;
+        budget.release(text.len());
+        all_processed_lines.extend(processed_lines);
+    }
+    all_processed_lines.extend(finish_features(features));
+    paginate_or_write(arguments, all_processed_lines, output)
+}
+
+/// handle `--stats`: every input goes through the features and the processed lines are counted,
+/// the report follows once all inputs are done, after the content with `summary`.
+/// The content is streamed to the output, only the pager needs it held, within `--max-memory`.
+fn handle_stats(
+    mode: StatsMode,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let paginated = pagination_requested(arguments.pagination);
+    let mut budget = MemoryBudget::new(arguments.max_memory);
+    let mut held_lines = Vec::new();
+    let mut writer = BufWriter::with_capacity(buffering(arguments).size, &mut *output);
+    let mut inputs = Vec::new();
+    for (index, source) in input_sources_or_stdin(arguments)?.into_iter().enumerate() {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        if mode == StatsMode::Summary && arguments.show_filenames {
+            let banner = banner_lines(&source, index);
+            if paginated {
+                budget.hold(banner.iter().map(String::len).sum())?;
+                held_lines.extend(banner);
+            } else {
+                write_lines_to(banner, &mut writer)?;
+            }
+        }
+
+        let mut stats = TextStats::default();
+        let read = for_each_processed_record(&source, arguments, features, |record, terminator| {
+            stats.add_line(&record, terminator);
+            match mode {
+                StatsMode::Only => Ok(()),
+                StatsMode::Summary if paginated => {
+                    budget.hold(record.len())?;
+                    held_lines.push(into_text(record));
+                    Ok(())
+                }
+                StatsMode::Summary => write_records(&mut writer, std::slice::from_ref(&record), "\n", "\n"),
+            }
+        });
+        if skip_unreadable(arguments, read)?.is_some() {
+            inputs.push((source.name(), stats));
+        }
+    }
+
+    let mut closing_lines = finish_features(features);
+    closing_lines.extend(stats_report(&inputs));
+    if paginated {
+        drop(writer);
+        held_lines.extend(closing_lines);
+        return paginate_or_write(arguments, held_lines, output);
+    }
+    write_lines_to(closing_lines, &mut writer)?;
+    writer.flush().map_err(|error| RicatError::OutputFlushError(format!("Error flushing output: {}", error)))
+}
+
+/// handle `--sample N`: every input goes through the features, and N of the processed lines are picked at random
+/// while streaming, so only the sample is held in memory
+fn handle_sample(
+    size: usize,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let mut reservoir = Reservoir::new(size, Random::from_entropy());
+    for source in input_sources_or_stdin(arguments)? {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        skip_unreadable(arguments, for_each_processed_line(&source, arguments, features, |line| reservoir.offer(line)))?;
+    }
+    let mut sample = reservoir.into_sample();
+    sample.extend(finish_features(features));
+    paginate_or_write(arguments, sample, output)
+}
+
+/// handle `--length-histogram`: every input goes through the features, and the lengths of the processed lines
+/// are counted into buckets, printed as a bar chart once all inputs are done
+fn handle_length_histogram(
+    bucket_width: usize,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let mut histogram = LengthHistogram::new(bucket_width);
+    for source in input_sources_or_stdin(arguments)? {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        skip_unreadable(arguments, for_each_processed_line(&source, arguments, features, |line| histogram.add_line(&line)))?;
+    }
+    let mut chart = histogram.render();
+    chart.extend(finish_features(features));
+    paginate_or_write(arguments, chart, output)
+}
+
+/// Streams the lines of the source through the features, handing every processed line to `processed`.
+/// Used by the modes which aggregate the output instead of writing it.
+fn for_each_processed_line(
+    source: &InputSource,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    mut processed: impl FnMut(String),
+) -> Result<(), RicatError> {
+    for_each_processed_record(source, arguments, features, |record, _| {
+        processed(into_text(record));
+        Ok(())
+    })
+}
+
+/// Streams the lines of the source through the features, handing every processed record to `processed` as bytes,
+/// with the terminator it had in the input. The pieces of a line split by a feature are kept apart by a terminator
+/// even when the input ended without one, like `write_records` writes them. An error of `processed` stops the input.
+fn for_each_processed_record(
+    source: &InputSource,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    mut processed: impl FnMut(Vec<u8>, &'static str) -> Result<(), RicatError>,
+) -> Result<(), RicatError> {
+    begin_input(features, source)?;
+    let name = source.name();
+    let reader = BufReader::new(open_text(source, arguments)?);
+    let mut lines = lossy_lines(reader).zero_terminated(arguments.zero_terminated);
+    let mut line_number = 0;
+    while let Some(record_result) = lines.next_record() {
+        let (record, ending) = record_result?;
+        line_number += 1;
+
+        let context = line_context(&mut lines, &name, line_number, ending, false)?;
+        let processed_records = apply_record_features(features, &record, &context).map_err(at_line(line_number))?;
+        let terminator = if arguments.zero_terminated { "\0" } else { "\n" };
+        let count = processed_records.len();
+        for (index, processed_record) in processed_records.into_iter().enumerate() {
+            let ending = if ending == LineEnding::None && index + 1 < count { terminator } else { ending.as_str() };
+            processed(processed_record.into_owned(), ending)?;
+        }
+    }
+    Ok(())
+}
+
+/// handle `--interleave`: the inputs are merged line by line like `paste`, line N of every input joined by the delimiter.
+/// Inputs running out of lines add empty fields until the longest one ends.
+fn handle_interleave(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let delimiter = arguments.delimiter.unwrap_or('\t').to_string();
+    let mut inputs = Vec::new();
+    for source in input_sources_or_stdin(arguments)? {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        let Some(reader) = skip_unreadable(arguments, open_text(&source, arguments))? else { continue };
+        let reader = BufReader::new(reader);
+        inputs.push(lossy_lines(reader).zero_terminated(arguments.zero_terminated));
+    }
+
+    let mut interleaved_lines = Vec::new();
+    while let Some(line) = next_interleaved_line(&mut inputs, &delimiter)? {
+        interleaved_lines.push(line);
+    }
+    let mut all_processed_lines = process_lines(features, "", interleaved_lines)?;
+    all_processed_lines.extend(finish_features(features));
+    paginate_or_write(arguments, all_processed_lines, output)
+}
+
+/// The next line of every input joined by the delimiter, with empty fields for the inputs which ended.
+/// `None` once all inputs ended.
+fn next_interleaved_line<I>(inputs: &mut [I], delimiter: &str) -> std::io::Result<Option<String>>
+where
+    I: Iterator<Item = std::io::Result<(String, LineEnding)>>,
+{
+    let mut fields = Vec::with_capacity(inputs.len());
+    let mut lines_left = false;
+    for lines in inputs {
+        match lines.next().transpose()? {
+            Some((line, _)) => {
+                lines_left = true;
+                fields.push(line);
+            }
+            None => fields.push(String::new()),
+        }
+    }
+    Ok(lines_left.then(|| fields.join(delimiter)))
+}
+
+/// handle `--diff`: the two inputs are compared as a whole, the unified diff then goes through the line features
+fn handle_diff(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let theme = writes_terminal(arguments).then(|| theme(arguments));
+    let inputs = two_inputs(arguments, "--diff")?;
+    let [old, new] = &inputs[..] else { return Ok(()) };
+
+    let diff = unified_diff(&old.1, &new.1, (&old.0, &new.0), arguments.diff_context, theme.as_ref());
+    let mut all_processed_lines = process_lines(features, "", diff)?;
+    all_processed_lines.extend(finish_features(features));
+    paginate_or_write(arguments, all_processed_lines, output)
+}
+
+/// handle `--side-by-side`: the two inputs are shown in parallel columns, the rows then go through the line features
+fn handle_side_by_side(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let theme = writes_terminal(arguments).then(|| theme(arguments));
+    let inputs = two_inputs(arguments, "--side-by-side")?;
+    let [old, new] = &inputs[..] else { return Ok(()) };
+
+    let rows = side_by_side(&old.1, &new.1, (&old.0, &new.0), get_terminal_width() as usize, theme.as_ref());
+    let mut all_processed_lines = process_lines(features, "", rows)?;
+    all_processed_lines.extend(finish_features(features));
+    paginate_or_write(arguments, all_processed_lines, output)
+}
+
+/// Names and texts of the two inputs of a mode comparing them, any other number of inputs is an error.
+/// Invalid UTF-8 sequences become `U+FFFD` like in the line features, so binary inputs can still be compared.
+/// An input which cannot be read is reported and left out, leaving nothing to compare.
+fn two_inputs(arguments: &Cli, mode: &str) -> Result<Vec<(String, String)>, RicatError> {
+    let sources = input_sources_or_stdin(arguments)?;
+    if sources.len() != 2 {
+        return Err(RicatError::ComparisonError(format!(
+            "{} compares exactly two inputs, {} given",
+            mode,
+            sources.len()
+        )));
+    }
+
+    let mut budget = MemoryBudget::new(arguments.max_memory);
+    let mut inputs = Vec::new();
+    for source in sources {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        let Some(reader) = skip_unreadable(arguments, open_text(&source, arguments))? else { continue };
+        let mut bytes = Vec::new();
+        budget.read_to_end(reader, &mut bytes)?;
+        inputs.push((source.name(), String::from_utf8_lossy(&bytes).into_owned()));
+    }
+    Ok(inputs)
+}
+
+/// handle `--decrypt`: every input is decrypted as a whole byte stream (and decompressed), then goes through the features
+fn handle_decrypted_input(
+    passphrase: &str,
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let sources = input_sources_or_stdin(arguments)?;
+    let options = output_options(arguments);
+
+    for (index, source) in sources.into_iter().enumerate() {
+        let Some(source) = skip_unreadable(arguments, source)? else { continue };
+        if arguments.show_filenames {
+            write_lines_to(banner_lines(&source, index), output)?;
+        }
+        let Some(reader) = skip_unreadable(arguments, source.open())? else { continue };
+        let reader = decrypt(reader, passphrase, &source.name())?;
+        let reader = decompress(reader, &source.name())?;
+        let reader = match arguments.from_encoding {
+            Some(encoding) => transcode(reader, encoding)?,
+            None => reader,
+        };
+
+        if features.is_empty() && options.ending_conversion.is_none() && !options.decorates_records() {
+            copy(reader, &mut *output)?;
+        } else {
+            process_input_to(reader, &source.name(), output, features, options.clone()).map_err(processing_error)?;
+        }
+    }
+    write_lines_to(finish_features(features), output)
+}
+
+/// handle `--whole-file` Base64: all inputs are read as one byte stream and encoded or decoded at once,
+/// the result then goes through the remaining line features. The bytes are taken as they are, a compressed
+/// input is encoded compressed, like `base64` does.
+fn handle_whole_stream_base64(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let mut budget = MemoryBudget::new(arguments.max_memory);
+    let mut input = Vec::new();
+    if has_file_inputs(arguments) {
+        for source in input_sources(arguments)? {
+            let Some(source) = skip_unreadable(arguments, source)? else { continue };
+            let Some(reader) = skip_unreadable(arguments, source.open())? else { continue };
+            budget.read_to_end(reader, &mut input)?;
+        }
+    } else {
+        budget.read_to_end(InputSource::Stdin.open()?, &mut input)?;
+    }
+
+    let transformed = if arguments.encode {
+        Base64::encode_stream(&input).into_bytes()
+    } else {
+        Base64::decode_stream(&input)
+            .ok_or_else(|| RicatError::DecodingError("invalid Base64 input".to_string()))?
+    };
+
+    if features.is_empty() && ending_conversion_from_args(arguments).is_none() {
+        // decoded data is written as it is, it may well be binary
+        output.write_all(&transformed)?;
+        return Ok(());
+    }
+
+    let options = output_options(arguments);
+    process_input_to(&transformed[..], "", output, features, options).map_err(processing_error)?;
+    write_lines_to(finish_features(features), output)
+}
+
+/// handle files or features : features are enabled, files can/cannot be passed
+fn handle_files_or_features(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let options = output_options(arguments);
+
+    if !has_file_inputs(arguments) {
+        let source = InputSource::Stdin;
+        begin_input(features, &source)?;
+        let Some(reader) = check_binary(open_text(&source, arguments)?, &source, arguments, output)? else {
+            return Ok(());
+        };
+        // written without extra buffering: lines typed on the terminal are echoed right away
+        let options = OutputOptions { streaming: true, ..options };
+        process_input_to(reader, &source.name(), output, features, options).map_err(processing_error)?;
+        write_lines_to(finish_features(features), output)
+    } else if !pagination_requested(arguments.pagination) {
+        // stream every file through the features, nothing needs to be held in memory
+        let mut writer = BufWriter::with_capacity(buffering(arguments).size, output);
+        let sources = input_sources(arguments)?;
+
+        // the threads hand over whole files, `-u` writes line by line and `--max-memory` holds no whole file
+        let jobs = if arguments.unbuffered || arguments.max_memory.is_some() { 1 } else { job_count(arguments.jobs) };
+        // the lists of `--files-from` would have to be read ahead, one fed as it goes is only waited for with `--jobs`
+        let file_lists = arguments.files_from.is_some() || arguments.files_from0.is_some();
+        if can_process_in_parallel(features, jobs) && (arguments.jobs.is_some() || !file_lists) {
+            let sources: Vec<_> = sources.collect();
+            // a single large file searched with `--jobs` is cut into chunks, the threads search them side by side
+            let chunked = match sources.as_slice() {
+                [Ok(source)] if arguments.jobs.is_some() && arguments.search_flag => {
+                    search_file_in_chunks(arguments, source, 0, jobs, options.clone(), &mut writer)?
+                }
+                _ => false,
+            };
+            if !chunked && worth_processing_in_parallel(&sources) {
+                process_sources_in_parallel(arguments, sources, jobs, options, &mut writer)?;
+            } else if !chunked {
+                process_sources(arguments, sources.into_iter(), features, options, &mut writer)?;
+            }
+        } else {
+            process_sources(arguments, sources, features, options, &mut writer)?;
+        }
+        write_lines_to(finish_features(features), &mut writer)?;
+
+        writer.flush().map_err(|error| {
+            RicatError::OutputFlushError(format!("Error flushing output: {}", error))
+        })?;
+        Ok(())
+    } else {
+        let all_processed_lines = collect_processed_lines(arguments, features)?;
+        paginate_or_write(arguments, all_processed_lines, output)
+    }
+}
+/// Writes the inputs one after the other through the features, skipping those which cannot be read
+fn process_sources(
+    arguments: &Cli,
+    sources: impl Iterator<Item = Result<InputSource, RicatError>>,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+    writer: &mut dyn Write,
+) -> Result<(), RicatError> {
+    for (index, source) in sources.enumerate() {
+        let result = source.and_then(|source| {
+            if arguments.show_filenames {
+                write_lines_to(banner_lines(&source, index), writer)?;
+            }
+            begin_input(features, &source)?;
+            let Some(reader) = check_binary(open_text(&source, arguments)?, &source, arguments, writer)? else {
+                return Ok(());
+            };
+            process_input_to(reader, &source.name(), writer, features, options.clone()).map_err(processing_error)
+        });
+        if let Err(error) = result {
+            skip_failed_input(arguments, error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the inputs through the features on `jobs` threads, skipping those which cannot be read
+fn process_sources_in_parallel(
+    arguments: &Cli,
+    sources: Vec<Result<InputSource, RicatError>>,
+    jobs: usize,
+    options: OutputOptions,
+    writer: &mut dyn Write,
+) -> Result<(), RicatError> {
+    // with `--fail-fast` the files before one which cannot be found are written, then its error stops ricat
+    let mut readable = Vec::new();
+    for source in sources {
+        match source.map_err(|error| skip_failed_input(arguments, error)) {
+            Ok(source) => readable.push(source),
+            Err(Ok(())) => {}
+            Err(Err(error)) => {
+                process_files_in_parallel(arguments, readable, jobs, options, writer)?;
+                return Err(error);
+            }
+        }
+    }
+    process_files_in_parallel(arguments, readable, jobs, options, writer)
+}
+
+/// handle files without features
+fn handle_files_without_features(
+    arguments: &Cli,
+    direct_output: Option<&DirectOutput>,
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    if pagination_requested(arguments.pagination) {
+        let all_lines = collect_processed_lines(arguments, &mut [])?;
+        paginate_or_write(arguments, all_lines, output)
+    } else {
+        // Directly copy files to standard output
+        for (index, source) in input_sources(arguments)?.enumerate() {
+            if let Err(error) = source.and_then(|source| copy_source(arguments, index, &source, direct_output, output)) {
+                skip_failed_input(arguments, error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copies one source to the output unchanged, after its banner
+fn copy_source(
+    arguments: &Cli,
+    index: usize,
+    source: &InputSource,
+    direct_output: Option<&DirectOutput>,
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    if arguments.show_filenames {
+        write_lines_to(banner_lines(source, index), output)?;
+    }
+
+    if arguments.decompress || binary_mode(arguments).is_some() {
+        let reader = if arguments.decompress {
+            source.open_decompressed()?
+        } else {
+            source.open()?
+        };
+        if let Some(reader) = check_binary(reader, source, arguments, output)? {
+            let mut progress = progress_bar(arguments, source);
+            copy_with_progress(reader, &mut *output, buffering(arguments), progress.as_mut())?;
+            if let Some(progress) = progress.as_mut() {
+                progress.finish();
+            }
+        }
+        return Ok(());
+    }
+
+    let mut progress = progress_bar(arguments, source);
+    match source {
+        InputSource::File(file_path) => {
+            copy_file(arguments, file_path, direct_output, output, progress.as_mut())?
+        }
+        source => copy_with_progress(source.open()?, &mut *output, buffering(arguments), progress.as_mut())?,
+    }
+    if let Some(progress) = progress.as_mut() {
+        progress.finish();
+    }
+    Ok(())
+}
+
+/// Carries on past an input which cannot be read, like GNU cat: its error is reported on stderr and ricat
+/// exits with a failure status once the other inputs are written. With `--fail-fast`, or for errors which
+/// are not about the input (e.g. writing the output), it stops ricat.
+fn skip_failed_input(arguments: &Cli, error: RicatError) -> Result<(), RicatError> {
+    let input_error = matches!(
+        error,
+        RicatError::FileOpenError(_)
+            | RicatError::UrlReadError(_)
+            | RicatError::ArchiveError(_)
+            | RicatError::ClipboardError(_)
+            | RicatError::DecompressionError(_)
+            | RicatError::PathExpansionError(_)
+            | RicatError::InputIsOutputError(_)
+    );
+    if arguments.fail_fast || !input_error {
+        return Err(error);
+    }
+    eprintln!("ricat: {}", error);
+    arguments.failed_inputs.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The input, or its reader, unless it failed: the error is then reported by `skip_failed_input` and `None` returned,
+/// so the modes reading the inputs one by one go on with the next
+fn skip_unreadable<T>(arguments: &Cli, result: Result<T, RicatError>) -> Result<Option<T>, RicatError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(error) => skip_failed_input(arguments, error).map(|_| None),
+    }
+}
+
+/// Copies a file argument with the I/O backend of `--io-backend`
+fn copy_file(
+    arguments: &Cli,
+    file_path: &str,
+    direct_output: Option<&DirectOutput>,
+    output: &mut dyn Write,
+    progress: Option<&mut Progress>,
+) -> Result<(), RicatError> {
+    let mut progress = progress;
+    let backend = arguments.io_backend.unwrap_or_default();
+    if backend == IoBackend::Auto && copy_directly(file_path, direct_output, output, progress.as_deref_mut())? {
+        return Ok(());
+    }
+
+    let file = File::open(file_path).map_err(|error| {
+        RicatError::FileOpenError(format!("Error opening file {}: {}", file_path, error))
+    })?;
+    let metadata = file.metadata()?;
+    if metadata.is_dir() {
+        return Err(RicatError::FileOpenError(format!("Error opening file {}: Is a directory", file_path)));
+    }
+    let buffering = buffering(arguments);
+    match backend.resolve(&metadata, arguments.mmap_threshold.unwrap_or(DEFAULT_MMAP_THRESHOLD)) {
+        IoBackend::Mmap => copy_mmap_with_progress(file_path, output, buffering, progress),
+        IoBackend::Uring => copy_with_uring(file_path, output, buffering, progress),
+        // a regular file is copied up to its size when the copy starts, one appended to meanwhile is not followed
+        // (files of /proc show a size of 0 and are read to their end)
+        _ if metadata.is_file() && metadata.len() > 0 => copy_with_progress(file.take(metadata.len()), output, buffering, progress),
+        _ => copy_with_progress(file, output, buffering, progress),
+    }
+}
+
+/// Copies the file to the output inside the kernel, after flushing what is written so far.
+/// Returns `false` when it has to be copied through ricat: no direct output, or the kernel cannot copy it.
+fn copy_directly(
+    file_path: &str,
+    direct_output: Option<&DirectOutput>,
+    output: &mut dyn Write,
+    progress: Option<&mut Progress>,
+) -> Result<bool, RicatError> {
+    let Some(direct_output) = direct_output else {
+        return Ok(false);
+    };
+    // the buffered copy reports the files which cannot be opened
+    let Ok(file) = File::open(file_path) else {
+        return Ok(false);
+    };
+    output.flush().map_err(|error| {
+        RicatError::OutputFlushError(format!("Error flushing output: {}", error))
+    })?;
+    copy_file_direct(&file, direct_output, progress).map_err(|error| {
+        RicatError::MemoryMapWriteError(format!("Error writing to output: {}", error))
+    })
+}
+
+/// Opens the source for the line pipeline: decompressed, and transcoded to UTF-8 with `--from-encoding`
+fn open_text(source: &InputSource, arguments: &Cli) -> Result<Box<dyn Read>, RicatError> {
+    let reader = source.open_decompressed()?;
+    match arguments.from_encoding {
+        Some(encoding) => transcode(reader, encoding),
+        None => Ok(reader),
+    }
+}
+
+/// Progress bar for copying the source: always with `--progress`, and for large files when the output is not a terminal
+/// but stderr is (e.g. `ricat big.iso > copy.iso`)
+fn progress_bar(arguments: &Cli, source: &InputSource) -> Option<Progress> {
+    // the size of a compressed file says nothing about the length of the decompressed output
+    let total = match source {
+        InputSource::File(path) if !arguments.decompress => std::fs::metadata(path).ok().map(|metadata| metadata.len()),
+        _ => None,
+    };
+
+    let output_is_terminal = output_target(arguments) == OutputTarget::Stdout && stdout().is_terminal();
+    let large_copy = total.is_some_and(|total| total >= LARGE_FILE_SIZE) && !output_is_terminal && stderr().is_terminal();
+
+    (arguments.progress || large_copy).then(|| Progress::new(&source.name(), total))
+}
+
+/// Processes every input source into lines, used when the whole output is needed before paginating
+fn collect_processed_lines(
+    arguments: &Cli,
+    features: &mut [Box<dyn ByteFeature>],
+) -> Result<Vec<String>, RicatError> {
+    let mut all_processed_lines = Vec::<String>::new();
+    let mut budget = MemoryBudget::new(arguments.max_memory);
+
+    for (index, source) in input_sources(arguments)?.enumerate() {
+        let result = source.and_then(|source| {
+            if arguments.show_filenames {
+                all_processed_lines.extend(banner_lines(&source, index));
+            }
+            begin_input(features, &source)?;
+
+            let mut hex_view = Vec::new();
+            let Some(reader) = check_binary(open_text(&source, arguments)?, &source, arguments, &mut hex_view)? else {
+                all_processed_lines.extend(String::from_utf8_lossy(&hex_view).lines().map(str::to_string));
+                return Ok(());
+            };
+            let processed_lines = process_input_ret_within(reader, &source.name(), features, output_options(arguments), &mut budget)
+                .map_err(processing_error)?;
+            all_processed_lines.extend(processed_lines);
+            Ok(())
+        });
+        if let Err(error) = result {
+            skip_failed_input(arguments, error)?;
+        }
+    }
+    all_processed_lines.extend(finish_features(features));
+    Ok(all_processed_lines)
+}
+
+/// Paginates the lines when needed, otherwise writes them directly to standard output
+fn paginate_or_write(
+    arguments: &Cli,
+    lines: Vec<String>,
+    output: &mut dyn Write,
+) -> Result<(), RicatError> {
+    if should_paginate(arguments.pagination, lines.len()) {
+        paginate_output_with(lines, stdout(), arguments.pager_long_lines.unwrap_or_default(), &theme(arguments).pager_status)
+            .map(|_| ())
+            .map_err(|error| RicatError::PaginationError(format!("Error paginating: {}", error)))
+    } else {
+        write_lines(lines, output)
+    }
+}
+
+/// How binary input is handled, `None` when it is passed through unchanged.
+/// Without `--binary`, detection only happens when writing to a terminal, so `ricat a.bin > b.bin` keeps working.
+fn binary_mode(arguments: &Cli) -> Option<BinaryMode> {
+    if arguments.force_binary {
+        None
+    } else if arguments.binary.is_some() {
+        arguments.binary
+    } else if output_target(arguments) == OutputTarget::Stdout && stdout().is_terminal() {
+        Some(BinaryMode::Skip)
+    } else {
+        None
+    }
+}
+
+/// Checks the input for binary content according to `--binary`/`--force-binary`.
+/// Returns the reader to continue with, or `None` when the binary content was skipped or written as a hex view.
+fn check_binary<W: Write + ?Sized>(
+    reader: Box<dyn Read>,
+    source: &InputSource,
+    arguments: &Cli,
+    writer: &mut W,
+) -> Result<Option<Box<dyn Read>>, RicatError> {
+    let Some(mode) = binary_mode(arguments) else {
+        return Ok(Some(reader));
+    };
+
+    let (is_binary, reader) = sniff(reader)?;
+    if !is_binary {
+        return Ok(Some(reader));
+    }
+
+    match mode {
+        BinaryMode::Skip => {
+            eprintln!(
+                "ricat: file {} is binary (use --force-binary to print it, or --binary=hex)",
+                source.name()
+            );
+        }
+        BinaryMode::Hex => hex_dump(reader, writer)?,
+    }
+    Ok(None)
+}
+/// Generate Feature Vector: Will Add Features based on arguments passed
+fn add_features_from_args(arguments: &Cli) -> Result<Vec<Box<dyn ByteFeature>>, RicatError> {
+    let mut registry = builtin_features();
+    let plugins = config::config_dir().join("plugins");
+    register_plugins(&mut registry, &plugins);
+    register_wasm_plugins(&mut registry, &plugins);
+    registry.build(arguments, &arguments.feature_names, &arguments.feature_order)
+}
+
+/// Whether the output goes to a terminal, escape codes for colors would only garble files and pipes
+fn writes_terminal(arguments: &Cli) -> bool {
+    output_target(arguments) == OutputTarget::Stdout && stdout().is_terminal()
+}
+
+/// Colors of `--theme` or the configuration, with the colors the `[theme]` section changes
+fn theme(arguments: &Cli) -> Theme {
+    Theme::builtin(arguments.theme.unwrap_or_default()).with_settings(&arguments.theme_colors)
+}
+
+/// The theme for colors added to the text of the lines, only when they go to a terminal as text,
+/// JSON and templated output are left plain
+fn text_theme(arguments: &Cli) -> Option<Theme> {
+    let structured = arguments.output_format.is_some() || arguments.format.is_some();
+    (writes_terminal(arguments) && !structured).then(|| theme(arguments))
+}
+
+/// The built-in features by name, in the order they are applied to a line
+fn builtin_features() -> FeatureRegistry {
+    let mut registry = FeatureRegistry::new();
+
+    // the lines at the ends of every input are dropped before anything else looks at them
+    registry.register_with_value("skip-head", "--skip-head", |arguments| {
+        Some(Box::new(SkipHead::new(arguments.skip_head?)))
+    });
+    registry.register_with_value("skip-tail", "--skip-tail", |arguments| {
+        Some(Box::new(SkipTail::new(arguments.skip_tail?)))
+    });
+
+    // escape sequences go first, a line holding nothing but a color reset is empty for the other features
+    registry.register("strip-ansi", |arguments| arguments.strip_ansi, |_| Some(Box::new(StripAnsi::new())));
+    registry.register("squeeze", |arguments| arguments.squeeze_blank, |arguments| {
+        Some(Box::new(CompressEmptyLines::with_max_blank(arguments.max_blank.unwrap_or(1))))
+    });
+    registry.register(
+        "unique",
+        |arguments| arguments.unique || arguments.unique_global,
+        |arguments| {
+            Some(if arguments.unique_global {
+                Box::new(UniqueLines::global(arguments.max_memory.unwrap_or(UNIQUE_MEMORY_CAP)))
+            } else {
+                Box::new(UniqueLines::new())
+            })
+        },
+    );
+
+    // with `--whole-file` Base64 is applied to the whole input before the line features
+    registry.register(
+        "encode-base64",
+        |arguments| arguments.encode && !arguments.whole_file,
+        |_| Some(Box::new(Base64Encoding::new())),
+    );
+    registry.register(
+        "decode-base64",
+        |arguments| arguments.decode && !arguments.whole_file,
+        |_| Some(Box::new(Base64Decoding::new())),
+    );
+    registry.register_with_value("encode", "--encode", |arguments| {
+        (!arguments.encode_codec.is_empty()).then(|| Box::new(CodecEncoding::chain(arguments.encode_codec.clone())) as _)
+    });
+    registry.register_with_value("decode", "--decode", |arguments| {
+        (!arguments.decode_codec.is_empty()).then(|| Box::new(CodecDecoding::chain(arguments.decode_codec.clone())) as _)
+    });
+    registry.register("encode-base32", |arguments| arguments.encode_base32, |_| Some(Box::new(Base32Encoding::new())));
+    registry.register("decode-base32", |arguments| arguments.decode_base32, |_| Some(Box::new(Base32Decoding::new())));
+    registry.register("html-escape", |arguments| arguments.html_escape, |_| Some(Box::new(CodecEncoding::new(Codec::Html))));
+    registry.register("html-unescape", |arguments| arguments.html_unescape, |_| Some(Box::new(CodecDecoding::new(Codec::Html))));
+    registry.register("json-escape", |arguments| arguments.json_escape, |_| Some(Box::new(CodecEncoding::new(Codec::Json))));
+    registry.register("json-unescape", |arguments| arguments.json_unescape, |_| Some(Box::new(CodecDecoding::new(Codec::Json))));
+    registry.register("encode-hex", |arguments| arguments.encode_hex, |_| Some(Box::new(HexEncoding::new())));
+    registry.register("decode-hex", |arguments| arguments.decode_hex, |_| Some(Box::new(HexDecoding::new())));
+
+    // before the search, so it finds lines by their commit or author too
+    registry.register("blame", |arguments| arguments.blame, |arguments| {
+        Some(Box::new(Blame::new(writes_terminal(arguments)).with_theme(theme(arguments))))
+    });
+    registry.register("search", |arguments| arguments.search_flag, |arguments| {
+        let text_to_search = match &arguments.search_text {
+            None => "",
+            Some(text) => text,
+        };
+        let mut search = LineWithGivenText::new(text_to_search.trim(), arguments.ignore_case);
+        if let Some(theme) = text_theme(arguments) {
+            search = search.with_highlight(theme.search_match);
+        }
+        Some(Box::new(if arguments.summary { search.with_summary() } else { search }))
+    });
+
+    // fields are cut from the lines the search found, then numbered like any other line
+    registry.register_with_value("fields", "--fields", |arguments| {
+        let output_delimiter = arguments.output_delim.as_deref().unwrap_or(&arguments.field_delim);
+        Some(Box::new(FieldExtraction::new(arguments.fields.clone()?, &arguments.field_delim, output_delimiter)))
+    });
+    registry.register_with_value("columns", "--columns", |arguments| {
+        Some(Box::new(ColumnSelection::new(arguments.columns.clone()?)))
+    });
+    registry.register("rev", |arguments| arguments.rev, |_| Some(Box::new(LineReversal::new())));
+    // the script gets the lines as cut, its lines are numbered like any other
+    registry.register_with_value("script", "--script", |arguments| {
+        let script = arguments.script.clone().or_else(|| arguments.script_file.clone())?;
+        Some(Box::new(ScriptFeature::new(script)))
+    });
+    registry.register_with_value("hash-lines", "--hash-lines", |arguments| {
+        Some(Box::new(LineHashing::new(arguments.hash_lines?, arguments.hash_lines_annotate)))
+    });
+    registry.register("delta", |arguments| arguments.delta, |_| Some(Box::new(LineDelta::new())));
+    registry.register("git", |arguments| arguments.git, |arguments| {
+        Some(Box::new(GitMarkers::new(writes_terminal(arguments)).with_theme(theme(arguments))))
+    });
+
+    registry.register("numbering", |arguments| arguments.numbers, |arguments| {
+        Some(Box::new(LineNumbering::with_format(number_format(arguments)).with_style(number_style(arguments))))
+    });
+    registry.register("relative-numbering", |arguments| arguments.relative_numbers, |arguments| {
+        // blocks as high as the screen with the pager, without it the whole output is one block
+        let page = if pagination_requested(arguments.pagination) { page_size() } else { usize::MAX };
+        Some(Box::new(LineNumbering::relative(page, number_format(arguments)).with_style(number_style(arguments))))
+    });
+
+    // sampled once numbered, so the lines keep the numbers they have in the input
+    registry.register_with_value("sample-percent", "--sample-percent", |arguments| {
+        Some(Box::new(SamplePercent::new(arguments.sample_percent?, Random::from_entropy())))
+    });
+    registry.register("dollar", |arguments| arguments.dollar, |_| Some(Box::new(DollarSymbolAtLast::new())));
+
+    // tabs are expanded before `-t`, which would find none left
+    registry.register("expand-tabs", |arguments| arguments.expand_tabs.is_some(), |arguments| {
+        let width = arguments.expand_tabs.flatten().map_or(DEFAULT_TAB_WIDTH, |width| width as usize);
+        Some(Box::new(ExpandTabs::new(width)))
+    });
+    registry.register("unexpand", |arguments| arguments.unexpand.is_some(), |arguments| {
+        let width = arguments.unexpand.flatten().map_or(DEFAULT_TAB_WIDTH, |width| width as usize);
+        Some(Box::new(UnexpandTabs::new(width)))
+    });
+    registry.register("tabs", |arguments| arguments.tabs, |_| Some(Box::new(ReplaceTabspaces::new())));
+    registry.register("trim-trailing", |arguments| arguments.trim_trailing, |_| Some(Box::new(TrimTrailing::new())));
+    registry.register("highlight-trailing", |arguments| arguments.highlight_trailing, |arguments| {
+        writes_terminal(arguments).then(|| Box::new(HighlightTrailing::with_theme(&theme(arguments))) as _)
+    });
+    registry.register("show-whitespace", |arguments| arguments.show_whitespace, |arguments| {
+        Some(Box::new(ShowWhitespace::new(writes_terminal(arguments)).with_theme(theme(arguments))))
+    });
+
+    // folded after numbering and `$`, those still mark the lines of the input
+    registry.register_with_value("wrap", "--wrap", |arguments| {
+        Some(Box::new(LineWrapping::new(arguments.wrap?.columns())))
+    });
+    registry.register("log-levels", |arguments| arguments.log_levels, |arguments| {
+        text_theme(arguments).map(|theme| Box::new(LogLevels::new(&theme)) as _)
+    });
+    registry.register("rainbow", |arguments| arguments.rainbow, |arguments| {
+        writes_terminal(arguments).then(|| Box::new(Rainbow::new()) as _)
+    });
+
+    // the same pairs the options conflict on, so `--features` cannot enable them together either
+    registry.conflict("numbering", "relative-numbering");
+    registry.conflict("expand-tabs", "unexpand");
+    registry.conflict("fields", "columns");
+
+    registry
+}
+
+/// Add features from configuration file
+fn enable_features_from_config(config: &config::RicatConfig, arguments: &mut Cli) {
+    // println!("Config: {:#?}", config);
+    if config.number_feature && !arguments.numbers {
+       arguments.numbers = true; 
+    }
+
+    if config.dollar_sign_feature && !arguments.dollar {
+        arguments.dollar = true;
+    }
+
+    if config.tabs_feature && !arguments.tabs {
+        arguments.tabs = true;
+    }
+
+    if config.compress_empty_line_feature && !arguments.squeeze_blank {
+        arguments.squeeze_blank = true;
+    }
+
+    for name in &config.features {
+        if !arguments.feature_names.contains(name) {
+            arguments.feature_names.push(name.clone());
+        }
+    }
+
+    if arguments.feature_order.is_empty() {
+        arguments.feature_order = config.feature_order.clone();
+    }
+
+    if arguments.pagination.is_none() {
+        arguments.pagination = config.pagination_mode;
+    }
+
+    if arguments.number_width.is_none() {
+        arguments.number_width = config.number_width();
+    }
+
+    if arguments.number_align.is_none() {
+        arguments.number_align = config.number_align();
+    }
+
+    if arguments.number_separator.is_none() {
+        arguments.number_separator = config.number_separator();
+    }
+
+    if arguments.number_start.is_none() {
+        arguments.number_start = config.number_start();
+    }
+
+    if arguments.theme.is_none() {
+        arguments.theme = config.theme.name;
+    }
+    arguments.theme_colors = config.theme.clone();
+
+    if arguments.max_blank.is_none() {
+        arguments.max_blank = config.squeeze.max_blank;
+    }
+
+    if arguments.pager_long_lines.is_none() {
+        arguments.pager_long_lines = config.pager_long_lines;
+    }
+
+    if arguments.expand_tabs == Some(None) {
+        arguments.expand_tabs = Some(config.tab_width());
+    }
+
+    if arguments.unexpand == Some(None) {
+        arguments.unexpand = Some(config.tab_width());
+    }
+
+    if arguments.io_backend.is_none() {
+        arguments.io_backend = config.io_backend;
+    }
+
+    if arguments.max_memory.is_none() {
+        arguments.max_memory = config.max_memory;
+    }
+
+    if arguments.mmap_threshold.is_none() {
+        arguments.mmap_threshold = config.mmap_threshold;
+    }
+}
+
+/// Banner lines printed before a source with `--show-filenames`, a blank line separates it from the previous source
+fn banner_lines(source: &InputSource, index: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if index > 0 {
+        lines.push(String::new());
+    }
+    lines.push(source.banner());
+    lines
+}
+
+/// Writes all the lines to the writer, used when the output is not paginated
+fn write_lines<W: Write>(lines: Vec<String>, writer: W) -> Result<(), RicatError> {
+    let mut buf_writer = BufWriter::new(writer);
+
+    write_lines_to(lines, &mut buf_writer)?;
+
+    buf_writer.flush().map_err(|error| {
+        RicatError::OutputFlushError(format!("Error flushing output: {}", error))
+    })?;
+    Ok(())
+}
+
+/// Writes each line followed by a newline, without flushing the writer
+fn write_lines_to<W: Write + ?Sized>(lines: Vec<String>, writer: &mut W) -> Result<(), RicatError> {
+    for line in lines {
+        writeln!(writer, "{}", line).map_err(|error| {
+            RicatError::LineProcessingError(format!("Error writing line: {}", error))
+        })?;
+    }
+    Ok(())
+}
+
+
+/// Size of the reads of a copy and of the output buffer when `--buffer-size` is not given.
+/// Copies of a 120 MB file through pipes took 0.15 s with 4 KiB reads and 0.085 s from 64 KiB on,
+/// larger reads only hold more memory.
+pub const DEFAULT_BUFFER_SIZE: usize = 128 * 1024;
+
+/// Largest `--buffer-size`, the buffer is allocated up front and a larger one would rather abort ricat than help it
+pub const MAX_BUFFER_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// How the output is buffered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Buffering {
+    /// bytes read at once by a copy, and held by the output buffer before they are written
+    pub size: usize,
+    /// every read of a copy is written out right away, like `cat -u`
+    pub unbuffered: bool,
+}
+
+impl Default for Buffering {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_BUFFER_SIZE,
+            unbuffered: false,
+        }
+    }
+}
+
+/// Copies data from the reader to the writer without modification.
+/* Less System Calls: the number of read and write system calls is reduced */
+pub fn copy<R: Read, W: Write>(reader: R, writer: W) -> Result<(), RicatError> {
+    copy_with_progress(reader, writer, Buffering::default(), None)
+}
+
+/// Copies data from the reader to the writer, reporting the copied bytes to the progress bar
+pub fn copy_with_progress<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    buffering: Buffering,
+    mut progress: Option<&mut Progress>,
+) -> Result<(), RicatError> {
+    // buffer to hold chunks of the file
+    let mut buffer = vec![0_u8; buffering.size];
+
+    loop {
+        let len = reader.read(&mut buffer)?;
+        if len == 0 {
+            break; // End of file or stream
+        }
+        writer.write_all(&buffer[..len])?;
+        if buffering.unbuffered {
+            writer.flush()?;
+        }
+        if let Some(progress) = progress.as_deref_mut() {
+            progress.advance(len);
+        }
+    }
+    Ok(())
+}
+
+/*In Memory Copy: Via Memory Mapped IO */
+// Memory-mapped I/O (MMIO) is a technique that allows the physical memory of a computer to be accessed using software. Specifically, memory-mapped I/O is used for accessing memory-mapped registers in the I/O space of a computer.
+// Memory-mapped I/O is used to reduce the overhead of accessing the computer's memory by allowing the memory to be accessed directly by the CPU. This can improve the performance of the computer by reducing the number of instructions required to access the memory.
+/// Copies data from the file to the writer using memory-mapped I/O.
+pub fn copy_mmap<W:Write>(file_path: &str, writer: W) -> Result<(), RicatError> {
+    copy_mmap_with_progress(file_path, writer, Buffering::default(), None)
+}
+
+/// Size of the windows a file is mapped to memory in, a huge file is never mapped whole
+pub const MMAP_WINDOW_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Copies data from the file to the writer using memory-mapped I/O, reporting the copied bytes to the progress bar.
+/// The file is mapped one window after the other, each read sequentially and unmapped once written, so a 10 GB
+/// file does not hold 10 GB of mapped pages. With a progress bar every window is written in chunks, so the bar
+/// moves while the copy runs.
+pub fn copy_mmap_with_progress<W: Write>(
+    file_path: &str,
+    mut writer: W,
+    buffering: Buffering,
+    progress: Option<&mut Progress>,
+) -> Result<(), RicatError> {
+    const CHUNK_SIZE: usize = 1024 * 1024;
+
+    let file = File::open(file_path).map_err(|error| {
+        RicatError::FileOpenError(format!("Error opening file {}: {}", file_path, error))
+    })?;
+
+    // named pipes, character devices and zero-length `/proc` files cannot be mapped: fall back to the buffered copy
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return copy_with_progress(file, writer, buffering, progress);
+    }
+
+    let write_error =
+        |error: std::io::Error| RicatError::MemoryMapWriteError(format!("Error writing to output: {}", error));
+    let mut progress = progress;
+    let mut offset = 0;
+    while offset < metadata.len() {
+        let window_len = (metadata.len() - offset).min(MMAP_WINDOW_SIZE) as usize;
+        let window = unsafe { MmapOptions::new().offset(offset).len(window_len).map(&file) }.map_err(|error| {
+            RicatError::MemoryMapError(format!("Error mapping file to memory: {}", error))
+        })?;
+        // read ahead of the copy, and drop the pages behind it first
+        #[cfg(unix)]
+        let _ = window.advise(Advice::Sequential);
+
+        // `write_all` carries on after partial and interrupted writes
+        match progress.as_mut() {
+            None => writer.write_all(&window).map_err(write_error)?,
+            Some(progress) => {
+                for chunk in window.chunks(CHUNK_SIZE) {
+                    writer.write_all(chunk).map_err(write_error)?;
+                    progress.advance(chunk.len());
+                }
+            }
+        }
+        offset += window_len as u64;
+    }
+    if buffering.unbuffered {
+        writer.flush().map_err(write_error)?;
+    }
+
+    Ok(())
+}
+
+/// Size of the reads the lines are split from, large enough for `memchr` to search whole blocks at once
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Options controlling how the processed lines are written
+#[derive(Debug, Clone, Default)]
+pub struct OutputOptions {
+    /// keep the original line terminators (`\n`, `\r\n` or none for the last line) instead of always writing `\n`
+    pub keep_line_endings: bool,
+    /// convert the original line terminators, implies keeping them
+    pub ending_conversion: Option<EndingConversion>,
+    /// chunk or truncate lines longer than a limit instead of reading them whole
+    pub line_limit: Option<LineLimit>,
+    /// lines are NUL-terminated records on input and output (`-z`)
+    pub zero_terminated: bool,
+    /// put the byte offset of the input line in front of every line (`--show-offsets`)
+    pub offset_format: Option<OffsetFormat>,
+    /// the input is read as it arrives (terminal, socket), lines are processed without waiting for the next one
+    pub streaming: bool,
+    /// every input line is flushed to the output once written (`-u`)
+    pub unbuffered: bool,
+    /// lines are written as they are or as JSON objects (`--output-format`)
+    pub output_format: OutputFormat,
+    /// every line is laid out by the template (`--format`)
+    pub template: Option<Arc<OutputTemplate>>,
+}
+
+impl OutputOptions {
+    /// The lines are changed on the way out even without a feature: offsets, JSON or a template
+    fn decorates_records(&self) -> bool {
+        self.offset_format.is_some() || self.output_format == OutputFormat::Json || self.template.is_some()
+    }
+
+    /// The lines are written with the number of their input line, as JSON or by a template
+    fn writes_line_numbers(&self) -> bool {
+        self.output_format == OutputFormat::Json || self.template.is_some()
+    }
+}
+
+/// Number base of the byte offsets shown with `--show-offsets`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetFormat {
+    /// decimal, as used for seek positions
+    Dec,
+    /// hexadecimal, as shown by `xxd` and `hexdump`
+    Hex,
+}
+
+/// How the processed lines are written, `--output-format`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// the lines as they are
+    #[default]
+    Text,
+    /// JSON Lines: an object per line with the input name, the line number and the text, `{"file":"a.txt","line":12,"text":"..."}`
+    Json,
+}
+
+/// A processed line written by `--output-format json`
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    file: &'a str,
+    line: usize,
+    /// byte offset of the input line, with `--show-offsets`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    text: Cow<'a, str>,
+}
+
+/// Processes input by applying each configured text feature to every line, writing the processed lines to the writer.
+pub fn process_input_to<R: Read, W: Write + ?Sized>(
+    reader: R,
+    name: &str,
+    writer: &mut W,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+) -> Result<(), RicatError> {
+    let buf_reader = BufReader::with_capacity(READ_BUFFER_SIZE, reader);
+
+    let mut lines = lossy_lines(buf_reader)
+        .with_limit(options.line_limit)
+        .zero_terminated(options.zero_terminated);
+    let terminator = if options.zero_terminated { "\0" } else { "\n" };
+
+    let mut line_number = 0;
+    while let Some(record_result) = lines.next_record() {
+        let (record, ending) = record_result?;
+        line_number += 1;
+
+        // without features (e.g. only `-z` or a line ending conversion) the line is written as it was read,
+        // and its buffer is read into again
+        if features.is_empty() && !options.decorates_records() {
+            write_records(writer, std::slice::from_ref(&record), output_ending(&options, ending), terminator)?;
+            flush_line(writer, &options)?;
+            lines.reuse(record);
+            continue;
+        }
+
+        let context = line_context(&mut lines, name, line_number, ending, options.streaming)?;
+        let processed_records = apply_record_features(features, &record, &context).map_err(at_line(line_number))?;
+        let processed_lines = decorate_records(&options, &context, processed_records);
+        write_records(writer, &processed_lines, output_ending(&options, ending), terminator)?;
+        flush_line(writer, &options)?;
+        drop(processed_lines);
+        lines.reuse(record);
+    }
+    Ok(())
+}
+
+/// Flushes the lines written for an input line with `-u`
+fn flush_line<W: Write + ?Sized>(writer: &mut W, options: &OutputOptions) -> Result<(), RicatError> {
+    if !options.unbuffered {
+        return Ok(());
+    }
+    writer.flush().map_err(|error| RicatError::OutputFlushError(format!("Error flushing output: {}", error)))
+}
+
+/// Writes the records one input line turned into, each followed by the terminator of that line
+fn write_records<W: Write + ?Sized, R: AsRef<[u8]>>(
+    writer: &mut W,
+    records: &[R],
+    ending: &str,
+    terminator: &str,
+) -> Result<(), RicatError> {
+    for (index, record) in records.iter().enumerate() {
+        // a line split by a feature keeps its pieces apart even when the input ended without a newline
+        let ending = if ending.is_empty() && index + 1 < records.len() { terminator } else { ending };
+        writer.write_all(record.as_ref()).and_then(|_| writer.write_all(ending.as_bytes())).map_err(|error| {
+            RicatError::LineProcessingError(format!("Error writing line: {}", error))
+        })?;
+    }
+    Ok(())
+}
+
+/// Terminator written after the lines of an input line: the one it had, converted, or the usual one
+fn output_ending(options: &OutputOptions, ending: LineEnding) -> &'static str {
+    if options.output_format == OutputFormat::Json {
+        // the line ending is not part of the text, every object is on a line of its own
+        return if options.zero_terminated { "\0" } else { "\n" };
+    }
+    match options.ending_conversion {
+        Some(conversion) => conversion.apply(ending).as_str(),
+        None if options.keep_line_endings => ending.as_str(),
+        None if options.zero_terminated => "\0",
+        None => "\n",
+    }
+}
+
+/// Processes the whole input by applying each configured text feature to every line, followed by the lines
+/// the features add at its end.
+pub fn process_input_ret<R: Read>(
+    reader: R,
+    features: &mut [Box<dyn ByteFeature>],
+) -> Result<Vec<String>, RicatError> {
+    let mut processed_lines = process_input_ret_with(reader, "-", features, OutputOptions::default())?;
+    processed_lines.extend(finish_features(features));
+    Ok(processed_lines)
+}
+
+/// Processes one of several inputs like `process_input_ret`, reading the lines as given by the options:
+/// chunked or truncated, or NUL-terminated. The features are not finished, more input may follow.
+pub fn process_input_ret_with<R: Read>(
+    reader: R,
+    name: &str,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+) -> Result<Vec<String>, RicatError> {
+    process_input_ret_within(reader, name, features, options, &mut MemoryBudget::default())
+}
+
+/// Processes the input into lines like `process_input_ret_with`, counting them in the memory budget
+fn process_input_ret_within<R: Read>(
+    reader: R,
+    name: &str,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+    budget: &mut MemoryBudget,
+) -> Result<Vec<String>, RicatError> {
+    let buf_reader = BufReader::new(reader);
+    let mut processed_lines = Vec::new();
+    let mut lines = lossy_lines(buf_reader)
+        .with_limit(options.line_limit)
+        .zero_terminated(options.zero_terminated);
+
+    let mut line_number = 0;
+    while let Some(record_result) = lines.next_record() {
+        let (record, ending) = record_result?;
+        line_number += 1;
+
+        let context = line_context(&mut lines, name, line_number, ending, options.streaming)?;
+        let processed_records = apply_record_features(features, &record, &context).map_err(at_line(line_number))?;
+        let processed_records = decorate_records(&options, &context, processed_records);
+        for record in processed_records {
+            let line = into_text(record.into_owned());
+            budget.hold(line.len())?;
+            processed_lines.push(line);
+        }
+    }
+    Ok(processed_lines)
+}
+
+/// Puts the byte offset of their input line in front of the processed lines with `--show-offsets`,
+/// turns them into JSON objects with `--output-format json` or lays them out by the `--format` template
+fn decorate_records<'a>(options: &OutputOptions, context: &LineContext, records: Vec<Cow<'a, [u8]>>) -> Vec<Cow<'a, [u8]>> {
+    if let Some(template) = &options.template {
+        return records.iter().map(|record| Cow::Owned(template.render(context, record))).collect();
+    }
+    if options.output_format == OutputFormat::Json {
+        return records.iter().map(|record| Cow::Owned(json_record(options, context, record))).collect();
+    }
+    let Some(format) = options.offset_format else {
+        return records;
+    };
+    let offset = context.byte_offset;
+    let offset = match format {
+        OffsetFormat::Dec => format!("{:>10}", offset),
+        OffsetFormat::Hex => format!("{:08x}", offset),
+    };
+    records.into_iter().map(|record| Cow::Owned([format!("{} ", offset).as_bytes(), &record].concat())).collect()
+}
+
+/// The processed line as a JSON object, invalid UTF-8 replaced
+fn json_record(options: &OutputOptions, context: &LineContext, record: &[u8]) -> Vec<u8> {
+    let record = JsonRecord {
+        file: context.file_name,
+        line: context.line_number,
+        offset: options.offset_format.map(|_| context.byte_offset),
+        text: String::from_utf8_lossy(record),
+    };
+    // a struct of strings and numbers always serializes
+    serde_json::to_vec(&record).unwrap_or_default()
+}
+
+/// Tells every feature that a new input starts
+fn begin_input(features: &mut [Box<dyn ByteFeature>], source: &InputSource) -> Result<(), RicatError> {
+    for feature in features.iter_mut() {
+        feature.begin_records(source)?;
+    }
+    Ok(())
+}
+
+/// Lines the features add once all inputs ended, in the order of the features
+fn finish_features(features: &mut [Box<dyn ByteFeature>]) -> Vec<String> {
+    features.iter_mut().flat_map(|feature| feature.finish_records()).map(into_text).collect()
+}
+
+/// Passes a line through every feature in order, returns the resulting lines: none when a feature omits the line,
+/// several when one splits it.
+fn apply_features(
+    features: &mut [Box<dyn ByteFeature>],
+    line: String,
+    context: &LineContext,
+) -> Result<Vec<String>, RicatError> {
+    let processed_records = apply_record_features(features, line.as_bytes(), context)?;
+    Ok(processed_records.into_iter().map(Cow::into_owned).map(into_text).collect())
+}
+
+/// Passes the bytes of a record through every feature in order, like `apply_features`.
+/// Records the features keep as they are stay borrowed from the input, nothing is copied for them.
+fn apply_record_features<'a, F: ByteFeature + ?Sized>(
+    features: &mut [Box<F>],
+    record: &'a [u8],
+    context: &LineContext,
+) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+    let mut processed_records = vec![Cow::Borrowed(record)];
+
+    for feature in features.iter_mut() {
+        if processed_records.is_empty() {
+            break;
+        }
+        let mut next_records = Vec::with_capacity(processed_records.len());
+        for current_record in processed_records {
+            match current_record {
+                Cow::Borrowed(current_record) => next_records.extend(feature.apply_record(current_record, context)?),
+                Cow::Owned(current_record) => {
+                    let records = feature.apply_record(&current_record, context)?;
+                    // a record made by an earlier feature and kept as it is moves on, others are copied out of it
+                    let kept = matches!(records.as_slice(), [Cow::Borrowed(kept)] if kept.as_ptr() == current_record.as_ptr() && kept.len() == current_record.len());
+                    if kept {
+                        drop(records);
+                        next_records.push(Cow::Owned(current_record));
+                    } else {
+                        next_records.extend(records.into_iter().map(|record| Cow::Owned(record.into_owned())));
+                    }
+                }
+            }
+        }
+        processed_records = next_records;
+    }
+    Ok(processed_records)
+}
+
+/// Passes generated lines (table rows, diffs) through the features, like the lines of the named input
+fn process_lines(
+    features: &mut [Box<dyn ByteFeature>],
+    name: &str,
+    lines: Vec<String>,
+) -> Result<Vec<String>, RicatError> {
+    let count = lines.len();
+    let mut processed_lines = Vec::with_capacity(count);
+    for (index, line) in lines.into_iter().enumerate() {
+        let context = LineContext {
+            file_name: name,
+            line_number: index + 1,
+            is_last: index + 1 == count,
+            ..Default::default()
+        };
+        processed_lines.extend(apply_features(features, line, &context).map_err(at_line(index + 1))?);
+    }
+    Ok(processed_lines)
+}
+
+/// Context of the line read last, looking ahead for the end of the input unless it is read as it arrives
+fn line_context<'a, R: BufRead>(
+    lines: &mut LossyLines<R>,
+    name: &'a str,
+    line_number: usize,
+    ending: LineEnding,
+    streaming: bool,
+) -> std::io::Result<LineContext<'a>> {
+    let is_last = if streaming && ending != LineEnding::None { false } else { lines.at_end()? };
+    Ok(LineContext { file_name: name, line_number, byte_offset: lines.line_offset(), is_last })
+}
+
+/// Error of the processing of an input, feature errors already tell the line and are kept as they are,
+/// like memory limits, which are about the whole input
+fn processing_error(error: RicatError) -> RicatError {
+    match error {
+        RicatError::FeatureError(_) | RicatError::MemoryLimitError(_) => error,
+        error => RicatError::LineProcessingError(format!("Error processing line: {}", error)),
+    }
+}
+
+/// Adds the number of the line a feature failed on to its error, other errors stay as they are
+fn at_line(line_number: usize) -> impl FnOnce(RicatError) -> RicatError {
+    move |error| match error {
+        RicatError::FeatureError(message) => RicatError::FeatureError(format!("line {}: {}", line_number, message)),
+        error => error,
+    }
+}
@@ -0,0 +1,345 @@
+use std::io::{self, BufRead};
+
+use memchr::memchr;
+
+/// Line terminator found at the end of an input line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// NUL, with `-z`
+    Nul,
+    /// last line of the input without a terminator
+    None,
+}
+
+impl LineEnding {
+    /// The terminator as it appeared in the input
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Nul => "\0",
+            LineEnding::None => "",
+        }
+    }
+}
+
+/// Line terminator conversion applied on output (dos2unix/unix2dos style)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndingConversion {
+    /// `\r\n` becomes `\n`
+    CrLfToLf,
+    /// `\n` becomes `\r\n`
+    LfToCrLf,
+}
+
+impl EndingConversion {
+    /// Converts a line terminator, a missing final terminator stays missing
+    pub fn apply(&self, ending: LineEnding) -> LineEnding {
+        match (self, ending) {
+            (EndingConversion::CrLfToLf, LineEnding::CrLf) => LineEnding::Lf,
+            (EndingConversion::LfToCrLf, LineEnding::Lf) => LineEnding::CrLf,
+            (_, ending) => ending,
+        }
+    }
+}
+
+/// What happens to a line longer than the `--max-line-bytes` limit
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OversizeMode {
+    /// split the line into lines of at most the limit
+    #[default]
+    Chunk,
+    /// keep the start of the line up to the limit and skip the rest
+    Truncate,
+}
+
+/// Limit on the length of a single line, so a huge file without line breaks is never held in memory as one line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineLimit {
+    pub max_bytes: usize,
+    pub mode: OversizeMode,
+}
+
+/// Iterator over the lines of a reader which never fails on invalid UTF-8.
+///
+/// Lines are split on `\n` (a trailing `\r` is removed, same as `BufRead::lines()`),
+/// invalid UTF-8 sequences are replaced with `U+FFFD` instead of aborting the whole input.
+/// Each line is returned along with its original terminator.
+/// With `zero_terminated` the lines are NUL-terminated records instead, like `grep -z`.
+pub struct LossyLines<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// byte ending a line, `\n` or NUL
+    delimiter: u8,
+    limit: Option<LineLimit>,
+    /// start of the next chunk of an oversized line, read past the previous chunk
+    carry: Vec<u8>,
+    line_number: u64,
+    warned: bool,
+    /// bytes taken from the reader so far
+    consumed: u64,
+    /// byte offset of the line returned last
+    line_offset: u64,
+}
+
+/// Creates a `LossyLines` iterator over the reader
+pub fn lossy_lines<R: BufRead>(reader: R) -> LossyLines<R> {
+    LossyLines {
+        reader,
+        buffer: Vec::new(),
+        delimiter: b'\n',
+        limit: None,
+        carry: Vec::new(),
+        line_number: 0,
+        warned: false,
+        consumed: 0,
+        line_offset: 0,
+    }
+}
+
+impl<R: BufRead> LossyLines<R> {
+    /// Caps the length of the lines, longer lines are chunked or truncated with a warning on stderr.
+    /// The chunks of a line come without terminator, so writing them with their endings restores the line.
+    pub fn with_limit(mut self, limit: Option<LineLimit>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Splits the input on NUL instead of `\n`, for lists of file names and other records which may hold line breaks
+    pub fn zero_terminated(mut self, zero_terminated: bool) -> Self {
+        if zero_terminated {
+            self.delimiter = b'\0';
+        }
+        self
+    }
+
+    /// Byte offset in the input where the line returned last starts, the chunks of a long line have their own
+    pub fn line_offset(&self) -> u64 {
+        self.line_offset
+    }
+
+    /// Whether the input ended after the line returned last. Reads ahead when nothing is buffered,
+    /// which waits for more input on a terminal or socket.
+    pub fn at_end(&mut self) -> io::Result<bool> {
+        Ok(self.carry.is_empty() && self.reader.fill_buf()?.is_empty())
+    }
+
+    /// The next line as the raw bytes read, without its terminator, for the features working on bytes.
+    /// The iterator returns the same lines converted to text.
+    pub fn next_record(&mut self) -> Option<io::Result<(Vec<u8>, LineEnding)>> {
+        self.buffer.clear();
+        // the carried rest of a chunked line was consumed already, the next line starts with it
+        self.line_offset = self.consumed - self.carry.len() as u64;
+        if let Some(limit) = self.limit {
+            return self.next_limited(limit);
+        }
+        match self.read_line() {
+            Ok(0) => None,
+            Ok(len) => {
+                self.consumed += len as u64;
+                let ending = strip_line_ending(&mut self.buffer, self.delimiter);
+                Some(Ok((std::mem::take(&mut self.buffer), ending)))
+            }
+            Err(error) => Some(Err(error)),
+        }
+    }
+
+    /// Hands a record returned by `next_record` back once it was written, its allocation is used
+    /// for the next line, so lines passed through unchanged are read without allocating
+    pub fn reuse(&mut self, mut record: Vec<u8>) {
+        if record.capacity() > self.buffer.capacity() {
+            record.clear();
+            self.buffer = record;
+        }
+    }
+
+    /// Reads up to and including the next delimiter into the buffer, searching the reader's buffer
+    /// with `memchr` a whole block at a time. Returns the number of bytes read, 0 at the end of the input.
+    fn read_line(&mut self) -> io::Result<usize> {
+        let mut len = 0;
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            let (used, found) = match memchr(self.delimiter, available) {
+                Some(end) => (end + 1, true),
+                None => (available.len(), false),
+            };
+            self.buffer.extend_from_slice(&available[..used]);
+            self.reader.consume(used);
+            len += used;
+            if found || used == 0 {
+                return Ok(len);
+            }
+        }
+    }
+
+    fn next_limited(&mut self, limit: LineLimit) -> Option<io::Result<(Vec<u8>, LineEnding)>> {
+        let oversized = match self.read_limited_line(limit.max_bytes) {
+            Ok(oversized) => oversized,
+            Err(error) => return Some(Err(error)),
+        };
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let ending = if oversized {
+            match self.cut_oversized_line(limit) {
+                Ok(ending) => ending,
+                Err(error) => return Some(Err(error)),
+            }
+        } else {
+            self.line_number += 1;
+            strip_line_ending(&mut self.buffer, self.delimiter)
+        };
+        Some(Ok((std::mem::take(&mut self.buffer), ending)))
+    }
+
+    /// Reads the next line into the buffer, stopping after `max_bytes + 2` bytes without a line break,
+    /// which leaves room for a `\r\n` terminator. Returns whether the line is oversized.
+    fn read_limited_line(&mut self, max_bytes: usize) -> io::Result<bool> {
+        self.buffer.append(&mut self.carry);
+        while !self.buffer.ends_with(&[self.delimiter]) && self.buffer.len() < max_bytes + 2 {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+            let room = (max_bytes + 2 - self.buffer.len()).min(available.len());
+            let len = match memchr(self.delimiter, &available[..room]) {
+                Some(newline) => newline + 1,
+                None => room,
+            };
+            self.buffer.extend_from_slice(&available[..len]);
+            self.reader.consume(len);
+            self.consumed += len as u64;
+        }
+
+        let terminator = if self.delimiter == b'\n' && self.buffer.ends_with(b"\r\n") {
+            2
+        } else {
+            usize::from(self.buffer.ends_with(&[self.delimiter]))
+        };
+        Ok(self.buffer.len() - terminator > max_bytes)
+    }
+
+    /// Shortens an oversized line to the limit, without cutting a UTF-8 character in half
+    fn cut_oversized_line(&mut self, limit: LineLimit) -> io::Result<LineEnding> {
+        let cut = oversized_line_cut(&self.buffer, limit.max_bytes);
+        if !self.warned {
+            self.warned = true;
+            warn_oversized_line(self.line_number + 1, limit);
+        }
+
+        match limit.mode {
+            OversizeMode::Chunk => {
+                self.carry = self.buffer.split_off(cut);
+                Ok(LineEnding::None)
+            }
+            OversizeMode::Truncate => {
+                let last_byte = self.buffer.last().copied();
+                let ending = strip_line_ending(&mut self.buffer, self.delimiter);
+                self.buffer.truncate(cut);
+                if ending == LineEnding::None {
+                    self.skip_rest_of_line(last_byte)
+                } else {
+                    self.line_number += 1;
+                    Ok(ending)
+                }
+            }
+        }
+    }
+
+    /// Skips the rest of a truncated line without holding it in memory, returns its terminator
+    fn skip_rest_of_line(&mut self, mut last_byte: Option<u8>) -> io::Result<LineEnding> {
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(LineEnding::None);
+            }
+            match memchr(self.delimiter, available) {
+                Some(end) => {
+                    let carriage_return = match end {
+                        0 => last_byte == Some(b'\r'),
+                        _ => available[end - 1] == b'\r',
+                    };
+                    self.reader.consume(end + 1);
+                    self.consumed += end as u64 + 1;
+                    self.line_number += 1;
+                    return Ok(match (self.delimiter, carriage_return) {
+                        (b'\0', _) => LineEnding::Nul,
+                        (_, true) => LineEnding::CrLf,
+                        (_, false) => LineEnding::Lf,
+                    });
+                }
+                None => {
+                    last_byte = available.last().copied();
+                    let len = available.len();
+                    self.reader.consume(len);
+                    self.consumed += len as u64;
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LossyLines<R> {
+    type Item = io::Result<(String, LineEnding)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.next_record()?;
+        Some(record.map(|(bytes, ending)| (into_text(bytes), ending)))
+    }
+}
+
+/// Converts the bytes of a line to text, invalid UTF-8 sequences become `U+FFFD`
+pub fn into_text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned())
+}
+
+/// Where an oversized line is cut, at most `max_bytes` in and not in the middle of a UTF-8 character
+pub fn oversized_line_cut(line: &[u8], max_bytes: usize) -> usize {
+    let mut cut = max_bytes;
+    while cut > 0 && line[cut] & 0xc0 == 0x80 {
+        cut -= 1;
+    }
+    if cut == 0 {
+        cut = max_bytes;
+    }
+    cut
+}
+
+/// Warns on stderr that line `line_number` is over the limit, the readers only warn about the first one
+pub fn warn_oversized_line(line_number: u64, limit: LineLimit) {
+    let action = match limit.mode {
+        OversizeMode::Chunk => "split into chunks",
+        OversizeMode::Truncate => "truncated",
+    };
+    eprintln!(
+        "ricat: warning: line {} is longer than {} bytes and is {}",
+        line_number, limit.max_bytes, action
+    );
+}
+
+/// Removes the terminator from the end of a line, returning it
+pub fn strip_line_ending(buffer: &mut Vec<u8>, delimiter: u8) -> LineEnding {
+    if !buffer.ends_with(&[delimiter]) {
+        return LineEnding::None;
+    }
+    if delimiter == b'\0' {
+        buffer.pop();
+        return LineEnding::Nul;
+    }
+    buffer.pop();
+    if buffer.ends_with(b"\r") {
+        buffer.pop();
+        return LineEnding::CrLf;
+    }
+    LineEnding::Lf
+}
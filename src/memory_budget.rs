@@ -0,0 +1,94 @@
+use std::io::{self, Read};
+
+use crate::errors::RicatError;
+use crate::progress::format_bytes;
+
+/// Bytes of input and lines ricat may hold in memory at once, `--max-memory`. The modes needing the whole
+/// input (the pager, `--table`, `--yaml`, `--diff`, ...) count what they hold and stop with an error once it
+/// is more, instead of growing until the machine runs out of memory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryBudget {
+    limit: Option<usize>,
+    held: usize,
+}
+
+impl MemoryBudget {
+    /// Budget of `limit` bytes, none without a limit
+    pub fn new(limit: Option<usize>) -> Self {
+        Self { limit, held: 0 }
+    }
+
+    /// Counts `bytes` more held, fails once the total is over the limit
+    pub fn hold(&mut self, bytes: usize) -> Result<(), RicatError> {
+        self.held = self.held.saturating_add(bytes);
+        match self.limit {
+            Some(limit) if self.held > limit => Err(RicatError::MemoryLimitError(format!(
+                "more than {} would be held in memory (--max-memory), the output needs the whole input here",
+                format_bytes(limit as f64)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts `bytes` no longer held
+    pub fn release(&mut self, bytes: usize) {
+        self.held = self.held.saturating_sub(bytes);
+    }
+
+    /// Reads the whole reader into the buffer. Over the limit it stops reading right away, not once it is all read.
+    pub fn read_to_end(
+        &mut self,
+        mut reader: impl Read,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), RicatError> {
+        let len = match self.limit {
+            Some(limit) => {
+                let remaining = limit.saturating_sub(self.held) as u64;
+                reader.take(remaining + 1).read_to_end(buffer)?
+            }
+            None => reader.read_to_end(buffer)?,
+        };
+        self.hold(len)
+    }
+
+    /// Reads the whole reader into the string like `read_to_end`, failing on invalid UTF-8
+    pub fn read_to_string(
+        &mut self,
+        reader: impl Read,
+        text: &mut String,
+    ) -> Result<(), RicatError> {
+        let mut bytes = Vec::new();
+        self.read_to_end(reader, &mut bytes)?;
+        text.push_str(
+            &String::from_utf8(bytes)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+        );
+        Ok(())
+    }
+}
+
+/// Parses a size in bytes for `--max-memory`, with an optional `K`, `M` or `G` suffix for KiB, MiB and GiB
+pub fn parse_byte_size(value: &str) -> Result<usize, String> {
+    let (number, unit) = match value.char_indices().last() {
+        Some((index, suffix)) if suffix.is_ascii_alphabetic() => (&value[..index], Some(suffix)),
+        _ => (value, None),
+    };
+    let multiplier: usize = match unit.map(|unit| unit.to_ascii_uppercase()) {
+        None => 1,
+        Some('K') => 1024,
+        Some('M') => 1024 * 1024,
+        Some('G') => 1024 * 1024 * 1024,
+        Some(_) => return Err(format!("`{}` has an unknown unit, use K, M or G", value)),
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .filter(|&bytes| bytes > 0)
+        .ok_or_else(|| {
+            format!(
+                "`{}` is not a positive size in bytes, like 4096, 64M or 1G",
+                value
+            )
+        })
+}
@@ -0,0 +1,385 @@
+use std::fs::{File, Metadata, OpenOptions};
+use std::io::{self, stdout, BufWriter, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+use crate::errors::RicatError;
+use crate::zero_copy::DirectOutput;
+
+/// Output destination of the processed content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputTarget {
+    /// standard output
+    Stdout,
+    /// `-o FILE`: write to the file instead of standard output
+    File(String),
+    /// `--tee FILE`: write to the file and to standard output
+    Tee(String),
+    /// `--connect HOST:PORT`: send the output over a TCP connection
+    Tcp(String),
+}
+
+/// Device and inode of a regular file, to tell an input which is the output file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileIdentity {
+    device: u64,
+    inode: u64,
+}
+
+impl FileIdentity {
+    /// Identity of the file, none for anything but a regular file
+    #[cfg(unix)]
+    pub fn of(metadata: &Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        metadata.is_file().then(|| Self {
+            device: metadata.dev(),
+            inode: metadata.ino(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn of(_metadata: &Metadata) -> Option<Self> {
+        None
+    }
+}
+
+/// The regular file the output goes to: the standard output redirected to a file (`ricat a >> a`),
+/// or the `-o`/`--tee` file when it exists already. Checked before the output is opened, `-o` truncates it.
+pub fn output_identity(target: &OutputTarget) -> Option<FileIdentity> {
+    let metadata = match target {
+        OutputTarget::Stdout => stdout_metadata()?,
+        OutputTarget::File(path) | OutputTarget::Tee(path) => std::fs::metadata(path).ok()?,
+        OutputTarget::Tcp(_) => return None,
+    };
+    FileIdentity::of(&metadata)
+}
+
+#[cfg(unix)]
+fn stdout_metadata() -> Option<Metadata> {
+    use std::os::fd::AsFd;
+
+    let fd = stdout().as_fd().try_clone_to_owned().ok()?;
+    File::from(fd).metadata().ok()
+}
+
+#[cfg(not(unix))]
+fn stdout_metadata() -> Option<Metadata> {
+    None
+}
+
+/// Compression applied to the output with `--compress`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputCompression {
+    Gzip,
+    Zstd,
+}
+
+/// Writer compressing everything written to it, or passing it through unchanged.
+/// `finish` has to be called once all output is written, it writes the end of the compressed stream.
+pub enum CompressedWriter<W: Write> {
+    Plain(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Wraps the writer in an encoder, fails when the codec is not compiled in
+    pub fn new(writer: W, compression: Option<OutputCompression>) -> Result<Self, RicatError> {
+        match compression {
+            None => Ok(CompressedWriter::Plain(writer)),
+            #[cfg(feature = "gzip")]
+            Some(OutputCompression::Gzip) => Ok(CompressedWriter::Gzip(
+                flate2::write::GzEncoder::new(writer, flate2::Compression::default()),
+            )),
+            #[cfg(feature = "zstd")]
+            Some(OutputCompression::Zstd) => zstd::stream::write::Encoder::new(writer, 0)
+                .map(CompressedWriter::Zstd)
+                .map_err(|error| RicatError::CompressionError(error.to_string())),
+            #[allow(unreachable_patterns)]
+            Some(compression) => Err(RicatError::CompressionError(format!(
+                "ricat was built without the `{}` feature",
+                format!("{:?}", compression).to_lowercase()
+            ))),
+        }
+    }
+
+    /// Ends the compressed stream and returns the inner writer
+    pub fn finish(self) -> Result<W, RicatError> {
+        match self {
+            CompressedWriter::Plain(writer) => Ok(writer),
+            #[cfg(feature = "gzip")]
+            CompressedWriter::Gzip(encoder) => encoder
+                .finish()
+                .map_err(|error| RicatError::CompressionError(error.to_string())),
+            #[cfg(feature = "zstd")]
+            CompressedWriter::Zstd(encoder) => encoder
+                .finish()
+                .map_err(|error| RicatError::CompressionError(error.to_string())),
+        }
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressedWriter::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "gzip")]
+            CompressedWriter::Gzip(encoder) => encoder.write(buf),
+            #[cfg(feature = "zstd")]
+            CompressedWriter::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressedWriter::Plain(writer) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            CompressedWriter::Gzip(encoder) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            CompressedWriter::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Pace of the `--typewriter` output
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypewriterUnit {
+    /// pause after every character
+    #[default]
+    Char,
+    /// pause after every line
+    Line,
+}
+
+/// Writer printing its output slowly, one character or line at a time, for demos and teaching.
+/// Every unit is flushed before the pause, and the pause is a plain sleep, so Ctrl+C stops it right away.
+pub struct TypewriterWriter<W: Write> {
+    inner: W,
+    delay: Duration,
+    unit: TypewriterUnit,
+}
+
+impl<W: Write> TypewriterWriter<W> {
+    pub fn new(inner: W, delay: Duration, unit: TypewriterUnit) -> Self {
+        Self { inner, delay, unit }
+    }
+
+    fn write_unit(&mut self, unit: &[u8]) -> io::Result<()> {
+        self.inner.write_all(unit)?;
+        self.inner.flush()?;
+        thread::sleep(self.delay);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for TypewriterWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.unit {
+            TypewriterUnit::Char => {
+                let mut start = 0;
+                for end in 1..=buf.len() {
+                    // UTF-8 continuation bytes belong to the character before them
+                    let splits_character = buf.get(end).is_some_and(|byte| byte & 0xC0 == 0x80);
+                    if !splits_character {
+                        self.write_unit(&buf[start..end])?;
+                        start = end;
+                    }
+                }
+            }
+            TypewriterUnit::Line => {
+                for line in buf.split_inclusive(|&byte| byte == b'\n') {
+                    self.write_unit(line)?;
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// How the output file given by `-o`/`--tee` is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputFileOptions {
+    /// `--append`: add to the end of an existing file instead of truncating it
+    pub append: bool,
+    /// `--fsync`: sync the file to disk once all output has been written
+    pub fsync: bool,
+    /// `--buffer-size`: capacity of the buffer in front of the file or socket, the default one otherwise
+    pub buffer_size: Option<usize>,
+}
+
+/// Buffered output file
+pub struct OutputFile {
+    writer: BufWriter<File>,
+    fsync: bool,
+}
+
+impl OutputFile {
+    /// The file for copies by the kernel, sharing its position with this writer
+    pub fn direct_output(&self) -> Option<DirectOutput> {
+        DirectOutput::file(self.writer.get_ref())
+    }
+
+    /// With `--fsync`, the file to sync to disk once all output has been written and flushed
+    pub fn output_sync(&self) -> Result<Option<OutputSync>, RicatError> {
+        if !self.fsync {
+            return Ok(None);
+        }
+        self.writer
+            .get_ref()
+            .try_clone()
+            .map(|file| Some(OutputSync(file)))
+            .map_err(|error| {
+                RicatError::OutputFileError(format!(
+                    "Failed to open the output file for syncing: {}",
+                    error
+                ))
+            })
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The output file of `--fsync`, synced once at the end rather than on every flush, which `-u` does for every line
+pub struct OutputSync(File);
+
+impl OutputSync {
+    /// Syncs the data and metadata of the file to disk, the output must be flushed before
+    pub fn sync(self) -> Result<(), RicatError> {
+        self.0.sync_all().map_err(|error| {
+            RicatError::OutputFileError(format!("Failed to sync the output file: {}", error))
+        })
+    }
+}
+
+/// Writer duplicating everything written to it into two writers, like `tee`
+pub struct TeeWriter<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.first.write_all(buf)?;
+        self.second.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
+
+/// Opens the writer for the output target, files are written through a `BufWriter`.
+/// `--fsync` is left to the caller, `open_output_direct` returns the file to sync.
+pub fn open_output(
+    target: &OutputTarget,
+    options: OutputFileOptions,
+) -> Result<Box<dyn Write>, RicatError> {
+    match target {
+        OutputTarget::Stdout => Ok(Box::new(stdout())),
+        OutputTarget::File(path) => Ok(Box::new(open_output_file(path, options)?)),
+        OutputTarget::Tee(path) => Ok(Box::new(TeeWriter::new(
+            open_output_file(path, options)?,
+            stdout(),
+        ))),
+        OutputTarget::Tcp(address) => TcpStream::connect(address)
+            .map(|stream| Box::new(buffered(stream, options.buffer_size)) as Box<dyn Write>)
+            .map_err(|error| {
+                RicatError::SocketError(format!("Failed to connect to {}: {}", address, error))
+            }),
+    }
+}
+
+/// Writer of the output target opened by `open_output_direct`
+pub struct OpenedOutput {
+    pub writer: Box<dyn Write>,
+    /// output the kernel can copy files to directly
+    pub direct: Option<DirectOutput>,
+    /// file to sync with `--fsync` once the writer is flushed at the end
+    pub sync: Option<OutputSync>,
+}
+
+/// Opens the writer for the output target like `open_output`, along with the output the kernel
+/// can copy files to directly, for the standard output and `-o` files
+pub fn open_output_direct(
+    target: &OutputTarget,
+    options: OutputFileOptions,
+) -> Result<OpenedOutput, RicatError> {
+    match target {
+        OutputTarget::Stdout => Ok(OpenedOutput {
+            writer: Box::new(stdout()),
+            direct: DirectOutput::stdout(),
+            sync: None,
+        }),
+        OutputTarget::File(path) => {
+            let file = open_output_file(path, options)?;
+            Ok(OpenedOutput {
+                direct: file.direct_output(),
+                sync: file.output_sync()?,
+                writer: Box::new(file),
+            })
+        }
+        OutputTarget::Tee(path) => {
+            let file = open_output_file(path, options)?;
+            Ok(OpenedOutput {
+                direct: None,
+                sync: file.output_sync()?,
+                writer: Box::new(TeeWriter::new(file, stdout())),
+            })
+        }
+        _ => open_output(target, options).map(|writer| OpenedOutput {
+            writer,
+            direct: None,
+            sync: None,
+        }),
+    }
+}
+
+/// Creates (or truncates) the output file, or opens it for appending with `--append`.
+/// Appending uses `O_APPEND`, so every write lands at the current end of the file even when other processes append to it too.
+pub fn open_output_file(path: &str, options: OutputFileOptions) -> Result<OutputFile, RicatError> {
+    let mut open_options = OpenOptions::new();
+    if options.append {
+        open_options.append(true).create(true);
+    } else {
+        open_options.write(true).create(true).truncate(true);
+    }
+
+    let file = open_options.open(path).map_err(|error| {
+        RicatError::OutputFileError(format!("Failed to open {}: {}", path, error))
+    })?;
+    Ok(OutputFile {
+        writer: buffered(file, options.buffer_size),
+        fsync: options.fsync,
+    })
+}
+
+fn buffered<W: Write>(writer: W, buffer_size: Option<usize>) -> BufWriter<W> {
+    match buffer_size {
+        Some(size) => BufWriter::with_capacity(size, writer),
+        None => BufWriter::new(writer),
+    }
+}
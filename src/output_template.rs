@@ -0,0 +1,121 @@
+use std::fmt::Write as _;
+
+use regex::Regex;
+
+use crate::LineContext;
+
+/// A piece of the `--format` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    /// `{file}`: name of the input, `-` for stdin
+    File,
+    /// `{line}`: number of the line in its input
+    Line,
+    /// `{offset}`: byte offset where the line starts in its input
+    Offset,
+    /// `{text}`: the processed line
+    Text,
+    /// `{match}`: the text `--search` found in the line, empty without a search
+    Match,
+}
+
+/// Layout of every written line given with `--format`, e.g. `{file}:{line}: {text}` for editors and
+/// CI annotations. `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone)]
+pub struct OutputTemplate {
+    parts: Vec<Part>,
+    /// pattern of the search, for `{match}`
+    search: Option<Regex>,
+}
+
+impl OutputTemplate {
+    /// Parses the template, failing on unknown placeholders and unmatched braces
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        return Err(format!("`{{` without `}}` in `{}`", template));
+                    };
+                    let part = match &rest[..end] {
+                        "file" => Part::File,
+                        "line" => Part::Line,
+                        "offset" => Part::Offset,
+                        "text" => Part::Text,
+                        "match" => Part::Match,
+                        name => {
+                            return Err(format!(
+                                "unknown placeholder `{{{}}}`, use {{file}}, {{line}}, {{offset}}, {{text}} or {{match}}, and {{{{ }}}} for braces",
+                                name
+                            ))
+                        }
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => {
+                    return Err(format!(
+                        "`}}` without `{{` in `{}`, write `}}}}` for a brace",
+                        template
+                    ))
+                }
+                char => literal.push(char),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self {
+            parts,
+            search: None,
+        })
+    }
+
+    /// Fills `{match}` with what the pattern finds in the line
+    pub fn with_search(mut self, search: Regex) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    /// The processed line laid out by the template, invalid UTF-8 replaced
+    pub fn render(&self, context: &LineContext, record: &[u8]) -> Vec<u8> {
+        let text = String::from_utf8_lossy(record);
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => line.push_str(literal),
+                Part::File => line.push_str(context.file_name),
+                Part::Line => {
+                    let _ = write!(line, "{}", context.line_number);
+                }
+                Part::Offset => {
+                    let _ = write!(line, "{}", context.byte_offset);
+                }
+                Part::Text => line.push_str(&text),
+                Part::Match => {
+                    if let Some(found) = self.search.as_ref().and_then(|search| search.find(&text))
+                    {
+                        line.push_str(found.as_str());
+                    }
+                }
+            }
+        }
+        line.into_bytes()
+    }
+}
@@ -0,0 +1,287 @@
+use std::io::{stdout, IsTerminal, Write};
+
+use crossterm::{
+    cursor::{Hide, MoveUp, Show},
+    event::{read, Event, KeyCode},
+    execute,
+    terminal::{self, Clear, ClearType},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::PagesMode;
+use crate::errors::RicatError;
+use crate::theme::Style;
+
+/// Long-Line Mode: how the pager handles lines wider than the terminal
+#[derive(Deserialize, Serialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LongLineMode {
+    /// soft-wrap long lines, wrapped rows are counted against the page height
+    #[default]
+    Wrap,
+    /// truncate long lines to the terminal width, scroll with left/right arrow keys
+    Scroll,
+}
+
+/// Key pressed by the user at the pager prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PagerKey {
+    NextPage,
+    Quit,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// get current user terminal height for pagination
+fn get_terminal_height() -> u16 {
+    match terminal::size() {
+        Ok((_, height)) => height,
+        Err(_) => 24, //default
+    }
+}
+
+/// get current user terminal width for long line handling
+pub fn get_terminal_width() -> u16 {
+    match terminal::size() {
+        Ok((width, _)) => width,
+        Err(_) => 80, //default
+    }
+}
+
+/// Number of lines on a pager page: the terminal rows, less the one of the prompt
+pub fn page_size() -> usize {
+    (get_terminal_height().saturating_sub(1) as usize).max(1)
+}
+
+/// Decides whether the pager is needed for the given number of output lines.
+/// `auto` mode behaves like `git`: the pager is used only when stdout is a terminal and the output does not fit the screen.
+pub fn should_paginate(mode: Option<PagesMode>, line_count: usize) -> bool {
+    match mode {
+        None | Some(PagesMode::Never) => false,
+        Some(PagesMode::Always) => true,
+        Some(PagesMode::Auto) => {
+            stdout().is_terminal() && line_count > page_size()
+        }
+    }
+}
+
+/// Whether the pagination mode may need the pager at all, the output has to be collected before paginating.
+pub fn pagination_requested(mode: Option<PagesMode>) -> bool {
+    match mode {
+        Some(PagesMode::Always) => true,
+        Some(PagesMode::Auto) => stdout().is_terminal(),
+        None | Some(PagesMode::Never) => false,
+    }
+}
+
+/// Number of terminal rows a line occupies when soft-wrapped at `width` columns
+pub fn wrapped_rows(line: &str, width: usize) -> usize {
+    let columns = line.chars().count();
+    if width == 0 || columns == 0 {
+        return 1;
+    }
+    columns.div_ceil(width)
+}
+
+/// Visible part of a line when horizontally scrolled by `offset` columns
+pub fn visible_slice(line: &str, offset: usize, width: usize) -> String {
+    line.chars().skip(offset).take(width).collect()
+}
+
+/// Paginate output
+pub fn paginate_output<W: Write>(lines: Vec<String>, writer: W) -> Result<bool, RicatError> {
+    paginate_output_with(lines, writer, LongLineMode::Wrap, &Style::default())
+}
+
+/// Paginate output, handling lines wider than the terminal according to `long_lines`,
+/// with the prompt drawn in the `status` style
+pub fn paginate_output_with<W: Write>(
+    lines: Vec<String>,
+    writer: W,
+    long_lines: LongLineMode,
+    status: &Style,
+) -> Result<bool, RicatError> {
+    let page_size = page_size();
+    let terminal_width = get_terminal_width() as usize;
+
+    match long_lines {
+        LongLineMode::Wrap => paginate_wrapped(&lines, writer, page_size, terminal_width, status),
+        LongLineMode::Scroll => {
+            paginate_scrolled(&lines, writer, page_size, terminal_width, status)
+        }
+    }
+}
+
+/// Soft-wrap pagination: a page holds as many lines as fit in `page_size` terminal rows
+fn paginate_wrapped<W: Write>(
+    lines: &[String],
+    mut writer: W,
+    page_size: usize,
+    terminal_width: usize,
+    status: &Style,
+) -> Result<bool, RicatError> {
+    let mut used_rows = 0;
+
+    for current_line in lines {
+        let rows = wrapped_rows(current_line, terminal_width);
+        if used_rows > 0 && used_rows + rows > page_size {
+            match wait_for_user_input(&mut writer, status) {
+                Ok(true) => used_rows = 0,
+                Ok(false) => return Ok(false),
+                Err(error) => return Err(error),
+            }
+        }
+
+        writeln!(writer, "{}\r", current_line).map_err(|error| {
+            RicatError::PaginationError(format!("Error writing line: {}", error))
+        })?;
+        used_rows += rows;
+    }
+    Ok(true)
+}
+
+/// Horizontal-scroll pagination: lines are truncated to the terminal width, left/right arrows shift the view
+fn paginate_scrolled<W: Write>(
+    lines: &[String],
+    mut writer: W,
+    page_size: usize,
+    terminal_width: usize,
+    status: &Style,
+) -> Result<bool, RicatError> {
+    let scroll_step = (terminal_width / 2).max(1);
+    let mut offset = 0;
+
+    for page in lines.chunks(page_size) {
+        write_scrolled_page(&mut writer, page, offset, terminal_width)?;
+
+        // the prompt is shown after every page, including the last one, so it can be scrolled too
+        loop {
+            match wait_for_pager_key(&mut writer, status)? {
+                PagerKey::NextPage => break,
+                PagerKey::Quit => return Ok(false),
+                key => {
+                    offset = match key {
+                        PagerKey::ScrollLeft => offset.saturating_sub(scroll_step),
+                        _ => offset + scroll_step,
+                    };
+                    // redraw the current page in place with the new offset
+                    execute!(
+                        writer,
+                        MoveUp(page.len() as u16),
+                        Clear(ClearType::FromCursorDown)
+                    )
+                    .map_err(|error| RicatError::CursorMoveError(error.to_string()))?;
+                    write_scrolled_page(&mut writer, page, offset, terminal_width)?;
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Writes one page of horizontally scrolled lines
+fn write_scrolled_page<W: Write>(
+    writer: &mut W,
+    page: &[String],
+    offset: usize,
+    terminal_width: usize,
+) -> Result<(), RicatError> {
+    for current_line in page {
+        writeln!(
+            writer,
+            "{}\r",
+            visible_slice(current_line, offset, terminal_width)
+        )
+        .map_err(|error| RicatError::PaginationError(format!("Error writing line: {}", error)))?;
+    }
+    Ok(())
+}
+
+// Paginate Output using Iterators
+fn _paginate_output_iterator<W: Write>(
+    lines: impl Iterator<Item = String>,
+    mut writer: W,
+) -> Result<bool, RicatError> {
+    let terminal_height = get_terminal_height() as usize;
+    let page_size = terminal_height.saturating_sub(1);
+
+    let mut lines_iter = lines.enumerate();
+
+    loop {
+        let mut current_page_lines = Vec::new();
+        for (_idx, curr_line) in lines_iter.by_ref().take(page_size) {
+            current_page_lines.push(curr_line);
+        }
+
+        if current_page_lines.is_empty() {
+            break;
+        }
+
+        for curr_line in current_page_lines {
+            writeln!(writer, "{}\r", curr_line).map_err(|error| {
+                RicatError::PaginationError(format!("Error writing line: {}", error))
+            })?;
+        }
+
+        match wait_for_user_input(&mut writer, &Style::default()) {
+            Ok(true) => continue,
+            Ok(false) => return Ok(false),
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(true)
+}
+
+/// Waiting for User Input, the prompt drawn in the `status` style
+pub fn wait_for_user_input<W: Write>(writer: &mut W, status: &Style) -> Result<bool, RicatError> {
+    loop {
+        match wait_for_pager_key(writer, status)? {
+            PagerKey::NextPage => return Ok(true),
+            PagerKey::Quit => return Ok(false),
+            PagerKey::ScrollLeft | PagerKey::ScrollRight => continue,
+        }
+    }
+}
+
+/// Shows the pager prompt and waits for a key press
+fn wait_for_pager_key<W: Write>(writer: &mut W, status: &Style) -> Result<PagerKey, RicatError> {
+    execute!(writer, Hide).map_err(|error| RicatError::CursorHideError(error.to_string()))?;
+
+    write!(
+        writer,
+        "{}",
+        status.paint("--More--(press any key || q to quit)")
+    )
+    .map_err(|error| RicatError::LineWriteError(error.to_string()))?;
+    writer
+        .flush()
+        .map_err(|error| RicatError::OutputFlushError(error.to_string()))?;
+
+    crossterm::terminal::enable_raw_mode()
+        .map_err(|error| RicatError::RawModeEnableError(error.to_string()))?;
+
+    let pressed_key = loop {
+        match read() {
+            Ok(Event::Key(key_event)) => match key_event.code {
+                KeyCode::Char('q') => break PagerKey::Quit,
+                KeyCode::Left => break PagerKey::ScrollLeft,
+                KeyCode::Right => break PagerKey::ScrollRight,
+                _ => break PagerKey::NextPage,
+            },
+            Ok(_) => continue,
+            Err(error) => {
+                return Err(RicatError::InputReadError(error.to_string()));
+            }
+        }
+    };
+
+    crossterm::terminal::disable_raw_mode()
+        .map_err(|error| RicatError::RawModeDisableError(error.to_string()))?;
+    execute!(writer, Show).map_err(|error| RicatError::CursorShowError(error.to_string()))?;
+
+    execute!(writer, Clear(ClearType::CurrentLine))
+        .map_err(|error| RicatError::ClearLineError(error.to_string()))?;
+    write!(writer, "\r").map_err(|error| RicatError::CursorMoveError(error.to_string()))?;
+
+    Ok(pressed_key)
+}
@@ -0,0 +1,387 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+
+use memmap2::Mmap;
+
+use crate::binary_file::{looks_binary, SNIFF_SIZE};
+use crate::decompression::CompressionFormat;
+use crate::errors::RicatError;
+use crate::input_source::InputSource;
+use crate::line_reader::lossy_lines;
+use crate::progress::LARGE_FILE_SIZE;
+use crate::{
+    apply_record_features, at_line, banner_lines, begin_input, binary_mode, builtin_features,
+    check_binary, decorate_records, line_context, open_text, output_ending, process_input_to,
+    processing_error, skip_failed_input, write_lines_to, write_records, ByteFeature, Cli,
+    OutputOptions,
+};
+
+/// Files at least this large are searched in chunks on several threads with `--jobs`
+pub const CHUNKED_SEARCH_SIZE: u64 = LARGE_FILE_SIZE;
+
+/// Size of the chunks of a chunked search, each is extended to the end of the line it stops in
+pub const SEARCH_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Output the threads hold for the inputs waiting on the ones before them, no further input is started past it
+pub const PENDING_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Number of threads processing files with `--jobs`, one per core unless given
+pub fn job_count(jobs: Option<usize>) -> usize {
+    jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()))
+}
+
+/// Whether the inputs can go through the features in parallel: threads to spare, and features which keep
+/// nothing from one line to the next, so every thread can have its own
+pub fn can_process_in_parallel(features: &[Box<dyn ByteFeature>], jobs: usize) -> bool {
+    jobs > 1 && features.iter().all(|feature| feature.is_stateless())
+}
+
+/// Whether the inputs gain from going through the threads: two or more, all regular files. The standard input,
+/// FIFOs and the like are streamed instead, their lines are written as they are read.
+/// Inputs which cannot be found are skipped by both and left out.
+pub fn worth_processing_in_parallel(sources: &[Result<InputSource, RicatError>]) -> bool {
+    let mut files = 0;
+    for source in sources.iter().flatten() {
+        match source {
+            InputSource::File(path) => match std::fs::metadata(path) {
+                Ok(metadata) if metadata.is_file() => files += 1,
+                Ok(_) => return false,
+                Err(_) => {}
+            },
+            _ => return false,
+        }
+    }
+    files >= 2
+}
+
+/// Output of one input, as far as it got, and the error which stopped it
+type InputOutput = (Vec<u8>, Option<RicatError>);
+
+/// Processes the inputs on `jobs` threads, each with its own instances of the features, and writes
+/// their outputs in the order of the inputs. An input is held in memory until the ones before it are written.
+/// An input which cannot be read is reported and skipped like in the sequential loop, other errors
+/// (and with `--fail-fast` any error) stop the processing after the output the input produced before failing.
+pub fn process_files_in_parallel(
+    arguments: &Cli,
+    sources: Vec<InputSource>,
+    jobs: usize,
+    options: OutputOptions,
+    writer: &mut dyn Write,
+) -> Result<(), RicatError> {
+    in_order(
+        arguments,
+        &sources,
+        jobs,
+        |features, index, source| process_file(arguments, source, index, features, options.clone()),
+        |(buffer, error)| {
+            write_output(writer, &buffer)?;
+            error.map_or(Ok(()), |error| skip_failed_input(arguments, error))
+        },
+    )
+}
+
+/// Runs `process` on every item on `jobs` threads, each with its own instances of the features,
+/// and hands the results to `write` in the order of the items. Once `write` fails, no further item is started.
+/// While the output of the items waiting to be written is past `PENDING_LIMIT`, the threads wait for it to be.
+fn in_order<T, E>(
+    arguments: &Cli,
+    items: &[T],
+    jobs: usize,
+    process: impl Fn(&mut [Box<dyn ByteFeature>], usize, &T) -> (Vec<u8>, E) + Sync,
+    mut write: impl FnMut((Vec<u8>, E)) -> Result<(), RicatError>,
+) -> Result<(), RicatError>
+where
+    T: Sync,
+    E: Send,
+{
+    let next_item = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let pending_bytes = Mutex::new(0);
+    let written = Condvar::new();
+    let (sender, receiver) = mpsc::channel::<Result<(usize, (Vec<u8>, E)), RicatError>>();
+    // the flag is set under the lock, a thread cannot miss it between checking it and waiting
+    let halt = || {
+        let _pending = pending_bytes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        stop.store(true, Ordering::Relaxed);
+        written.notify_all();
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            let sender = sender.clone();
+            let (next_item, stop, process) = (&next_item, &stop, &process);
+            let (pending_bytes, written) = (&pending_bytes, &written);
+            scope.spawn(move || {
+                let mut features = match builtin_features().build(
+                    arguments,
+                    &arguments.feature_names,
+                    &arguments.feature_order,
+                ) {
+                    Ok(features) => features,
+                    Err(error) => {
+                        let _ = sender.send(Err(error));
+                        return;
+                    }
+                };
+                while !stop.load(Ordering::Relaxed) {
+                    {
+                        let mut pending = pending_bytes
+                            .lock()
+                            .unwrap_or_else(|error| error.into_inner());
+                        while *pending > PENDING_LIMIT && !stop.load(Ordering::Relaxed) {
+                            pending = written
+                                .wait(pending)
+                                .unwrap_or_else(|error| error.into_inner());
+                        }
+                    }
+                    let index = next_item.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let output = process(&mut features, index, item);
+                    *pending_bytes
+                        .lock()
+                        .unwrap_or_else(|error| error.into_inner()) += output.0.len();
+                    if sender.send(Ok((index, output))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // results arrive in any order, each is written once all the ones before it are
+        let mut pending = BTreeMap::new();
+        let mut next_to_write = 0;
+        for received in receiver {
+            let (index, output) = received.inspect_err(|_| halt())?;
+            pending.insert(index, output);
+            while let Some(output) = pending.remove(&next_to_write) {
+                let size = output.0.len();
+                write(output).inspect_err(|_| halt())?;
+                *pending_bytes
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner()) -= size;
+                written.notify_all();
+                next_to_write += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Searches a large file with `--search` in chunks on `jobs` threads: the file is mapped to memory, cut
+/// into chunks ending at line ends, and the lines found in every chunk are written in the order of the file.
+/// Returns `false`, without writing anything, when the file is too small to gain from it or is not plain text
+/// in the file itself (compressed, binary content, another encoding), it is then processed as usual.
+pub fn search_file_in_chunks(
+    arguments: &Cli,
+    source: &InputSource,
+    index: usize,
+    jobs: usize,
+    options: OutputOptions,
+    writer: &mut dyn Write,
+) -> Result<bool, RicatError> {
+    let InputSource::File(path) = source else {
+        return Ok(false);
+    };
+    // JSON and templated lines tell the line number, which starts over in every chunk
+    if options.writes_line_numbers() {
+        return Ok(false);
+    }
+    let Ok(file) = File::open(path) else {
+        return Ok(false);
+    };
+    match file.metadata() {
+        Ok(metadata) if metadata.is_file() && metadata.len() >= CHUNKED_SEARCH_SIZE => {}
+        _ => return Ok(false),
+    }
+    let Ok(mmap) = (unsafe { Mmap::map(&file) }) else {
+        return Ok(false);
+    };
+    let head = &mmap[..SNIFF_SIZE.min(mmap.len())];
+    let binary = binary_mode(arguments).is_some() && looks_binary(head);
+    if arguments.from_encoding.is_some() || CompressionFormat::detect(head).is_some() || binary {
+        return Ok(false);
+    }
+
+    if arguments.show_filenames {
+        write_lines_to(banner_lines(source, index), writer)?;
+    }
+    search_in_chunks(
+        arguments,
+        &mmap,
+        source,
+        SEARCH_CHUNK_SIZE,
+        jobs,
+        options,
+        writer,
+    )?;
+    Ok(true)
+}
+
+/// Processes the data of an input in chunks of about `chunk_size` bytes on `jobs` threads, each with its own
+/// instances of the features, and writes the lines of every chunk in the order of the data
+///
+/// The line numbers the features see start over in every chunk, stateless features do not look at them.
+pub fn search_in_chunks(
+    arguments: &Cli,
+    data: &[u8],
+    source: &InputSource,
+    chunk_size: usize,
+    jobs: usize,
+    options: OutputOptions,
+    writer: &mut dyn Write,
+) -> Result<(), RicatError> {
+    let delimiter = if options.zero_terminated {
+        b'\0'
+    } else {
+        b'\n'
+    };
+    let chunks = chunk_ranges(data, delimiter, chunk_size);
+    let last_chunk = chunks.len() - 1;
+    let name = source.name();
+
+    let mut lines_before = 0;
+    in_order(
+        arguments,
+        &chunks,
+        jobs,
+        |features, index, chunk| {
+            let mut buffer = Vec::new();
+            if let Err(error) = begin_input(features, source) {
+                return (buffer, Err((None, error)));
+            }
+            let result = process_chunk(
+                &data[chunk.clone()],
+                chunk.start as u64,
+                &name,
+                index == last_chunk,
+                &mut buffer,
+                features,
+                options.clone(),
+            );
+            (buffer, result.map_err(|(line, error)| (Some(line), error)))
+        },
+        |(buffer, result)| {
+            write_output(writer, &buffer)?;
+            match result {
+                Ok(lines) => {
+                    lines_before += lines;
+                    Ok(())
+                }
+                // errors tell the line in the whole input, counting the lines of the chunks before
+                Err((Some(line), error)) => {
+                    Err(processing_error(at_line(lines_before + line)(error)))
+                }
+                Err((None, error)) => Err(error),
+            }
+        },
+    )
+}
+
+/// Cuts the data into ranges of about `size` bytes, each extended to the end of the line it stops in
+pub fn chunk_ranges(data: &[u8], delimiter: u8, size: usize) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let end = match data.get(start + size..) {
+            Some(rest) => rest
+                .iter()
+                .position(|&byte| byte == delimiter)
+                .map_or(data.len(), |position| start + size + position + 1),
+            None => data.len(),
+        };
+        chunks.push(start..end);
+        start = end;
+    }
+    if chunks.is_empty() {
+        chunks.push(0..0);
+    }
+    chunks
+}
+
+/// Processes the lines of one chunk into the buffer, like `process_input_to`, with the byte offsets
+/// of the lines in the whole file. Returns the number of lines read, or the error with the line of the
+/// chunk it happened on.
+fn process_chunk(
+    data: &[u8],
+    start: u64,
+    name: &str,
+    is_last_chunk: bool,
+    buffer: &mut Vec<u8>,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+) -> Result<usize, (usize, RicatError)> {
+    let mut lines = lossy_lines(data)
+        .with_limit(options.line_limit)
+        .zero_terminated(options.zero_terminated);
+    let terminator = if options.zero_terminated { "\0" } else { "\n" };
+
+    let mut line_number = 0;
+    while let Some(record_result) = lines.next_record() {
+        let (record, ending) = record_result.map_err(|error| (line_number, error.into()))?;
+        line_number += 1;
+
+        let mut context = line_context(&mut lines, name, line_number, ending, false)
+            .map_err(|error| (line_number, error.into()))?;
+        // the end of a chunk is not the end of the input, only the end of the last one is
+        context.is_last &= is_last_chunk;
+        context.byte_offset += start;
+        let processed_records = apply_record_features(features, &record, &context)
+            .map_err(|error| (line_number, error))?;
+        let processed_lines = decorate_records(&options, &context, processed_records);
+        write_records(
+            buffer,
+            &processed_lines,
+            output_ending(&options, ending),
+            terminator,
+        )
+        .map_err(|error| (line_number, error))?;
+    }
+    Ok(line_number)
+}
+
+fn write_output(writer: &mut dyn Write, buffer: &[u8]) -> Result<(), RicatError> {
+    writer
+        .write_all(buffer)
+        .map_err(|error| RicatError::LineProcessingError(format!("Error writing line: {}", error)))
+}
+
+/// Processes one input into a buffer, like the sequential loop writes it
+fn process_file(
+    arguments: &Cli,
+    source: &InputSource,
+    index: usize,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+) -> InputOutput {
+    let mut buffer = Vec::new();
+    let result = write_file(arguments, source, index, features, options, &mut buffer);
+    (buffer, result.err())
+}
+
+fn write_file(
+    arguments: &Cli,
+    source: &InputSource,
+    index: usize,
+    features: &mut [Box<dyn ByteFeature>],
+    options: OutputOptions,
+    buffer: &mut Vec<u8>,
+) -> Result<(), RicatError> {
+    if arguments.show_filenames {
+        write_lines_to(banner_lines(source, index), buffer)?;
+    }
+    begin_input(features, source)?;
+    if let Some(reader) = check_binary(open_text(source, arguments)?, source, arguments, buffer)? {
+        process_input_to(reader, &source.name(), buffer, features, options)
+            .map_err(processing_error)?;
+    }
+    Ok(())
+}
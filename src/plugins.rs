@@ -0,0 +1,220 @@
+use std::path::Path;
+#[cfg(feature = "plugins")]
+use std::{borrow::Cow, cell::OnceCell, ffi::c_void, sync::Arc};
+
+#[cfg(feature = "plugins")]
+use crate::errors::RicatError;
+use crate::feature_registry::FeatureRegistry;
+#[cfg(feature = "plugins")]
+use crate::{ByteFeature, LineContext};
+
+/// Version of the plugin interface, a plugin reporting another one is not loaded
+#[cfg(feature = "plugins")]
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Callback a plugin calls once for every line it produces, with the bytes of the line
+#[cfg(feature = "plugins")]
+pub type EmitLine = unsafe extern "C" fn(sink: *mut c_void, line: *const u8, len: usize);
+
+/// Functions of a plugin, the C interface every plugin library exports:
+///
+/// - `uint32_t ricat_plugin_abi_version(void)`, which returns `PLUGIN_ABI_VERSION`
+/// - `const char *ricat_plugin_name(void)`, the name to enable the feature with, e.g. `--features todo`,
+///   which is also the name of the library file (`todo.so` or `libtodo.so`)
+/// - `void *ricat_plugin_new(void)`, a new instance of the feature, null when it cannot be made
+/// - `int32_t ricat_plugin_apply(void *state, const uint8_t *line, size_t len, emit, void *sink)`,
+///   which calls `emit(sink, ...)` for every resulting line (none to omit the line) and returns 0, or fails
+///   with another value
+/// - `void ricat_plugin_free(void *state)`
+#[cfg(feature = "plugins")]
+#[derive(Clone, Copy)]
+pub struct PluginApi {
+    pub new: unsafe extern "C" fn() -> *mut c_void,
+    pub apply: unsafe extern "C" fn(*mut c_void, *const u8, usize, EmitLine, *mut c_void) -> i32,
+    pub free: unsafe extern "C" fn(*mut c_void),
+}
+
+/// Instance of a plugin feature, the state is freed by the plugin when it is dropped
+#[cfg(feature = "plugins")]
+pub struct PluginFeature {
+    name: String,
+    api: PluginApi,
+    state: *mut c_void,
+    /// the library the functions live in, kept loaded as long as the feature is
+    _library: Option<Arc<dyn Send + Sync>>,
+}
+
+#[cfg(feature = "plugins")]
+impl PluginFeature {
+    /// Makes a new instance of the plugin, failing when it returns no state
+    pub fn new(
+        name: &str,
+        api: PluginApi,
+        library: Option<Arc<dyn Send + Sync>>,
+    ) -> Result<Self, RicatError> {
+        let state = unsafe { (api.new)() };
+        if state.is_null() {
+            return Err(RicatError::FeatureError(format!(
+                "plugin `{}` could not make a new instance",
+                name
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            api,
+            state,
+            _library: library,
+        })
+    }
+}
+
+/// Collects the lines emitted by a plugin into the `Vec<Vec<u8>>` behind `sink`
+#[cfg(feature = "plugins")]
+unsafe extern "C" fn collect_line(sink: *mut c_void, line: *const u8, len: usize) {
+    let lines = &mut *(sink as *mut Vec<Vec<u8>>);
+    lines.push(if len == 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(line, len).to_vec()
+    });
+}
+
+#[cfg(feature = "plugins")]
+impl ByteFeature for PluginFeature {
+    fn apply_record<'a>(
+        &mut self,
+        record: &'a [u8],
+        _context: &LineContext,
+    ) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let mut lines: Vec<Vec<u8>> = Vec::new();
+        let status = unsafe {
+            (self.api.apply)(
+                self.state,
+                record.as_ptr(),
+                record.len(),
+                collect_line,
+                &mut lines as *mut Vec<Vec<u8>> as *mut c_void,
+            )
+        };
+        if status != 0 {
+            return Err(RicatError::FeatureError(format!(
+                "plugin `{}` failed with status {}",
+                self.name, status
+            )));
+        }
+        Ok(lines.into_iter().map(Cow::Owned).collect())
+    }
+}
+
+#[cfg(feature = "plugins")]
+impl Drop for PluginFeature {
+    fn drop(&mut self) {
+        unsafe { (self.api.free)(self.state) }
+    }
+}
+
+/// Registers every plugin library in the directory as a feature named after the file, without a `lib` prefix:
+/// `todo.so` and `libtodo.so` are enabled with `--features todo`. A library is only loaded once its feature
+/// is requested, and one which does not load stops ricat with its error then. Libraries whose names are taken
+/// are skipped with a warning.
+#[cfg(feature = "plugins")]
+pub fn register_plugins(registry: &mut FeatureRegistry, directory: &Path) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension() == Some(std::env::consts::DLL_EXTENSION.as_ref()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(name) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| stem.strip_prefix("lib").unwrap_or(stem).to_string())
+        else {
+            continue;
+        };
+        if registry.names().contains(&name.as_str()) {
+            eprintln!(
+                "ricat: warning: plugin {} is not loaded, the feature `{}` exists already",
+                path.display(),
+                name
+            );
+            continue;
+        }
+        // loaded on the first use, every instance shares the library
+        let loaded: OnceCell<(Arc<dyn Send + Sync>, PluginApi)> = OnceCell::new();
+        let feature_name = name.clone();
+        registry.register_by_name(&name, move |_| {
+            let (library, api) = match loaded.get() {
+                Some(loaded) => loaded,
+                None => {
+                    let (library, api) = load_plugin(&path, &feature_name).map_err(|error| {
+                        RicatError::FeatureError(format!(
+                            "plugin {} cannot be loaded: {}",
+                            path.display(),
+                            error
+                        ))
+                    })?;
+                    loaded.get_or_init(|| (Arc::new(library), api))
+                }
+            };
+            let feature = PluginFeature::new(&feature_name, *api, Some(Arc::clone(library)))?;
+            Ok(Box::new(feature))
+        });
+    }
+}
+
+/// Loads a plugin library and looks up its functions, the name it tells has to be the one of its file
+#[cfg(feature = "plugins")]
+fn load_plugin(
+    path: &Path,
+    expected_name: &str,
+) -> Result<(libloading::Library, PluginApi), String> {
+    use std::ffi::{c_char, CStr};
+
+    unsafe {
+        let library = libloading::Library::new(path).map_err(|error| error.to_string())?;
+        let symbol_error = |error: libloading::Error| error.to_string();
+
+        let version = library
+            .get::<unsafe extern "C" fn() -> u32>(b"ricat_plugin_abi_version\0")
+            .map_err(symbol_error)?();
+        if version != PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "it has interface version {}, ricat needs {}",
+                version, PLUGIN_ABI_VERSION
+            ));
+        }
+
+        let name = library
+            .get::<unsafe extern "C" fn() -> *const c_char>(b"ricat_plugin_name\0")
+            .map_err(symbol_error)?();
+        if name.is_null() {
+            return Err("it has no name".to_string());
+        }
+        let name = CStr::from_ptr(name)
+            .to_str()
+            .map_err(|_| "its name is not UTF-8".to_string())?;
+        if name != expected_name {
+            return Err(format!(
+                "it is named `{}`, the file has to be named after it: `{}.{}`",
+                name,
+                name,
+                std::env::consts::DLL_EXTENSION
+            ));
+        }
+
+        let api = PluginApi {
+            new: *library.get(b"ricat_plugin_new\0").map_err(symbol_error)?,
+            apply: *library.get(b"ricat_plugin_apply\0").map_err(symbol_error)?,
+            free: *library.get(b"ricat_plugin_free\0").map_err(symbol_error)?,
+        };
+        Ok((library, api))
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn register_plugins(_registry: &mut FeatureRegistry, _directory: &Path) {}
@@ -0,0 +1,132 @@
+use std::io::{stderr, Write};
+use std::time::{Duration, Instant};
+
+/// Files at least this large show the progress bar on their own when the output is not a terminal
+pub const LARGE_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Minimum time between two redraws of the progress bar
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Width of the bar itself, in characters
+const BAR_WIDTH: usize = 30;
+
+/// Progress bar on stderr for a single copy, showing the copied bytes, throughput and ETA
+pub struct Progress {
+    label: String,
+    total: Option<u64>,
+    copied: u64,
+    started: Instant,
+    last_draw: Option<Instant>,
+    last_width: usize,
+}
+
+impl Progress {
+    /// Starts the progress of copying `label`, `total` is the size in bytes when it is known
+    pub fn new(label: &str, total: Option<u64>) -> Self {
+        Self {
+            label: label.to_string(),
+            total,
+            copied: 0,
+            started: Instant::now(),
+            last_draw: None,
+            last_width: 0,
+        }
+    }
+
+    /// Records `len` more copied bytes, redrawing the bar at most every `REDRAW_INTERVAL`
+    pub fn advance(&mut self, len: usize) {
+        self.copied += len as u64;
+        let due = self
+            .last_draw
+            .is_none_or(|last_draw| last_draw.elapsed() >= REDRAW_INTERVAL);
+        if due {
+            self.draw();
+        }
+    }
+
+    /// Draws the final state of the bar and moves to the next line
+    pub fn finish(&mut self) {
+        self.draw();
+        eprintln!();
+    }
+
+    fn draw(&mut self) {
+        self.last_draw = Some(Instant::now());
+        let line = self.render(self.started.elapsed());
+        // pad with spaces to overwrite the rest of a longer previous line
+        let padding = self.last_width.saturating_sub(line.chars().count());
+        self.last_width = line.chars().count();
+        let mut stderr = stderr();
+        // progress is best effort, a closed stderr must not fail the copy
+        let _ = write!(stderr, "\r{}{}", line, " ".repeat(padding));
+        let _ = stderr.flush();
+    }
+
+    /// Text of the progress bar after `elapsed` time
+    pub fn render(&self, elapsed: Duration) -> String {
+        let seconds = elapsed.as_secs_f64();
+        let throughput = if seconds > 0.0 {
+            self.copied as f64 / seconds
+        } else {
+            0.0
+        };
+
+        match self.total {
+            Some(total) if total > 0 => {
+                let fraction = (self.copied as f64 / total as f64).min(1.0);
+                let filled = (fraction * BAR_WIDTH as f64) as usize;
+                let eta = if throughput > 0.0 {
+                    format_duration(total.saturating_sub(self.copied) as f64 / throughput)
+                } else {
+                    "--".to_string()
+                };
+                format!(
+                    "{} [{}{}] {:>3}% {}/{} {}/s ETA {}",
+                    self.label,
+                    "#".repeat(filled),
+                    " ".repeat(BAR_WIDTH - filled),
+                    (fraction * 100.0) as u32,
+                    format_bytes(self.copied as f64),
+                    format_bytes(total as f64),
+                    format_bytes(throughput),
+                    eta
+                )
+            }
+            _ => format!(
+                "{} {} {}/s",
+                self.label,
+                format_bytes(self.copied as f64),
+                format_bytes(throughput)
+            ),
+        }
+    }
+}
+
+/// Human readable byte count, e.g. `12.5 MiB`
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value as u64, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Remaining time as `1h02m03s`, `2m03s` or `3s`
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h{:02}m{:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m{:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small pseudo random generator (SplitMix64), good enough to pick lines and cheap to seed
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seeded from the clock and the process id, so every run picks different lines
+    pub fn from_entropy() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::with_seed(nanos ^ (u64::from(std::process::id()) << 32))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// Uniform number in `0..bound`
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+
+    /// Uniform number in `[0, 1)`
+    pub fn fraction(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Uniform random sample of at most `size` items from a stream of unknown length, in one pass
+/// with memory for the sample only (reservoir sampling). The sample keeps the order of the stream.
+pub struct Reservoir<T> {
+    size: usize,
+    seen: u64,
+    items: Vec<(u64, T)>,
+    random: Random,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(size: usize, random: Random) -> Self {
+        Self {
+            size,
+            seen: 0,
+            items: Vec::with_capacity(size.min(1024)),
+            random,
+        }
+    }
+
+    /// Offers the next item of the stream, which replaces a random one of the sample with decreasing chance
+    pub fn offer(&mut self, item: T) {
+        let index = self.seen;
+        self.seen += 1;
+        if self.items.len() < self.size {
+            self.items.push((index, item));
+        } else {
+            let slot = self.random.below(self.seen);
+            if slot < self.size as u64 {
+                self.items[slot as usize] = (index, item);
+            }
+        }
+    }
+
+    /// The sampled items, in the order they came in
+    pub fn into_sample(mut self) -> Vec<T> {
+        self.items.sort_unstable_by_key(|(index, _)| *index);
+        self.items.into_iter().map(|(_, item)| item).collect()
+    }
+}
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+use crate::errors::RicatError;
+use crate::{ByteFeature, LineContext};
+
+/// Script given with `--script` or `--script-file`, compiled once when the options are parsed
+#[derive(Clone, Debug)]
+pub struct Script {
+    #[cfg(feature = "scripting")]
+    ast: rhai::AST,
+}
+
+/// Compiles the script of `--script`
+pub fn parse_script(source: &str) -> Result<Script, String> {
+    compile(source)
+}
+
+/// Reads and compiles the script file of `--script-file`
+pub fn parse_script_file(path: &str) -> Result<Script, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|error| format!("cannot read {}: {}", path, error))?;
+    compile(&source)
+}
+
+#[cfg(feature = "scripting")]
+fn compile(source: &str) -> Result<Script, String> {
+    engine()
+        .compile(source)
+        .map(|ast| Script { ast })
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(not(feature = "scripting"))]
+fn compile(_source: &str) -> Result<Script, String> {
+    Err(MISSING_FEATURE.to_string())
+}
+
+#[cfg(not(feature = "scripting"))]
+const MISSING_FEATURE: &str =
+    "ricat was built without the `scripting` feature, reinstall with `cargo install ricat --features scripting`";
+
+/// Rhai engine with the Rust names of the string functions too, e.g. `line.to_uppercase()`
+#[cfg(feature = "scripting")]
+fn engine() -> rhai::Engine {
+    let mut engine = rhai::Engine::new();
+    engine.register_fn("to_uppercase", |text: &str| text.to_uppercase());
+    engine.register_fn("to_lowercase", |text: &str| text.to_lowercase());
+    // the output is for the lines, what the script prints goes to the standard error
+    engine.on_print(|text| eprintln!("{}", text));
+    engine.on_debug(|text, _, _| eprintln!("{}", text));
+    engine
+}
+
+/// Feature: runs a script on every line. The script gets the line as `line`, its number as `line_number`
+/// and the input as `file`, and returns the new line, an array of lines, or nothing (`()`) to drop the line.
+/// `state` is an object map kept from line to line, e.g. for counting; other variables last for one line.
+pub struct ScriptFeature {
+    #[cfg(feature = "scripting")]
+    engine: rhai::Engine,
+    #[cfg(feature = "scripting")]
+    script: Script,
+    #[cfg(feature = "scripting")]
+    scope: rhai::Scope<'static>,
+}
+
+impl ScriptFeature {
+    #[cfg(feature = "scripting")]
+    pub fn new(script: Script) -> Self {
+        let mut scope = rhai::Scope::new();
+        scope.push("state", rhai::Map::new());
+        Self {
+            engine: engine(),
+            script,
+            scope,
+        }
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub fn new(_script: Script) -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl ByteFeature for ScriptFeature {
+    fn apply_record<'a>(
+        &mut self,
+        record: &'a [u8],
+        context: &LineContext,
+    ) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let kept = self.scope.len();
+        self.scope
+            .push("line", String::from_utf8_lossy(record).into_owned())
+            .push("line_number", context.line_number as rhai::INT)
+            .push("file", context.file_name.to_string());
+        let result = self
+            .engine
+            .eval_ast_with_scope::<rhai::Dynamic>(&mut self.scope, &self.script.ast);
+        self.scope.rewind(kept);
+
+        let value = result
+            .map_err(|error| RicatError::FeatureError(format!("script failed: {}", error)))?;
+        if value.is_unit() {
+            return Ok(Vec::new());
+        }
+        Ok(match value.try_cast_result::<rhai::Array>() {
+            Ok(lines) => lines
+                .into_iter()
+                .map(|line| Cow::Owned(line.to_string().into_bytes()))
+                .collect(),
+            Err(value) => vec![Cow::Owned(value.to_string().into_bytes())],
+        })
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+impl ByteFeature for ScriptFeature {
+    fn apply_record<'a>(
+        &mut self,
+        _record: &'a [u8],
+        _context: &LineContext,
+    ) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        Err(RicatError::FeatureError(MISSING_FEATURE.to_string()))
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::{self, BufRead, BufReader, Read};
+use std::net::TcpListener;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::errors::RicatError;
+
+/// Reader over the lines sent by the connection threads of a socket listener.
+/// Lines of concurrent connections are never mixed up, each one is received whole.
+pub struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl ChannelReader {
+    pub fn new(receiver: Receiver<Vec<u8>>) -> Self {
+        Self {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                // every sender is gone, no more connections can come in
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Sends every line read from a connection, a last line without newline gets one
+/// so it is not glued to the next line of another connection.
+pub fn forward_lines<R: Read>(connection: R, sender: Sender<Vec<u8>>) {
+    let mut reader = BufReader::new(connection);
+    loop {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => return,
+            Ok(_) => {
+                if !line.ends_with(b"\n") {
+                    line.push(b'\n');
+                }
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Longest pause between two attempts to accept a connection after `accept` failed
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Accepts connections in a background thread, each one is served by its own thread
+/// which forwards its lines into the returned reader. While accepting fails (e.g. out of file descriptors),
+/// the thread waits longer and longer before the next attempt instead of spinning.
+fn serve_connections<S, F>(mut accept: F) -> Box<dyn Read>
+where
+    S: Read + Send + 'static,
+    F: FnMut() -> io::Result<S> + Send + 'static,
+{
+    let (sender, receiver) = channel();
+
+    thread::spawn(move || {
+        let mut backoff = Duration::from_millis(10);
+        loop {
+            match accept() {
+                Ok(connection) => {
+                    backoff = Duration::from_millis(10);
+                    let sender = sender.clone();
+                    thread::spawn(move || forward_lines(connection, sender));
+                }
+                Err(_) => {
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_ACCEPT_BACKOFF);
+                }
+            }
+        }
+    });
+
+    Box::new(ChannelReader::new(receiver))
+}
+
+/// Parses the address of `--listen`, `HOST:PORT` or a port alone, which listens on the loopback interface only
+pub fn listen_address(value: &str) -> Result<String, String> {
+    let (host, port) = value.rsplit_once(':').unwrap_or(("127.0.0.1", value));
+    let port: u16 = port
+        .parse()
+        .map_err(|_| format!("`{}` is not a port, use PORT or HOST:PORT", port))?;
+    if host.is_empty() {
+        return Err(format!("no host before `:{}`, use PORT or HOST:PORT", port));
+    }
+    Ok(format!("{}:{}", host, port))
+}
+
+/// Listens on a TCP address, like `nc -l`, every client connecting to it streams text into the returned reader
+pub fn listen_tcp(address: &str) -> Result<Box<dyn Read>, RicatError> {
+    let listener = TcpListener::bind(address).map_err(|error| {
+        RicatError::SocketError(format!("Failed to listen on {}: {}", address, error))
+    })?;
+    Ok(serve_connections(move || {
+        listener.accept().map(|(stream, _)| stream)
+    }))
+}
+
+/// Listens on a Unix domain socket, every client connecting to it streams text into the returned reader.
+/// Clients are served concurrently, a stale socket file left by an earlier run is replaced. Anything else
+/// at the path, or a socket another process still listens on, is left alone and refused.
+#[cfg(unix)]
+pub fn listen_unix(path: &str) -> Result<Box<dyn Read>, RicatError> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    // a symbolic link is not followed, it is not a socket of an earlier run
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(RicatError::SocketError(format!(
+                "{} exists and is not a socket, refusing to replace it",
+                path
+            )));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(RicatError::SocketError(format!(
+                "{} is in use by another listener",
+                path
+            )));
+        }
+        std::fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)
+        .map_err(|error| RicatError::SocketError(format!("Failed to bind {}: {}", path, error)))?;
+    Ok(serve_connections(move || {
+        listener.accept().map(|(stream, _)| stream)
+    }))
+}
+
+/// Unix domain sockets are not available on this platform
+#[cfg(not(unix))]
+pub fn listen_unix(path: &str) -> Result<Box<dyn Read>, RicatError> {
+    Err(RicatError::SocketError(format!(
+        "{}: Unix domain sockets are not supported on this platform",
+        path
+    )))
+}
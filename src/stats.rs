@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use crate::ansi::visible_len;
+
+/// Whether `--stats` prints the counts instead of the content, or after it
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsMode {
+    /// only the counts, like `wc`
+    Only,
+    /// the content, followed by the counts
+    Summary,
+}
+
+/// `wc` style counts of the lines of one input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStats {
+    pub lines: u64,
+    pub words: u64,
+    pub bytes: u64,
+    /// visible characters of the longest line, like `wc -L`
+    pub max_line_length: usize,
+}
+
+impl TextStats {
+    /// Counts a line with the terminator it had in the input, empty for a last line without one.
+    /// Like `wc`, only lines with a terminator are lines, and bytes which are not UTF-8 count as bytes.
+    pub fn add_line(&mut self, line: &[u8], terminator: &str) {
+        if !terminator.is_empty() {
+            self.lines += 1;
+        }
+        self.words += count_words(line);
+        self.bytes += (line.len() + terminator.len()) as u64;
+        self.max_line_length = self
+            .max_line_length
+            .max(visible_len(&String::from_utf8_lossy(line)));
+    }
+
+    /// Adds the counts of another input, for the total
+    pub fn add(&mut self, other: &TextStats) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.bytes += other.bytes;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+    }
+}
+
+/// Words like `wc` counts them, runs of printable characters between whitespace.
+/// Control characters and bytes which are not UTF-8 neither start a word nor end one.
+fn count_words(line: &[u8]) -> u64 {
+    let mut words = 0;
+    let mut in_word = false;
+    for character in line.utf8_chunks().flat_map(|chunk| chunk.valid().chars()) {
+        if character.is_whitespace() {
+            in_word = false;
+        } else if !character.is_control() && !in_word {
+            in_word = true;
+            words += 1;
+        }
+    }
+    words
+}
+
+/// Report with a header and one row per input, plus a total row for several inputs.
+/// The columns are right aligned to the widest count.
+pub fn stats_report(inputs: &[(String, TextStats)]) -> Vec<String> {
+    let mut rows = inputs.to_vec();
+    if inputs.len() > 1 {
+        let mut total = TextStats::default();
+        inputs.iter().for_each(|(_, stats)| total.add(stats));
+        rows.push(("total".to_string(), total));
+    }
+
+    let header = ["lines", "words", "bytes", "max-line"];
+    let width = rows
+        .iter()
+        .flat_map(|(_, stats)| {
+            [
+                stats.lines.to_string().len(),
+                stats.words.to_string().len(),
+                stats.bytes.to_string().len(),
+                stats.max_line_length.to_string().len(),
+            ]
+        })
+        .chain(header.iter().map(|name| name.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut report = vec![format!(
+        "{:>width$} {:>width$} {:>width$} {:>width$}",
+        header[0],
+        header[1],
+        header[2],
+        header[3],
+        width = width
+    )];
+    report.extend(rows.iter().map(|(name, stats)| {
+        format!(
+            "{:>width$} {:>width$} {:>width$} {:>width$} {}",
+            stats.lines,
+            stats.words,
+            stats.bytes,
+            stats.max_line_length,
+            name,
+            width = width
+        )
+    }));
+    report
+}
+
+/// Width of the longest bar of the histogram, in characters
+const BAR_WIDTH: usize = 40;
+
+/// Number of lines per range of line lengths, for `--length-histogram`
+pub struct LengthHistogram {
+    bucket_width: usize,
+    buckets: BTreeMap<usize, u64>,
+}
+
+impl LengthHistogram {
+    pub fn new(bucket_width: usize) -> Self {
+        Self {
+            bucket_width: bucket_width.max(1),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Counts the line into the bucket of its visible length
+    pub fn add_line(&mut self, line: &str) {
+        *self
+            .buckets
+            .entry(visible_len(line) / self.bucket_width)
+            .or_default() += 1;
+    }
+
+    /// One row per bucket from the shortest to the longest line, empty buckets in between included,
+    /// with the range of lengths, the number of lines and a bar scaled to the fullest bucket
+    pub fn render(&self) -> Vec<String> {
+        let (Some((&first, _)), Some((&last, _))) = (
+            self.buckets.first_key_value(),
+            self.buckets.last_key_value(),
+        ) else {
+            return Vec::new();
+        };
+        let most = self.buckets.values().copied().max().unwrap_or(0);
+        let range = |bucket: usize| {
+            format!(
+                "{}-{}",
+                bucket * self.bucket_width,
+                (bucket + 1) * self.bucket_width - 1
+            )
+        };
+        let range_width = range(last).len();
+        let count_width = most.to_string().len();
+
+        (first..=last)
+            .map(|bucket| {
+                let count = self.buckets.get(&bucket).copied().unwrap_or(0);
+                // every bucket holding lines gets at least a sliver of a bar, so outliers stay visible
+                let bar = (count as usize * BAR_WIDTH).div_ceil(most as usize);
+                format!(
+                    "{:>range_width$}  {:>count_width$}  {}",
+                    range(bucket),
+                    count,
+                    "#".repeat(bar),
+                    range_width = range_width,
+                    count_width = count_width
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,78 @@
+use serde::Deserialize;
+
+/// Structured format checked and re-emitted with `--yaml` / `--toml`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredFormat {
+    Yaml,
+    Toml,
+}
+
+/// Parse error of a structured document, with its position when the parser knows it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line and column, both starting at 1
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Error message for the input `name`, e.g. `config.toml:3:7: invalid string`
+    pub fn report(&self, name: &str) -> String {
+        match self.position {
+            Some((line, column)) => format!("{}:{}:{}: {}", name, line, column, self.message),
+            None => format!("{}: {}", name, self.message),
+        }
+    }
+}
+
+/// Parses the document and re-emits it well-formatted, keys stay in their original order.
+/// Comments are not part of the parsed data and are dropped.
+pub fn pretty_print(format: StructuredFormat, text: &str) -> Result<String, ParseError> {
+    match format {
+        StructuredFormat::Yaml => pretty_print_yaml(text),
+        StructuredFormat::Toml => pretty_print_toml(text),
+    }
+}
+
+/// YAML streams may hold several documents, they are kept apart by `---`
+fn pretty_print_yaml(text: &str) -> Result<String, ParseError> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(text) {
+        let value = serde_yaml::Value::deserialize(document).map_err(yaml_error)?;
+        documents.push(serde_yaml::to_string(&value).map_err(yaml_error)?);
+    }
+    Ok(documents.join("---\n"))
+}
+
+fn yaml_error(error: serde_yaml::Error) -> ParseError {
+    let position = error
+        .location()
+        .map(|location| (location.line(), location.column()));
+    let mut message = error.to_string();
+    if let Some((line, column)) = position {
+        // the position is reported in front of the message, it is not repeated inside it
+        message = message.replacen(&format!(" at line {} column {}", line, column), "", 1);
+    }
+    ParseError { position, message }
+}
+
+fn pretty_print_toml(text: &str) -> Result<String, ParseError> {
+    let value: toml::Table = toml::from_str(text).map_err(|error| ParseError {
+        position: error.span().map(|span| line_column(text, span.start)),
+        message: error.message().trim().replace('\n', ", "),
+    })?;
+    toml::to_string_pretty(&value).map_err(|error| ParseError {
+        position: None,
+        message: error.to_string(),
+    })
+}
+
+/// Line and column of a byte offset, both starting at 1
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = text.get(..offset).unwrap_or(text);
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
@@ -0,0 +1,97 @@
+use crate::theme::Style;
+
+/// Splits delimiter-separated text into rows of fields, CSV style:
+/// fields may be quoted with `"` to hold the delimiter or line breaks, `""` inside quotes is a literal quote.
+pub fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut characters = text.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if in_quotes => {
+                if characters.peek() == Some(&'"') {
+                    characters.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            character if in_quotes => field.push(character),
+            character if character == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if characters.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            character => field.push(character),
+        }
+    }
+
+    // last line without a line break
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Lines of the rows rendered as aligned columns, separated by two spaces.
+/// With `header` the first row is set off from the data by a rule, and drawn in the `heading` style when given.
+pub fn render_table(rows: &[Vec<String>], header: bool, heading: Option<&Style>) -> Vec<String> {
+    // line breaks inside quoted fields would tear the table apart
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| field.replace("\r\n", " ").replace('\n', " "))
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = Vec::new();
+    for row in &rows {
+        for (column, field) in row.iter().enumerate() {
+            let width = field.chars().count();
+            match widths.get_mut(column) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+
+    let table_width = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for (index, row) in rows.iter().enumerate() {
+        let line = render_row(row, &widths);
+        if header && index == 0 {
+            match heading {
+                Some(style) => lines.push(style.paint(&line)),
+                None => lines.push(line),
+            }
+            lines.push("-".repeat(table_width));
+        } else {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn render_row(row: &[String], widths: &[usize]) -> String {
+    let mut line = String::new();
+    for (column, field) in row.iter().enumerate() {
+        if column > 0 {
+            line.push_str("  ");
+        }
+        line.push_str(field);
+        // the last field is not padded, lines carry no trailing spaces
+        if column + 1 < row.len() {
+            let padding = widths[column] - field.chars().count();
+            line.push_str(&" ".repeat(padding));
+        }
+    }
+    line
+}
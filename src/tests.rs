@@ -1,8 +1,15 @@
 //! Unit Tests for ricat : `cargo test` to run all the tests at once
 //! for indiviual tests : `cargo test test-name` will run all those tests, which contain test-name
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
+    use std::borrow::Cow;
+
     use crate::*;
+    use crate::archive::split_member_argument;
+    use crate::binary_file::looks_binary;
+    use crate::output::{open_output, open_output_file, TeeWriter};
+    use crate::output_template::OutputTemplate;
 
     /// Tests the basic functionality of the `LineNumbering` feature.
     /// Ensures that the line number is added correctly to the line.
@@ -10,7 +17,7 @@ mod tests {
     fn line_numbering_basic() {
         let mut feature = LineNumbering::new();
         let result = feature.apply_feature("Test line");
-        assert_eq!(result, Some("1 Test line".to_string()));
+        assert_eq!(result, Some("1 Test line".into()));
     }
 
     /// Tests the incrementing behavior of the `LineNumbering` feature.
@@ -20,7 +27,7 @@ mod tests {
         let mut feature = LineNumbering::new();
         feature.apply_feature("First line");
         let result = feature.apply_feature("Second line");
-        assert_eq!(result, Some("2 Second line".to_string()));
+        assert_eq!(result, Some("2 Second line".into()));
     }
 
     /// Tests the basic functionality of the `DollarSymbolAtLast` feature.
@@ -29,7 +36,7 @@ mod tests {
     fn dollar_symbol_at_last_basic() {
         let mut feature = DollarSymbolAtLast::new();
         let result = feature.apply_feature("Test line");
-        assert_eq!(result, Some("Test line$".to_string()));
+        assert_eq!(result, Some("Test line$".into()));
     }
 
     /// Tests the basic functionality of the `ReplaceTabspaces` feature.
@@ -38,7 +45,7 @@ mod tests {
     fn replace_tabspaces_basic() {
         let mut feature = ReplaceTabspaces::new();
         let result = feature.apply_feature("Test\tline");
-        assert_eq!(result, Some("Test^Iline".to_string()));
+        assert_eq!(result, Some("Test^Iline".into()));
     }
 
     /// Tests the `ReplaceTabspaces` feature when no tab spaces are present.
@@ -47,7 +54,7 @@ mod tests {
     fn replace_tabspaces_no_tabs() {
         let mut feature = ReplaceTabspaces::new();
         let result = feature.apply_feature("Test line");
-        assert_eq!(result, Some("Test line".to_string()));
+        assert_eq!(result, Some("Test line".into()));
     }
 
     /// Tests the `CompressEmptyLines` feature with multiple empty lines.
@@ -67,7 +74,20 @@ mod tests {
     fn compress_empty_lines_single() {
         let mut feature = CompressEmptyLines::new();
         let result = feature.apply_feature("");
-        assert_eq!(result, Some("".to_string()));
+        assert_eq!(result, Some("".into()));
+    }
+
+    /// Tests the `CompressEmptyLines` feature keeping up to `max_blank` empty lines in a row.
+    /// Verifies that the count starts over after a line with text, and that 0 drops every empty line.
+    #[test]
+    fn compress_empty_lines_max_blank() {
+        let mut feature = CompressEmptyLines::with_max_blank(2);
+        let kept: Vec<bool> = ["", "", "", "a", "", ""].iter().map(|line| feature.apply_feature(line).is_some()).collect();
+        assert_eq!(kept, [true, true, false, true, true, true]);
+
+        let mut feature = CompressEmptyLines::with_max_blank(0);
+        assert!(feature.apply_feature(" ").is_none());
+        assert_eq!(feature.apply_feature("a"), Some("a".into()));
     }
 
     /// Tests the `LineWithGivenText` feature when the search text is found.
@@ -77,7 +97,7 @@ mod tests {
         let mut feature = LineWithGivenText::new("aditya", false);
         assert_eq!(
             feature.apply_feature("This is a line with aditya in it."),
-            Some("This is a line with aditya in it.".to_string())
+            Some("This is a line with aditya in it.".into())
         );
     }
 
@@ -98,7 +118,7 @@ mod tests {
         let mut feature = LineWithGivenText::new("reg:\\d", false);
         assert_eq!(
             feature.apply_feature("This line has a 1 digit."),
-            Some("This line has a 1 digit.".to_string())
+            Some("This line has a 1 digit.".into())
         );
     }
 
@@ -117,7 +137,7 @@ mod tests {
         let mut feature = LineWithGivenText::new("aditya", false);
         assert_eq!(
             feature.apply_feature("Exact match aditya"),
-            Some("Exact match aditya".to_string())
+            Some("Exact match aditya".into())
         );
     }
 
@@ -128,7 +148,7 @@ mod tests {
         let mut feature = LineWithGivenText::new("reg:\\[aditya\\]", false);
         assert_eq!(
             feature.apply_feature("Line with [aditya]"),
-            Some("Line with [aditya]".to_string())
+            Some("Line with [aditya]".into())
         );
     }
 
@@ -156,7 +176,7 @@ mod tests {
     fn feature_application_on_empty_input() {
         let mut feature = DollarSymbolAtLast::new();
         let result = feature.apply_feature("");
-        assert_eq!(result, Some("$".to_string()));
+        assert_eq!(result, Some("$".into()));
     }
 
     /// Tests the resetting behavior of the `LineNumbering` feature.
@@ -170,7 +190,7 @@ mod tests {
         // Simulate processing a new input source by creating a new instance
         let mut feature_new = LineNumbering::new();
         let result = feature_new.apply_feature("New first line");
-        assert_eq!(result, Some("1 New first line".to_string()));
+        assert_eq!(result, Some("1 New first line".into()));
     }
 
     /// Tests the `LineWithGivenText` feature with a regex pattern.
@@ -181,7 +201,7 @@ mod tests {
         let line_with_number = feature.apply_feature("This is line 42");
         let line_without_number = feature.apply_feature("This line has no numbers");
 
-        assert_eq!(line_with_number, Some("This is line 42".to_string()));
+        assert_eq!(line_with_number, Some("This is line 42".into()));
         assert!(line_without_number.is_none());
     }
 
@@ -229,7 +249,7 @@ mod tests {
         let mut feature = LineWithGivenText::new("aditya", true);
         assert_eq!(
             feature.apply_feature("This line contains ADITYA."),
-            Some("This line contains ADITYA.".to_string())
+            Some("This line contains ADITYA.".into())
         );
     }
 
@@ -241,7 +261,7 @@ mod tests {
         let mut feature = LineWithGivenText::new("OpenSource", true);
         assert_eq!(
             feature.apply_feature("I love opensource projects."),
-            Some("I love opensource projects.".to_string())
+            Some("I love opensource projects.".into())
         );
     }
 
@@ -255,4 +275,2089 @@ mod tests {
             .apply_feature("I enjoy programming in rust.")
             .is_none());
     }
+
+    /// Tests the pagination decision for each `PagesMode`.
+    /// `auto` mode must not paginate output which fits the screen.
+    #[test]
+    fn pagination_mode_decision() {
+        assert!(!should_paginate(None, 1000));
+        assert!(!should_paginate(Some(PagesMode::Never), 1000));
+        assert!(should_paginate(Some(PagesMode::Always), 1));
+        assert!(!should_paginate(Some(PagesMode::Auto), 1));
+    }
+
+    /// Tests the row accounting of soft-wrapped lines in the pager.
+    /// A line wider than the terminal occupies multiple rows of the page.
+    #[test]
+    fn pager_wrapped_rows() {
+        assert_eq!(wrapped_rows("", 80), 1);
+        assert_eq!(wrapped_rows("short", 80), 1);
+        assert_eq!(wrapped_rows(&"x".repeat(81), 80), 2);
+        assert_eq!(wrapped_rows(&"x".repeat(160), 80), 2);
+    }
+
+    /// Tests the visible part of a horizontally scrolled line in the pager.
+    #[test]
+    fn pager_visible_slice() {
+        assert_eq!(visible_slice("abcdefgh", 0, 4), "abcd");
+        assert_eq!(visible_slice("abcdefgh", 6, 4), "gh");
+        assert_eq!(visible_slice("abc", 10, 4), "");
+    }
+
+    /// Tests that invalid UTF-8 input does not abort line processing.
+    /// Invalid sequences are replaced and CRLF/LF line endings are both handled.
+    #[test]
+    fn non_utf8_input_is_processed_lossily() {
+        let input: &[u8] = b"valid line\r\ninvalid \xff\xfe bytes\nlast";
+        let lines = process_input_ret(input, &mut []).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                "valid line".to_string(),
+                "invalid \u{FFFD}\u{FFFD} bytes".to_string(),
+                "last".to_string()
+            ]
+        );
+    }
+
+    /// Tests that `keep_line_endings` preserves CRLF terminators and a missing final newline.
+    #[test]
+    fn keep_line_endings_round_trip() {
+        let input: &[u8] = b"first\r\nsecond\nlast";
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(DollarSymbolAtLast::new())];
+        let options = OutputOptions {
+            keep_line_endings: true,
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        process_input_to(input, "-", &mut output, &mut features, options).unwrap();
+        assert_eq!(output, b"first$\r\nsecond$\nlast$");
+
+        let mut output = Vec::new();
+        process_input_to(input, "-", &mut output, &mut features, OutputOptions::default()).unwrap();
+        assert_eq!(output, b"first$\nsecond$\nlast$\n");
+    }
+
+    /// Tests the CRLF/LF conversions of line endings, a missing final newline is not added.
+    #[test]
+    fn line_ending_conversion() {
+        let input: &[u8] = b"dos\r\nunix\nlast";
+
+        let mut output = Vec::new();
+        let options = OutputOptions {
+            ending_conversion: Some(EndingConversion::CrLfToLf),
+            ..Default::default()
+        };
+        process_input_to(input, "-", &mut output, &mut [], options).unwrap();
+        assert_eq!(output, b"dos\nunix\nlast");
+
+        let mut output = Vec::new();
+        let options = OutputOptions {
+            ending_conversion: Some(EndingConversion::LfToCrLf),
+            ..Default::default()
+        };
+        process_input_to(input, "-", &mut output, &mut [], options).unwrap();
+        assert_eq!(output, b"dos\r\nunix\r\nlast");
+    }
+
+    /// Tests that `copy_mmap` falls back to the buffered copy for files which cannot be memory mapped.
+    /// `/proc` files report a length of zero even though they have content.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn copy_mmap_fallback_for_proc_files() {
+        let mut output = Vec::new();
+        copy_mmap("/proc/self/status", &mut output).unwrap();
+        assert!(String::from_utf8_lossy(&output).contains("Name:"));
+    }
+
+    /// Tests that a file larger than one mapping window is copied whole, the windows joined in order.
+    #[test]
+    fn copy_mmap_joins_the_windows() {
+        let path = std::env::temp_dir().join(format!("ricat_mmap_windows_{}.bin", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"head").unwrap();
+        // sparse up to just past the first window, then a few bytes in the second one
+        file.set_len(MMAP_WINDOW_SIZE - 2).unwrap();
+        std::io::Seek::seek(&mut file, std::io::SeekFrom::End(0)).unwrap();
+        file.write_all(b"tail").unwrap();
+
+        let mut output = Vec::new();
+        copy_mmap(path.to_str().unwrap(), &mut output).unwrap();
+        assert_eq!(output.len() as u64, MMAP_WINDOW_SIZE + 2);
+        assert!(output.starts_with(b"head") && output.ends_with(b"tail"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that `--io-backend auto` reads small files and maps large ones, and that special files are never mapped.
+    #[cfg(unix)]
+    #[test]
+    fn io_backend_resolves_by_size_and_type() {
+        use crate::io_backend::IoBackend;
+
+        let path = std::env::temp_dir().join(format!("ricat_io_backend_{}.txt", std::process::id()));
+        std::fs::write(&path, "small file\n").unwrap();
+        let file = std::fs::metadata(&path).unwrap();
+        let device = std::fs::metadata("/dev/null").unwrap();
+
+        assert_eq!(IoBackend::Auto.resolve(&file, 1024), IoBackend::Read);
+        assert_eq!(IoBackend::Auto.resolve(&file, 4), IoBackend::Mmap);
+        assert_eq!(IoBackend::Read.resolve(&file, 4), IoBackend::Read);
+        assert_eq!(IoBackend::Mmap.resolve(&device, 4), IoBackend::Read);
+        assert_eq!(IoBackend::Uring.resolve(&device, 4), IoBackend::Read);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that a file appended to itself (`ricat a >> a`) is copied once and stops, and that it is refused
+    /// as an input when it is the output file.
+    #[cfg(unix)]
+    #[test]
+    fn input_file_appended_to_itself_stops() {
+        use crate::output::{output_identity, OutputTarget};
+
+        let path = std::env::temp_dir().join(format!("ricat_self_append_{}.txt", std::process::id()));
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let name = path.to_str().unwrap();
+        let mut arguments = Cli::try_parse_from(["ricat", "--io-backend", "read", name]).unwrap();
+        let mut output = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        copy_file(&arguments, name, None, &mut output, None).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\none\ntwo\n");
+
+        arguments.output_identity = output_identity(&OutputTarget::File(name.to_string()));
+        assert!(arguments.output_identity.is_some());
+        let sources: Vec<_> = input_sources(&arguments).unwrap().collect();
+        assert!(matches!(&sources[..], [Err(RicatError::InputIsOutputError(input))] if input == name));
+        assert_eq!(
+            RicatError::InputIsOutputError(name.to_string()).to_string(),
+            format!("{}: input file is output file", name)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that `-o` naming one of the inputs is refused before the file is truncated
+    #[cfg(unix)]
+    #[test]
+    fn output_file_among_inputs_is_not_truncated() {
+        use crate::output::{output_identity, OutputTarget};
+
+        let path = std::env::temp_dir().join(format!("ricat_output_input_{}.txt", std::process::id()));
+        std::fs::write(&path, "kept\n").unwrap();
+        let name = path.to_str().unwrap();
+        let mut arguments = Cli::try_parse_from(["ricat", "-n", name, "-o", name]).unwrap();
+        let target = OutputTarget::File(name.to_string());
+        arguments.output_identity = output_identity(&target);
+        let error = refuse_output_among_inputs(&arguments, &target).unwrap_err();
+        assert!(matches!(error, RicatError::InputIsOutputError(input) if input == name));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "kept\n");
+
+        let mut other = Cli::try_parse_from(["ricat", "-n", "Cargo.toml", "-o", name]).unwrap();
+        other.output_identity = output_identity(&target);
+        assert!(refuse_output_among_inputs(&other, &target).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that `ricat -n bench FILE` parses as the subcommand and times every strategy, the given features too.
+    #[test]
+    fn bench_times_every_strategy() {
+        let path = std::env::temp_dir().join(format!("ricat_bench_{}.txt", std::process::id()));
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let path = path.to_str().unwrap();
+        let arguments = Cli::try_parse_from(["ricat", "-n", "bench", "--runs", "1", path]).unwrap();
+        assert_eq!(
+            arguments.command,
+            Some(Command::Bench { file: path.to_string(), runs: 1 })
+        );
+
+        let mut output = Vec::new();
+        crate::bench::run_bench(&arguments, path, 1, &mut output).unwrap();
+        let table = String::from_utf8(output).unwrap();
+        for strategy in ["buffered read", "mmap", "kernel copy", "lines, no feature", "lines, given features"] {
+            assert!(table.contains(strategy), "{} missing from\n{}", strategy, table);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that the memory budget of `--max-memory` stops reading as soon as the input is larger than the limit.
+    #[test]
+    fn max_memory_stops_reading_past_the_limit() {
+        use crate::memory_budget::MemoryBudget;
+
+        let mut budget = MemoryBudget::new(Some(10));
+        let mut buffer = Vec::new();
+        let result = budget.read_to_end(std::io::Cursor::new(vec![b'x'; 1000]), &mut buffer);
+        assert!(matches!(result, Err(RicatError::MemoryLimitError(_))));
+        assert_eq!(buffer.len(), 11);
+
+        let mut budget = MemoryBudget::new(Some(10));
+        let mut text = String::new();
+        budget.read_to_string(&b"0123456789"[..], &mut text).unwrap();
+        assert!(budget.hold(1).is_err());
+    }
+
+    /// Tests the sizes `--max-memory` accepts, plain bytes or with a K, M or G suffix.
+    #[test]
+    fn max_memory_sizes() {
+        use crate::memory_budget::parse_byte_size;
+
+        assert_eq!(parse_byte_size("4096"), Ok(4096));
+        assert_eq!(parse_byte_size("64M"), Ok(64 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1g"), Ok(1024 * 1024 * 1024));
+        assert!(parse_byte_size("0").is_err());
+        assert!(parse_byte_size("12X").is_err());
+        assert!(parse_byte_size("M").is_err());
+    }
+
+    /// Tests that a missing file is skipped and counted like GNU cat does, the files after it still written,
+    /// also by the modes reading the inputs on their own, and that `--fail-fast` stops at it.
+    #[test]
+    fn missing_file_is_skipped_unless_fail_fast() {
+        let path = std::env::temp_dir().join(format!("ricat_after_missing_{}.txt", std::process::id()));
+        std::fs::write(&path, "still here\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let arguments = Cli::try_parse_from(["ricat", "--dollar", "/nonexistent/ricat", path]).unwrap();
+        let mut features = add_features_from_args(&arguments).unwrap();
+        let mut output = Vec::new();
+        handle_files_or_features(&arguments, &mut features, &mut output).unwrap();
+        assert_eq!(output, b"still here$\n");
+        assert_eq!(arguments.failed_inputs.load(Ordering::Relaxed), 1);
+
+        let arguments = Cli::try_parse_from(["ricat", "--sha256", "/nonexistent/ricat", path]).unwrap();
+        let mut output = Vec::new();
+        handle_checksums(&checksum_algorithms(&arguments), &arguments, &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with(&format!("  {}\n", path)));
+        assert_eq!(arguments.failed_inputs.load(Ordering::Relaxed), 1);
+
+        let arguments = Cli::try_parse_from(["ricat", "--stats=summary", path, "/nonexistent/ricat"]).unwrap();
+        let mut output = Vec::new();
+        handle_stats(StatsMode::Summary, &arguments, &mut [], &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().starts_with("still here\n"));
+        assert_eq!(arguments.failed_inputs.load(Ordering::Relaxed), 1);
+
+        let arguments = Cli::try_parse_from(["ricat", "--fail-fast", "/nonexistent/ricat", path]).unwrap();
+        let mut output = Vec::new();
+        let result = handle_files_without_features(&arguments, None, &mut output);
+        assert!(matches!(result, Err(RicatError::FileOpenError(_))));
+        assert!(output.is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that `-` among the file arguments stands for the standard input.
+    #[test]
+    fn dash_argument_is_stdin() {
+        let sources = sources_from_args(&["header.txt".to_string(), "-".to_string()], false).unwrap();
+        assert_eq!(
+            sources,
+            vec![InputSource::File("header.txt".to_string()), InputSource::Stdin]
+        );
+        assert_eq!(sources[1].name(), "-");
+    }
+
+    /// Tests the `--show-filenames` banners, separated by a blank line after the first file.
+    #[test]
+    fn file_banner_lines() {
+        let source = InputSource::File("a.txt".to_string());
+        assert_eq!(banner_lines(&source, 0), vec!["==> a.txt <==".to_string()]);
+        assert_eq!(
+            banner_lines(&InputSource::Stdin, 1),
+            vec!["".to_string(), "==> standard input <==".to_string()]
+        );
+    }
+
+    /// Tests the recursive directory walk, which skips links to directories, and the glob expansion of file arguments.
+    #[test]
+    fn recursive_and_glob_sources() {
+        let root = std::env::temp_dir().join(format!("ricat_recursive_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("b.rs"), "b").unwrap();
+        std::fs::write(root.join("nested").join("a.rs"), "a").unwrap();
+        std::fs::write(root.join("nested").join("c.txt"), "c").unwrap();
+
+        let root_arg = root.to_string_lossy().into_owned();
+        let names = |sources: Vec<InputSource>| {
+            sources
+                .iter()
+                .map(|source| source.name().replace(&root_arg, ""))
+                .collect::<Vec<_>>()
+        };
+
+        let walked = sources_from_args(std::slice::from_ref(&root_arg), true).unwrap();
+        assert_eq!(names(walked), vec!["/b.rs", "/nested/a.rs", "/nested/c.txt"]);
+
+        let globbed = sources_from_args(&[format!("{}/**/*.rs", root_arg)], true).unwrap();
+        assert_eq!(names(globbed), vec!["/b.rs", "/nested/a.rs"]);
+
+        assert!(sources_from_args(&[format!("{}/*.none", root_arg)], false).is_err());
+
+        // a link back to a parent directory is not walked
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("..", root.join("nested").join("loop")).unwrap();
+            let walked = sources_from_args(std::slice::from_ref(&root_arg), true).unwrap();
+            assert_eq!(names(walked), vec!["/b.rs", "/nested/a.rs", "/nested/c.txt"]);
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Tests reading a NUL-separated file list, as produced by `find -print0`.
+    /// Empty entries are skipped and names are taken literally.
+    #[test]
+    fn files_from0_list_entries() {
+        let list_path = std::env::temp_dir().join(format!("ricat_list_{}", std::process::id()));
+        std::fs::write(&list_path, b"a b.txt\0\0-\0last.txt").unwrap();
+
+        let sources = file_list(&list_path.to_string_lossy(), b'\0')
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                InputSource::File("a b.txt".to_string()),
+                InputSource::File("-".to_string()),
+                InputSource::File("last.txt".to_string()),
+            ]
+        );
+        std::fs::remove_file(list_path).unwrap();
+    }
+
+    /// Tests that `http://` and `https://` arguments are read as URLs, even when they look like glob patterns.
+    #[test]
+    fn url_arguments_are_url_sources() {
+        let sources = sources_from_args(&["https://example.com/data.txt?page=1".to_string()], false).unwrap();
+        assert_eq!(
+            sources,
+            vec![InputSource::Url("https://example.com/data.txt?page=1".to_string())]
+        );
+        assert_eq!(InputSource::from_arg("http.txt"), InputSource::File("http.txt".to_string()));
+    }
+
+    /// Tests the detection of compressed input from its magic bytes.
+    #[test]
+    fn compression_format_detection() {
+        assert_eq!(CompressionFormat::detect(&[0x1f, 0x8b, 0x08]), Some(CompressionFormat::Gzip));
+        assert_eq!(CompressionFormat::detect(&[0x28, 0xb5, 0x2f, 0xfd]), Some(CompressionFormat::Zstd));
+        assert_eq!(CompressionFormat::detect(b"BZh91AY&SY"), Some(CompressionFormat::Bzip2));
+        assert_eq!(CompressionFormat::detect(b"BZh9\x17\x72\x45\x38\x50\x90"), Some(CompressionFormat::Bzip2));
+        assert_eq!(CompressionFormat::detect(b"BZh hello"), None);
+        assert_eq!(CompressionFormat::detect(b"BZh91 and more"), None);
+        assert_eq!(CompressionFormat::detect(b"plain text"), None);
+    }
+
+    /// Tests that text which only starts like gzip data is read as it is, instead of failing to decompress.
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn text_starting_like_compressed_data_stays_plain() {
+        let text = b"\x1f\x8bnot gzip at all\n".to_vec();
+        let mut reader = decompress(Box::new(std::io::Cursor::new(text.clone())), "test").unwrap();
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, text);
+    }
+
+    /// Tests that gzip compressed input is decompressed before the features are applied.
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_input_is_decompressed() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"compressed line\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let reader = decompress(Box::new(std::io::Cursor::new(compressed)), "test.gz").unwrap();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineNumbering::new())];
+        let lines = process_input_ret(reader, &mut features).unwrap();
+        assert_eq!(lines, vec!["1 compressed line".to_string()]);
+    }
+
+    /// Tests the parsing of `archive::member` arguments, the archive has to be an existing file.
+    #[test]
+    fn archive_member_arguments() {
+        assert_eq!(
+            split_member_argument("logs.tar.gz::app/today.log"),
+            Some(("logs.tar.gz", "app/today.log"))
+        );
+        assert_eq!(split_member_argument("logs.tar.gz::"), None);
+        assert_eq!(
+            InputSource::from_arg("missing.zip::a.txt"),
+            InputSource::File("missing.zip::a.txt".to_string())
+        );
+        assert_eq!(
+            InputSource::from_arg("Cargo.toml::inner.txt"),
+            InputSource::ArchiveMember {
+                archive: "Cargo.toml".to_string(),
+                member: "inner.txt".to_string()
+            }
+        );
+    }
+
+    /// Tests that members of tar and zip archives are streamed with their own data only, stored or deflated,
+    /// and that a zip member not matching its checksum is reported.
+    #[cfg(feature = "archive")]
+    #[test]
+    fn archive_members_are_streamed() {
+        use crate::archive::open_member;
+        use std::io::Read;
+
+        let read = |archive: &std::path::Path, member: &str| {
+            let mut text = String::new();
+            open_member(archive.to_str().unwrap(), member).and_then(|mut reader| Ok(reader.read_to_string(&mut text).map(|_| text)?))
+        };
+
+        let tar_path = std::env::temp_dir().join(format!("ricat_member_{}.tar", std::process::id()));
+        let mut builder = tar::Builder::new(std::fs::File::create(&tar_path).unwrap());
+        for (name, content) in [("first.txt", "one\n"), ("dir/second.txt", "two\n")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content.as_bytes()).unwrap();
+        }
+        builder.finish().unwrap();
+        drop(builder);
+        assert_eq!(read(&tar_path, "first.txt").unwrap(), "one\n");
+        assert_eq!(read(&tar_path, "./dir/second.txt").unwrap(), "two\n");
+        assert!(matches!(read(&tar_path, "third.txt"), Err(RicatError::ArchiveError(_))));
+
+        let zip_path = std::env::temp_dir().join(format!("ricat_member_{}.zip", std::process::id()));
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+        for (name, method) in [("stored.txt", zip::CompressionMethod::Stored), ("deflated.txt", zip::CompressionMethod::Deflated)] {
+            writer.start_file(name, zip::write::SimpleFileOptions::default().compression_method(method)).unwrap();
+            writer.write_all(name.repeat(100).as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(read(&zip_path, "stored.txt").unwrap(), "stored.txt".repeat(100));
+        assert_eq!(read(&zip_path, "deflated.txt").unwrap(), "deflated.txt".repeat(100));
+
+        // the stored data changed in place no longer matches its checksum, the name in the local header comes right before it
+        let mut bytes = std::fs::read(&zip_path).unwrap();
+        let name = bytes.windows(20).position(|window| window == b"stored.txtstored.txt".as_slice()).unwrap();
+        bytes[name + 20] = b'S';
+        std::fs::write(&zip_path, bytes).unwrap();
+        assert!(read(&zip_path, "stored.txt").is_err());
+
+        std::fs::remove_file(tar_path).unwrap();
+        std::fs::remove_file(zip_path).unwrap();
+    }
+
+    /// Tests the detection of binary content from NUL bytes in the first block.
+    #[test]
+    fn binary_content_detection() {
+        assert!(looks_binary(b"ELF\0\x01\x02"));
+        assert!(!looks_binary("plain text with ünïcode\n".as_bytes()));
+
+        let (is_binary, mut reader) = sniff(Box::new(&b"text\0more"[..])).unwrap();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert!(is_binary);
+        assert_eq!(content, b"text\0more");
+    }
+
+    /// Tests the hex view of binary content.
+    #[test]
+    fn binary_hex_view() {
+        let mut output = Vec::new();
+        hex_dump(&b"Hi\0\xff"[..], &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("00000000  48 69 00 ff{}  |Hi..|\n", " ".repeat(36))
+        );
+    }
+
+    /// Tests that the tee writer duplicates the output into both writers.
+    #[test]
+    fn tee_writer_duplicates_output() {
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        {
+            let mut tee = TeeWriter::new(&mut first, &mut second);
+            let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineNumbering::new())];
+            process_input_to(&b"a\nb\n"[..], "-", &mut tee, &mut features, OutputOptions::default()).unwrap();
+        }
+        assert_eq!(first, b"1 a\n2 b\n");
+        assert_eq!(first, second);
+    }
+
+    /// Tests that `--append` adds to an existing output file instead of truncating it, and that it can be synced.
+    #[test]
+    fn output_file_append_keeps_existing_content() {
+        let path = std::env::temp_dir().join(format!("ricat_append_{}.log", std::process::id()));
+        let path = path.to_str().unwrap();
+        let options = OutputFileOptions {
+            append: true,
+            fsync: true,
+            ..Default::default()
+        };
+        std::fs::write(path, "old\n").unwrap();
+        let mut file = open_output_file(path, options).unwrap();
+        file.write_all(b"new\n").unwrap();
+        file.flush().unwrap();
+        // `--fsync` syncs once at the end, not on every flush
+        file.output_sync().unwrap().unwrap().sync().unwrap();
+        assert!(open_output_file(path, OutputFileOptions { append: true, ..Default::default() }).unwrap().output_sync().unwrap().is_none());
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "old\nnew\n");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that a file copied by the kernel lands in the output file between what is written before and after it.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zero_copy_keeps_the_output_position() {
+        let source_path = std::env::temp_dir().join(format!("ricat_zero_copy_in_{}.txt", std::process::id()));
+        let path = std::env::temp_dir().join(format!("ricat_zero_copy_out_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(&source_path, "copied by the kernel\n").unwrap();
+        let mut file = open_output_file(path, OutputFileOptions::default()).unwrap();
+        let direct_output = file.direct_output().unwrap();
+
+        file.write_all(b"before\n").unwrap();
+        file.flush().unwrap();
+        let source = std::fs::File::open(&source_path).unwrap();
+        assert!(crate::zero_copy::copy_file_direct(&source, &direct_output, None).unwrap());
+        file.write_all(b"after\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "before\ncopied by the kernel\nafter\n");
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(source_path).unwrap();
+    }
+
+    /// Tests that the io_uring reader returns the chunks of the file in order, with several reads in flight.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    #[test]
+    fn uring_reader_keeps_the_order() {
+        let path = std::env::temp_dir().join(format!("ricat_uring_{}.txt", std::process::id()));
+        let content: String = (0..1000).map(|number| format!("line {}\n", number)).collect();
+        std::fs::write(&path, &content).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        // the kernel may refuse io_uring, e.g. in containers, ricat then reads the file as usual
+        if let Ok(mut reader) = crate::io_backend::UringReader::new(file, 100) {
+            let mut read = String::new();
+            reader.read_to_string(&mut read).unwrap();
+            assert_eq!(read, content);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that `--compress gzip` output decompresses back to the processed text.
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compressed_output_roundtrip_gzip() {
+        let mut writer = CompressedWriter::new(Vec::new(), Some(OutputCompression::Gzip)).unwrap();
+        writer.write_all(b"1 ERROR disk full\n").unwrap();
+        let compressed = writer.finish().unwrap();
+
+        let mut reader = crate::decompression::decompress(Box::new(std::io::Cursor::new(compressed)), "test").unwrap();
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "1 ERROR disk full\n");
+    }
+
+    /// Tests that the clipboard source is named and bannered like the other inputs.
+    #[test]
+    fn clipboard_source_banner() {
+        assert_eq!(InputSource::Clipboard.name(), "clipboard");
+        assert_eq!(InputSource::Clipboard.banner(), "==> clipboard <==");
+    }
+
+    /// Tests that lines of socket connections reach the reader whole, with a newline added to an unterminated last line.
+    #[test]
+    fn socket_connection_lines_are_forwarded_whole() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        crate::socket_input::forward_lines(&b"first\nsecond"[..], sender.clone());
+        crate::socket_input::forward_lines(&b"third\n"[..], sender);
+
+        let mut text = String::new();
+        crate::socket_input::ChannelReader::new(receiver).read_to_string(&mut text).unwrap();
+        assert_eq!(text, "first\nsecond\nthird\n");
+    }
+
+    /// Tests that `--listen` with a port alone listens on the loopback interface, and a host is kept.
+    #[test]
+    fn listen_address_defaults_to_loopback() {
+        use crate::socket_input::listen_address;
+
+        assert_eq!(listen_address("9000").unwrap(), "127.0.0.1:9000");
+        assert_eq!(listen_address("0.0.0.0:9000").unwrap(), "0.0.0.0:9000");
+        assert_eq!(listen_address("[::1]:9000").unwrap(), "[::1]:9000");
+        assert!(listen_address("70000").is_err());
+        assert!(listen_address(":9000").is_err());
+        let arguments = Cli::try_parse_from(["ricat", "--listen", "9000"]).unwrap();
+        assert_eq!(arguments.listen.as_deref(), Some("127.0.0.1:9000"));
+    }
+
+    /// Tests that `--listen-unix` replaces a stale socket, but refuses a regular file or a socket still listened on.
+    #[cfg(unix)]
+    #[test]
+    fn listen_unix_only_replaces_stale_sockets() {
+        use crate::socket_input::listen_unix;
+
+        let root = std::env::temp_dir().join(format!("ricat_listen_unix_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let file = root.join("file");
+        std::fs::write(&file, "kept").unwrap();
+        assert!(matches!(listen_unix(file.to_str().unwrap()), Err(RicatError::SocketError(_))));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "kept");
+
+        let socket = root.join("ricat.sock");
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        let reader = listen_unix(socket.to_str().unwrap());
+        assert!(reader.is_ok());
+        assert!(matches!(listen_unix(socket.to_str().unwrap()), Err(RicatError::SocketError(_))));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Tests that `--connect` sends the output to the TCP peer.
+    #[test]
+    fn connect_output_sends_over_tcp() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let mut output = open_output(&OutputTarget::Tcp(address), OutputFileOptions::default()).unwrap();
+        output.write_all(b"1 remote log\n").unwrap();
+        drop(output);
+
+        let (mut connection, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        connection.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "1 remote log\n");
+    }
+
+    /// Tests the progress bar text: percentage, sizes, throughput and ETA.
+    #[test]
+    fn progress_bar_render() {
+        let progress = crate::progress::Progress::new("big.iso", Some(4 * 1024 * 1024));
+        assert_eq!(
+            progress.render(std::time::Duration::ZERO),
+            format!("big.iso [{}]   0% 0 B/4.0 MiB 0 B/s ETA --", " ".repeat(30))
+        );
+    }
+
+    /// Tests that the copied bytes drive the progress bar and ETA.
+    #[test]
+    fn progress_bar_counts_copied_bytes() {
+        let mut progress = crate::progress::Progress::new("big.iso", Some(4 * 1024 * 1024));
+        let data = vec![b'x'; 1024 * 1024];
+        let mut output = Vec::new();
+        copy_with_progress(&data[..], &mut output, Buffering::default(), Some(&mut progress)).unwrap();
+
+        assert_eq!(output.len(), data.len());
+        assert_eq!(
+            progress.render(std::time::Duration::from_secs(1)),
+            format!("big.iso [{}{}]  25% 1.0 MiB/4.0 MiB 1.0 MiB/s ETA 3s", "#".repeat(7), " ".repeat(23))
+        );
+    }
+
+    /// Tests that typewriter mode writes one whole character at a time, multi-byte characters included.
+    #[test]
+    fn typewriter_writes_whole_characters() {
+        struct RecordingWriter(Vec<Vec<u8>>);
+        impl Write for RecordingWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.push(buf.to_vec());
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut recorder = RecordingWriter(Vec::new());
+        TypewriterWriter::new(&mut recorder, std::time::Duration::ZERO, TypewriterUnit::Char)
+            .write_all("né\n".as_bytes())
+            .unwrap();
+        assert_eq!(recorder.0, vec![b"n".to_vec(), "é".as_bytes().to_vec(), b"\n".to_vec()]);
+    }
+
+    /// Tests that the rainbow feature colors every character and resets the color at the end of the line.
+    #[test]
+    fn rainbow_colors_every_character() {
+        let mut rainbow = Rainbow::new();
+        let (red, green, blue) = Rainbow::color_at(0.0);
+        let first_line = rainbow.apply_feature("ab").unwrap();
+
+        assert!(first_line.starts_with(&format!("\x1b[38;2;{};{};{}ma", red, green, blue)));
+        assert!(first_line.ends_with("b\x1b[0m"));
+        assert_eq!(first_line.matches("\x1b[38;2;").count(), 2);
+        assert_eq!(rainbow.apply_feature("").unwrap(), "");
+    }
+
+    /// Tests the `Hex::encode` function.
+    /// Verifies that every byte becomes two lowercase hex digits.
+    #[test]
+    fn test_encode_hex() {
+        assert_eq!(Hex::encode("Hi\n"), Some("48690a".to_string()));
+    }
+
+    /// Tests the `Hex::decode` function.
+    /// Ensures that spaced and unspaced hex decode, and odd or invalid digits return `None`.
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(Hex::decode("48 65 6C 6c 6f"), Some("Hello".to_string()));
+        assert!(Hex::decode("486").is_none());
+        assert!(Hex::decode("zz").is_none());
+    }
+
+    /// Tests the `Base32::encode` function.
+    /// Verifies that the input text is correctly encoded using Base32.
+    #[test]
+    fn test_encode_base32() {
+        assert_eq!(Base32::encode("foobar"), Some("MZXW6YTBOI======".to_string()));
+    }
+
+    /// Tests the `Base32::decode` function through the decoding feature.
+    /// Ensures that valid lines are decoded and invalid Base32 is a feature error.
+    #[test]
+    fn test_decode_base32_feature() {
+        let mut feature = Base32Decoding::new();
+        assert_eq!(feature.apply_record(b"MZXW6YTBOI======", &LineContext::default()).unwrap(), vec![b"foobar".to_vec()]);
+        assert!(matches!(feature.apply_record(b"not base32!", &LineContext::default()), Err(RicatError::FeatureError(_))));
+    }
+
+    /// Tests the `Ascii85` codec with the Adobe delimiters, `z` groups and a partial last group.
+    /// Ensures that decoding restores the input.
+    #[test]
+    fn test_ascii85_roundtrip() {
+        let text = "Man \0\0\0\0sure.";
+        let encoded = Ascii85::encode(text).unwrap();
+        assert_eq!(encoded, "<~9jqo^zF*2M7/c~>");
+        assert_eq!(Ascii85::decode(&encoded), Some(text.to_string()));
+    }
+
+    /// Tests the `Z85` codec through the `--decode z85` feature.
+    /// Ensures that inputs which are not a multiple of 4 bytes are rejected.
+    #[test]
+    fn test_z85_roundtrip() {
+        assert_eq!(Z85::encode("ZeroMQ!!"), Some("t4I.)o&i5S".to_string()));
+        let mut feature = CodecDecoding::new(Codec::Z85);
+        assert_eq!(feature.apply_record(b"t4I.)o&i5S", &LineContext::default()).unwrap(), vec![b"ZeroMQ!!".to_vec()]);
+        assert!(Z85::encode("abc").is_none());
+    }
+
+    /// Tests the `Html::encode` function.
+    /// Verifies that markup characters and quotes are escaped.
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(
+            Html::encode("<a href=\"x\">Tom & Jerry's</a>"),
+            Some("&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;".to_string())
+        );
+    }
+
+    /// Tests the `Html::decode` function.
+    /// Ensures that named and numeric entities are unescaped and unknown ones are kept.
+    #[test]
+    fn test_html_unescape() {
+        assert_eq!(
+            Html::decode("&lt;b&gt; &#x27;hi&#39; &amp;&amp; &bogus; AT&T"),
+            Some("<b> 'hi' && &bogus; AT&T".to_string())
+        );
+    }
+
+    /// Tests the `Json::decode` function.
+    /// Ensures that escapes, including surrogate pairs, are expanded and invalid ones are kept.
+    #[test]
+    fn test_json_unescape() {
+        assert_eq!(
+            Json::decode(r#"msg=\"boom\"\n\tat main \u00e9 \ud83d\ude00 \x \u12"#),
+            Some("msg=\"boom\"\n\tat main é 😀 \\x \\u12".to_string())
+        );
+    }
+
+    /// Tests the `Json::encode` function.
+    /// Verifies that quotes, backslashes and control characters are escaped.
+    #[test]
+    fn test_json_escape() {
+        assert_eq!(
+            Json::encode("say \"hi\"\\\t\u{1}"),
+            Some(r#"say \"hi\"\\\t\u0001"#.to_string())
+        );
+    }
+
+    /// Tests that whole-stream Base64 wraps at 76 columns like the `base64` tool.
+    /// Ensures that decoding ignores the line breaks and restores the bytes.
+    #[test]
+    fn test_base64_stream_roundtrip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = Base64::encode_stream(&data);
+        let lines: Vec<&str> = encoded.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert!(lines[..4].iter().all(|line| line.len() == 76));
+        assert_eq!(Base64::decode_stream(encoded.as_bytes()), Some(data));
+        assert_eq!(Base64::encode_stream(b"a\nb\n"), "YQpiCg==\n");
+    }
+
+    /// Tests that the byte codecs round-trip binary data which is not UTF-8.
+    /// Ensures that the text adapter still rejects such data instead of mangling it.
+    #[test]
+    fn test_binary_safe_codecs() {
+        let data = [0xff, 0x00, 0xfe, 0x80, 0x10, 0x20, 0x30, 0x40];
+        for codec in [Codec::Base64, Codec::Base32, Codec::Hex, Codec::Ascii85, Codec::Z85] {
+            let encoded = codec.encode_bytes(&data).unwrap();
+            assert_eq!(codec.decode_bytes(&encoded), Some(data.to_vec()), "{:?}", codec);
+        }
+        assert!(Base64::decode("/wD+gA==").is_none());
+    }
+
+    /// Tests chained codecs: `--encode base64,hex` is undone by `--decode hex,base64`.
+    /// Verifies that the codecs are applied in the declared order.
+    #[test]
+    fn test_codec_chain_roundtrip() {
+        let mut encoding = CodecEncoding::chain(vec![Codec::Base64, Codec::Hex]);
+        let encoded = encoding.apply_record(b"Hi", &LineContext::default()).unwrap().remove(0);
+        assert_eq!(encoded, Hex::encode("SGk=").unwrap().into_bytes());
+
+        let mut decoding = CodecDecoding::chain(vec![Codec::Hex, Codec::Base64]);
+        assert_eq!(decoding.apply_record(&encoded, &LineContext::default()).unwrap(), vec![b"Hi".to_vec()]);
+    }
+
+    /// Tests that byte features see the raw bytes of a record while line features get it as text.
+    /// Ensures that invalid UTF-8 is hex encoded as it is, and binary decoded data is written unchanged.
+    #[test]
+    fn byte_features_are_binary_safe() {
+        let mut output = Vec::new();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(HexEncoding::new())];
+        process_input_to(&b"\xff\x00a\n"[..], "-", &mut output, &mut features, OutputOptions::default()).unwrap();
+        assert_eq!(output, b"ff0061\n");
+
+        let mut output = Vec::new();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(HexDecoding::new())];
+        process_input_to(&b"fe00\n"[..], "-", &mut output, &mut features, OutputOptions::default()).unwrap();
+        assert_eq!(output, b"\xfe\x00\n");
+
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(HexDecoding::new()), Box::new(DollarSymbolAtLast::new())];
+        assert_eq!(process_input_ret(&b"6869ff\n"[..], &mut features).unwrap(), vec!["hi\u{FFFD}$"]);
+    }
+
+    /// Tests that a feature failing on a line stops the processing with the number of that line.
+    #[test]
+    fn feature_errors_report_the_line() {
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(Base64Decoding::new())];
+        let error = process_input_ret(&b"aGk=\nnot base64!\n"[..], &mut features).unwrap_err();
+        assert_eq!(error.to_string(), "Feature error: line 2: invalid Base64 input");
+
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(CodecEncoding::new(Codec::Z85))];
+        let error = process_lines(&mut features, "", vec!["abcd".to_string(), "abc".to_string()]).unwrap_err();
+        assert_eq!(error.to_string(), "Feature error: line 2: cannot encode the line as z85");
+    }
+
+    /// Tests that `--encrypt` output decrypts back with the same passphrase only.
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encryption_roundtrip() {
+        use crate::encryption::{decrypt, EncryptedWriter};
+
+        let mut writer = EncryptedWriter::new(Vec::new(), Some("correct horse".to_string())).unwrap();
+        writer.write_all(b"secret snippet\n").unwrap();
+        let encrypted = writer.finish().unwrap();
+
+        let mut text = String::new();
+        decrypt(Box::new(std::io::Cursor::new(encrypted.clone())), "correct horse", "test")
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "secret snippet\n");
+        assert!(decrypt(Box::new(std::io::Cursor::new(encrypted)), "wrong", "test").is_err());
+    }
+
+    /// Tests the checksums fed by the streaming copy against known digests of "abc".
+    /// Verifies the `sha256sum` style line for one algorithm and BSD style lines for several.
+    #[test]
+    fn checksums_of_streamed_input() {
+        use crate::checksum::{checksum_lines, ChecksumAlgorithm, DigestWriter};
+
+        let mut writer = DigestWriter::new(std::io::sink(), &[ChecksumAlgorithm::Sha256]);
+        copy(&b"abc"[..], &mut writer).unwrap();
+        assert_eq!(
+            checksum_lines("a.txt", &writer.finalize()),
+            vec!["ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  a.txt"]
+        );
+
+        let mut writer = DigestWriter::new(std::io::sink(), &[ChecksumAlgorithm::Md5, ChecksumAlgorithm::Crc32]);
+        copy(&b"abc"[..], &mut writer).unwrap();
+        assert_eq!(
+            checksum_lines("-", &writer.finalize()),
+            vec!["MD5 (-) = 900150983cd24fb0d6963f7d28e17f72", "CRC32 (-) = 352441c2"]
+        );
+    }
+
+    /// Tests the per-line hash feature in replace and annotate mode.
+    #[test]
+    fn hash_lines_replace_and_annotate() {
+        let mut replace = LineHashing::new(ChecksumAlgorithm::Sha1, false);
+        assert_eq!(
+            replace.apply_feature("abc"),
+            Some("a9993e364706816aba3e25717850c26c9cd0d89d".into())
+        );
+
+        let mut annotate = LineHashing::new(ChecksumAlgorithm::Crc32, true);
+        assert_eq!(annotate.apply_feature("abc"), Some("352441c2  abc".into()));
+    }
+
+    /// Tests that UTF-16 and Latin-1 input is detected and transcoded to UTF-8 before the features.
+    #[test]
+    fn transcode_detects_utf16_and_latin1() {
+        use crate::transcoding::{transcode, SourceEncoding};
+
+        let utf16: Vec<u8> = "héllo\nw\n".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let reader = transcode(Box::new(std::io::Cursor::new(utf16)), SourceEncoding::Auto).unwrap();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineNumbering::new())];
+        assert_eq!(process_input_ret(reader, &mut features).unwrap(), vec!["1 héllo", "2 w"]);
+
+        let mut text = String::new();
+        transcode(Box::new(&b"caf\xe9"[..]), SourceEncoding::Auto)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "café");
+    }
+
+    /// Tests that invalid UTF-8 sequences are reported with their line, column and byte offset.
+    #[test]
+    fn validate_utf8_reports_invalid_sequences() {
+        use crate::utf8_validation::validate_utf8;
+
+        let input = b"ok\nca\xff\xfef\xc3\xa9\nend\xe2\x82";
+        let mut reports = Vec::new();
+        let count = validate_utf8(&input[..], |sequence| reports.push(sequence.report("in"))).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(
+            reports,
+            vec![
+                "in:2:3: invalid UTF-8 at byte offset 5: ff",
+                "in:2:4: invalid UTF-8 at byte offset 6: fe",
+                "in:3:4: invalid UTF-8 at byte offset 14: e2 82",
+            ]
+        );
+    }
+
+    /// Tests that YAML and TOML are re-emitted well-formatted, and parse errors carry their line and column.
+    #[test]
+    fn structured_formats_pretty_print_and_report_errors() {
+        use crate::structured_formats::{pretty_print, StructuredFormat};
+
+        assert_eq!(
+            pretty_print(StructuredFormat::Yaml, "b: {c: 1}\na:   [x, y]\n").unwrap(),
+            "b:\n  c: 1\na:\n- x\n- y\n"
+        );
+        assert_eq!(
+            pretty_print(StructuredFormat::Toml, "z=1\n[t]\nb=\"q\"\n").unwrap(),
+            "z = 1\n\n[t]\nb = \"q\"\n"
+        );
+
+        let error = pretty_print(StructuredFormat::Toml, "a = 1\nb = = 2\n").unwrap_err();
+        assert_eq!(error.position, Some((2, 5)));
+        assert!(error.report("cfg.toml").starts_with("cfg.toml:2:5: "));
+    }
+
+    /// Tests that quoted CSV fields keep their delimiters, quotes and line breaks.
+    #[test]
+    fn parse_delimited_handles_quoted_fields() {
+        use crate::table::parse_delimited;
+
+        let rows = parse_delimited("a,\"b, c\",\"say \"\"hi\"\"\"\r\n\"x\ny\",,z", ',');
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a", "b, c", "say \"hi\""],
+                vec!["x\ny", "", "z"],
+            ]
+        );
+    }
+
+    /// Tests that the table is rendered as aligned columns with a rule under the header.
+    #[test]
+    fn render_table_aligns_columns() {
+        use crate::table::{parse_delimited, render_table};
+
+        let rows = parse_delimited("name\tage\nAlice\t30\nBo\t4\n", '\t');
+        assert_eq!(
+            render_table(&rows, true, None),
+            vec!["name   age", "----------", "Alice  30", "Bo     4"]
+        );
+    }
+
+    /// Tests that `--delta` prefixes every line with the right-aligned time since the previous line.
+    #[test]
+    fn line_delta_prefixes_elapsed_time() {
+        let mut delta = LineDelta::new();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let first = delta.apply_feature("first").unwrap();
+        let second = delta.apply_feature("second").unwrap();
+
+        let (time, line) = first.trim_start().split_once(' ').unwrap();
+        assert_eq!(line, "first");
+        let seconds: f64 = time.strip_prefix('+').unwrap().strip_suffix('s').unwrap().parse().unwrap();
+        assert!(seconds >= 0.02);
+        assert_eq!(second.len(), "   +0.000s second".len());
+        assert!(second.ends_with("s second"));
+    }
+
+    /// Tests that color, cursor, title and cut-off escape sequences are stripped, and take no visible room.
+    #[test]
+    fn strip_ansi_removes_escape_sequences() {
+        use crate::ansi::{segments, strip_ansi, visible_len, Segment};
+
+        let line = "\x1b[1;31merror\x1b[0m: \x1b]0;title\x07d\x1b(Bone\x1b[2K\x1b[";
+        assert_eq!(strip_ansi(line), "error: done");
+        assert_eq!(visible_len(line), 11);
+        assert_eq!(
+            segments("a\x1b[32mb")[..],
+            [Segment::Text("a"), Segment::Escape("\x1b[32m"), Segment::Text("b")]
+        );
+        assert_eq!(StripAnsi::new().apply_feature("plain \u{9b}1mtext").unwrap(), "plain text");
+    }
+
+    /// Tests that features opting into the ANSI-aware pipeline keep clear of escape sequences.
+    #[test]
+    fn ansi_aware_features_keep_clear_of_escapes() {
+        use crate::ansi::AnsiLine;
+
+        let colored = "\x1b[31mred\x1b[0m";
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(DollarSymbolAtLast::new())];
+        assert_eq!(apply_features(&mut features, colored.to_string(), &LineContext::default()).unwrap(), vec!["\x1b[31mred\x1b[0m$"]);
+        assert_eq!(apply_features(&mut features, "\x1b[1mbold".to_string(), &LineContext::default()).unwrap(), vec!["\x1b[1mbold\x1b[0m$"]);
+
+        let mut search: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineWithGivenText::new("error:", false))];
+        assert_eq!(apply_features(&mut search, "\x1b[1merror\x1b[0m: disk".to_string(), &LineContext::default()).unwrap().len(), 1);
+
+        let line = AnsiLine::parse("\x1b[32mgreen\x1b[0m text");
+        assert_eq!(line.truncate(3), "\x1b[32mgre\x1b[0m");
+        assert_eq!(line.visible_len(), 10);
+    }
+
+    /// Tests that spaces, tabs and non-breaking spaces are made visible, and colored only when asked to.
+    #[test]
+    fn show_whitespace_marks_spaces_tabs_and_nbsp() {
+        let mut plain = ShowWhitespace::new(false);
+        assert_eq!(plain.apply_feature("\tif a\u{a0}b ").unwrap(), "→if·a␣b·");
+
+        let mut colored = ShowWhitespace::new(true);
+        assert_eq!(colored.apply_feature("a b").unwrap(), "a\x1b[90m·\x1b[0mb");
+    }
+
+    /// Tests that trailing whitespace is trimmed or highlighted, also in front of a closing color reset.
+    #[test]
+    fn trailing_whitespace_is_trimmed_or_highlighted() {
+        let mut trim: Vec<Box<dyn ByteFeature>> = vec![Box::new(TrimTrailing::new())];
+        assert_eq!(apply_features(&mut trim, "code \t ".to_string(), &LineContext::default()).unwrap(), vec!["code"]);
+        assert_eq!(apply_features(&mut trim, "\x1b[31mred  \x1b[0m".to_string(), &LineContext::default()).unwrap(), vec!["\x1b[31mred\x1b[0m"]);
+        assert_eq!(apply_features(&mut trim, "  indented".to_string(), &LineContext::default()).unwrap(), vec!["  indented"]);
+
+        let mut highlight = HighlightTrailing::new();
+        assert_eq!(highlight.apply_feature("a b \t").unwrap(), "a b\x1b[41m \t\x1b[0m");
+        assert_eq!(highlight.apply_feature("clean").unwrap(), "clean");
+    }
+
+    /// Tests that tabs are expanded up to the next tab stop, with escape sequences taking no room.
+    #[test]
+    fn expand_tabs_honors_tab_stops() {
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(ExpandTabs::new(4))];
+        assert_eq!(apply_features(&mut features, "\ta\tbcd\te".to_string(), &LineContext::default()).unwrap(), vec!["    a   bcd e"]);
+        assert_eq!(
+            apply_features(&mut features, "\x1b[1mab\x1b[0m\tc".to_string(), &LineContext::default()).unwrap(),
+            vec!["\x1b[1mab\x1b[0m  c"]
+        );
+        assert_eq!(ExpandTabs::default().apply_feature("x\ty").unwrap(), "x       y");
+    }
+
+    /// Tests that leading spaces become tabs at the tab stops, and other whitespace stays.
+    #[test]
+    fn unexpand_converts_leading_spaces() {
+        let mut unexpand = UnexpandTabs::new(4);
+        assert_eq!(unexpand.apply_feature("          x  y").unwrap(), "\t\t  x  y");
+        assert_eq!(unexpand.apply_feature("  \t  x").unwrap(), "\t  x");
+        assert_eq!(unexpand.apply_feature("x    y").unwrap(), "x    y");
+    }
+
+    /// Tests that long lines are folded at word boundaries into several lines, which later features see one by one.
+    #[test]
+    fn wrap_folds_lines_at_word_boundaries() {
+        let mut features: Vec<Box<dyn ByteFeature>> =
+            vec![Box::new(LineWrapping::new(10)), Box::new(LineNumbering::new())];
+        assert_eq!(
+            apply_features(&mut features, "the quick brown fox jumps".to_string(), &LineContext::default()).unwrap(),
+            vec!["1 the quick ", "2 brown fox ", "3 jumps"]
+        );
+        assert_eq!(LineWrapping::new(4).apply_feature_lines("abcdefghij").unwrap(), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(LineWrapping::new(4).apply_feature_lines("").unwrap(), vec![""]);
+    }
+
+    /// Tests that oversized lines are chunked without splitting characters, or truncated up to their line break.
+    #[test]
+    fn max_line_bytes_chunks_or_truncates() {
+        use crate::line_reader::{LineLimit, OversizeMode};
+
+        let input: &[u8] = b"short\nabcd\xc3\xa9fgh\r\nend";
+        let chunk = LineLimit { max_bytes: 5, mode: OversizeMode::Chunk };
+        let options = OutputOptions { keep_line_endings: true, line_limit: Some(chunk), ..Default::default() };
+        let mut output = Vec::new();
+        process_input_to(input, "-", &mut output, &mut [], options.clone()).unwrap();
+        assert_eq!(output, input);
+        assert_eq!(
+            process_input_ret_with(input, "-", &mut [], options).unwrap(),
+            vec!["short", "abcd", "éfgh", "end"]
+        );
+
+        let truncate = LineLimit { max_bytes: 4, mode: OversizeMode::Truncate };
+        let options = OutputOptions { keep_line_endings: true, line_limit: Some(truncate), ..Default::default() };
+        let mut output = Vec::new();
+        process_input_to(input, "-", &mut output, &mut [], options).unwrap();
+        assert_eq!(output, b"shor\nabcd\r\nend");
+    }
+
+    /// Tests that `-z` splits records on NUL and terminates the output records with NUL, newlines included.
+    #[test]
+    fn zero_terminated_records() {
+        let input: &[u8] = b"a file\nwith newline\0second\0last";
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineNumbering::new())];
+        let options = OutputOptions { zero_terminated: true, ..Default::default() };
+
+        let mut output = Vec::new();
+        process_input_to(input, "-", &mut output, &mut features, options).unwrap();
+        assert_eq!(output, b"1 a file\nwith newline\x002 second\x003 last\0");
+    }
+
+    /// Tests the `nl` like number layouts: width, alignment, separator and starting number.
+    #[test]
+    fn line_numbering_formats() {
+        let format = NumberFormat { width: 4, align: NumberAlign::Right, separator: " | ".to_string(), start: 99 };
+        let mut numbering = LineNumbering::with_format(format.clone());
+        assert_eq!(numbering.apply_feature("a").unwrap(), "  99 | a");
+        assert_eq!(numbering.apply_feature("b").unwrap(), " 100 | b");
+
+        let mut left = LineNumbering::with_format(NumberFormat { align: NumberAlign::Left, ..format.clone() });
+        assert_eq!(left.apply_feature("a").unwrap(), "99   | a");
+        let mut zero = LineNumbering::with_format(NumberFormat { align: NumberAlign::Zero, separator: "\t".to_string(), ..format });
+        assert_eq!(zero.apply_feature("a").unwrap(), "0099\ta");
+    }
+
+    /// Tests that `--show-offsets` prefixes every line with its starting byte offset, counting the original terminators.
+    #[test]
+    fn show_offsets_prefixes_byte_offsets() {
+        let input: &[u8] = b"ab\r\n\xff\xfe\nlast";
+        let options = OutputOptions { offset_format: Some(OffsetFormat::Hex), ..Default::default() };
+        assert_eq!(
+            process_input_ret_with(input, "-", &mut [], options).unwrap(),
+            vec!["00000000 ab", "00000004 \u{FFFD}\u{FFFD}", "00000007 last"]
+        );
+
+        let options = OutputOptions { offset_format: Some(OffsetFormat::Dec), ..Default::default() };
+        let mut output = Vec::new();
+        process_input_to(&b"x\ny\n"[..], "-", &mut output, &mut [], options).unwrap();
+        assert_eq!(output, b"         0 x\n         2 y\n");
+    }
+
+    /// Tests that `--output-format json` writes an object per processed line, with the text escaped and the offset added by `--show-offsets`.
+    #[test]
+    fn json_output_writes_an_object_per_line() {
+        let options = OutputOptions { output_format: OutputFormat::Json, ..Default::default() };
+        let mut output = Vec::new();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineWithGivenText::new("b", false))];
+        process_input_to(&b"a\r\nsay \"b\"\r\nb\\\xff"[..], "a.txt", &mut output, &mut features, options.clone()).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"file\":\"a.txt\",\"line\":2,\"text\":\"say \\\"b\\\"\"}\n{\"file\":\"a.txt\",\"line\":3,\"text\":\"b\\\\\u{FFFD}\"}\n"
+        );
+
+        let options = OutputOptions { offset_format: Some(OffsetFormat::Dec), ..options };
+        assert_eq!(
+            process_input_ret_with(&b"x\ny"[..], "-", &mut [], options).unwrap(),
+            vec![r#"{"file":"-","line":1,"offset":0,"text":"x"}"#, r#"{"file":"-","line":2,"offset":2,"text":"y"}"#]
+        );
+    }
+
+    /// Tests that `--format` lays out every line by its template, with `{match}` taken from the search, and rejects unknown placeholders.
+    #[test]
+    fn format_template_lays_out_lines() {
+        let arguments = Cli::try_parse_from(["ricat", "--search", "--text", "reg:[0-9]+", "--format", "{file}:{line}:{offset}: {{{match}}} {text}"]).unwrap();
+        let mut features = builtin_features().build(&arguments, &[], &[]).unwrap();
+        let mut output = Vec::new();
+        process_input_to(&b"a\nb 12\n"[..], "x.txt", &mut output, &mut features, output_options(&arguments)).unwrap();
+        assert_eq!(output, b"x.txt:2:2: {12} b 12\n");
+
+        assert!(Cli::try_parse_from(["ricat", "--format", "{file} {name}"]).is_err());
+        assert!(OutputTemplate::parse("{text").is_err());
+        assert!(OutputTemplate::parse("text}").is_err());
+    }
+
+    /// Tests that `ricat completions` parses its shell and writes a script completing the flags and subcommands.
+    #[test]
+    fn completions_cover_flags_and_subcommands() {
+        let arguments = Cli::try_parse_from(["ricat", "completions", "fish"]).unwrap();
+        assert_eq!(arguments.command, Some(Command::Completions { shell: clap_complete::Shell::Fish }));
+        assert!(Cli::try_parse_from(["ricat", "completions", "tcsh"]).is_err());
+
+        let mut script = Vec::new();
+        write_completions(clap_complete::Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("--search") && script.contains("--output-format") && script.contains("bench"));
+    }
+
+    /// Tests that the file of `ricat config init` is valid with its examples commented out or in, and that `config show` prints TOML read back the same.
+    #[test]
+    fn default_config_parses_with_its_examples() {
+        let config: config::RicatConfig = toml::from_str(config::DEFAULT_CONFIG).unwrap();
+        assert!(!config.number_feature && config.pagination_mode.is_none());
+
+        let uncommented: String = config::DEFAULT_CONFIG
+            .lines()
+            .map(|line| if line.starts_with("# ") && (line.contains(" = ") || line.starts_with("# [")) { &line[2..] } else { line })
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let config: config::RicatConfig = toml::from_str(&uncommented).unwrap();
+        assert_eq!(config.pagination_mode, Some(PagesMode::Auto));
+        assert_eq!(config.io_backend, Some(IoBackend::Auto));
+        assert_eq!(config.max_memory, Some(64 * 1024 * 1024));
+        assert_eq!(config.profiles.keys().collect::<Vec<_>>(), ["code", "logs"]);
+        assert_eq!(config.theme.name, Some(crate::theme::ThemeName::Default));
+
+        let shown: config::RicatConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(shown.feature_order, config.feature_order);
+        assert_eq!(shown.number_separator, config.number_separator);
+    }
+
+    /// Tests that a mistake in the configuration file is an error naming the file, line and key, and that
+    /// `config check` reports it, as well as invalid `RICAT_*` variables.
+    #[test]
+    fn config_mistakes_name_the_line_and_key() {
+        let path = std::env::temp_dir().join(format!("ricat_config_check_{}.toml", std::process::id()));
+        let error_of = |text: &str| {
+            std::fs::write(&path, text).unwrap();
+            config::read_config(&path).unwrap_err().to_string()
+        };
+        let location = format!("{}:", path.display());
+
+        let error = error_of("number_feature = true\nnmbers = true\n");
+        assert!(error.contains(&format!("{}2:1: `nmbers`: unknown field `nmbers`", location)), "{}", error);
+        let error = error_of("[numbering]\nwidth = 4\n\n[squeeze]\nmax_blank = \"two\"\n");
+        assert!(error.contains(&format!("{}5:13: `squeeze.max_blank`: invalid type", location)), "{}", error);
+        let error = error_of("[profiles.code]\ntabs.wdth = 4\n");
+        assert!(error.contains("`profiles.code.tabs.wdth`: unknown field `wdth`, expected `width`"), "{}", error);
+
+        std::fs::write(&path, "[profiles.code]\nnumber_feature = true\n").unwrap();
+        let mut output = Vec::new();
+        config::check_config(&path, vec![("RICAT_PROFILE".to_string(), "code".to_string())], &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), format!("{}: ok\n", path.display()));
+        let variables = vec![("RICAT_TAB_WIDTH".to_string(), "wide".to_string())];
+        assert!(matches!(config::check_config(&path, variables, &mut Vec::new()), Err(RicatError::EnvironmentError(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Tests that the configuration lives in the platform directory, unless only the `~/.config/ricat` of earlier versions holds a file.
+    #[test]
+    fn config_dir_keeps_a_legacy_config() {
+        let root = std::env::temp_dir().join(format!("ricat_config_dirs_{}", std::process::id()));
+        let (platform, legacy) = (root.join("platform"), root.join("legacy"));
+        std::fs::create_dir_all(&legacy).unwrap();
+        let choose = || config::choose_config_dir(Some(platform.clone()), Some(legacy.clone()));
+
+        assert_eq!(choose(), Some(platform.clone()));
+        std::fs::write(legacy.join("ricat_cfg.toml"), "").unwrap();
+        assert_eq!(choose(), Some(legacy.clone()));
+        std::fs::create_dir_all(&platform).unwrap();
+        std::fs::write(platform.join("ricat_cfg.toml"), "").unwrap();
+        assert_eq!(choose(), Some(platform.clone()));
+        assert_eq!(config::choose_config_dir(None, Some(legacy.clone())), Some(legacy));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Tests that `RICAT_*` variables override the configuration file, and that invalid values name the variable and what it takes.
+    #[test]
+    fn env_vars_override_the_config() {
+        let mut config: config::RicatConfig = toml::from_str("number_feature = true\ntabs_feature = true\nnumber_width = 4").unwrap();
+        let variables = [("RICAT_NUMBERS", "0"), ("RICAT_DOLLAR", "yes"), ("RICAT_PAGES", "Never"), ("RICAT_MAX_MEMORY", "64M"),
+            ("RICAT_FEATURES", "squeeze, numbering"), ("RICAT_CONFIG_DIR", "/tmp")];
+        config::apply_env_overrides(&mut config, variables.map(|(name, value)| (name.to_string(), value.to_string()))).unwrap();
+        assert!(!config.number_feature && config.dollar_sign_feature && config.tabs_feature);
+        assert_eq!(config.number_width, Some(4));
+        assert_eq!(config.pagination_mode, Some(PagesMode::Never));
+        assert_eq!(config.max_memory, Some(64 * 1024 * 1024));
+        assert_eq!(config.features, vec!["squeeze", "numbering"]);
+
+        let error = config::apply_env_overrides(&mut config, [("RICAT_PAGER_LONG_LINES".to_string(), "fold".to_string())]).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid environment variable: RICAT_PAGER_LONG_LINES=fold is not one of wrap, scroll");
+        assert!(config::apply_env_overrides(&mut config, [("RICAT_NUMBERS".to_string(), "maybe".to_string())]).is_err());
+    }
+
+    /// Tests that `--profile` applies its section over the settings of the file, and that an unknown profile lists the known ones.
+    #[test]
+    fn profile_overrides_the_file_settings() {
+        let text = "tabs_feature = true\nnumber_width = 4\nfeatures = [\"squeeze\"]\n\n[profiles.logs]\nnumber_feature = true\nfeatures = [\"unique\"]\npagination_mode = \"never\"\n\n[profiles.code]\ntab_width = 2\n";
+        let mut config: config::RicatConfig = toml::from_str(text).unwrap();
+        config.select_profile("logs").unwrap();
+        assert!(config.number_feature && config.tabs_feature);
+        assert_eq!(config.features, vec!["unique"]);
+        assert_eq!(config.pagination_mode, Some(PagesMode::Never));
+        assert_eq!(config.number_width, Some(4));
+        assert_eq!(config.tab_width, None);
+
+        let error = config.select_profile("docs").unwrap_err();
+        assert_eq!(error.to_string(), "Error reading config file: no profile `docs`, the profiles are code");
+    }
+
+    /// Tests that the `[numbering]`, `[squeeze]` and `[tabs]` sections set the defaults of their flags, over the keys of the top level.
+    #[test]
+    fn feature_sections_set_flag_defaults() {
+        let text = "number_width = 8\nnumber_start = 5\n[numbering]\nwidth = 4\nseparator = \": \"\n[squeeze]\nmax_blank = 2\n[tabs]\nwidth = 4\n";
+        let config: config::RicatConfig = toml::from_str(text).unwrap();
+        let mut arguments = Cli::try_parse_from(["ricat", "-s", "--expand-tabs", "--number-start", "10"]).unwrap();
+        enable_features_from_config(&config, &mut arguments);
+        assert_eq!((arguments.number_width, arguments.number_start), (Some(4), Some(10)));
+        assert_eq!(arguments.number_separator.as_deref(), Some(": "));
+        assert_eq!(arguments.max_blank, Some(2));
+        assert_eq!(arguments.expand_tabs, Some(Some(4)));
+
+        let mut features = builtin_features().build(&arguments, &[], &[]).unwrap();
+        let mut output = Vec::new();
+        process_input_to(&b"a\n\n\n\nb\n"[..], "-", &mut output, &mut features, OutputOptions::default()).unwrap();
+        assert_eq!(output, b"a\n\n\nb\n");
+    }
+
+    /// Tests that styles are parsed from color and attribute words, and that unknown words are rejected.
+    #[test]
+    fn theme_styles_parse_colors_and_attributes() {
+        use crate::theme::Style;
+
+        assert_eq!(Style::parse("bold red").unwrap().paint("x"), "\x1b[1;31mx\x1b[0m");
+        assert_eq!(Style::parse("bright-blue on-#ff8000").unwrap().paint("x"), "\x1b[94;48;2;255;128;0mx\x1b[0m");
+        assert_eq!(Style::parse("208 on-gray").unwrap().paint("x"), "\x1b[38;5;208;100mx\x1b[0m");
+        assert_eq!(Style::parse("none").unwrap().paint("x"), "x");
+        assert!(Style::parse("bold purple").unwrap_err().contains("`purple`"));
+        assert!(Style::parse("#12345").is_err());
+    }
+
+    /// Tests that the `[theme]` section picks a built-in theme and changes single colors, which color the
+    /// line numbers, the search matches and the log levels.
+    #[test]
+    fn theme_section_colors_the_features() {
+        use crate::theme::{Theme, ThemeName};
+
+        let text = "[theme]\nname = \"monochrome\"\nsearch_match = \"underline\"\n[profiles.loud]\ntheme.line_number = \"red\"\n";
+        let mut config: config::RicatConfig = toml::from_str(text).unwrap();
+        config.select_profile("loud").unwrap();
+        let mut arguments = Cli::try_parse_from(["ricat"]).unwrap();
+        enable_features_from_config(&config, &mut arguments);
+        let theme = theme(&arguments);
+        assert_eq!(arguments.theme, Some(ThemeName::Monochrome));
+        assert_eq!(theme.pager_status, Theme::builtin(ThemeName::Monochrome).pager_status);
+
+        let mut numbering = LineNumbering::with_format(NumberFormat::default()).with_style(theme.line_number.clone());
+        assert_eq!(numbering.apply_feature("a").unwrap(), "\x1b[31m1\x1b[0m a");
+        let mut search = LineWithGivenText::new("o", false).with_highlight(theme.search_match.clone());
+        assert_eq!(search.apply_feature("foo bar").unwrap(), "f\x1b[4mo\x1b[0m\x1b[4mo\x1b[0m bar");
+        assert_eq!(search.apply_feature("bar"), None);
+        let mut levels = LogLevels::new(&Theme::default());
+        assert_eq!(levels.apply_feature("12:00 WARN disk").unwrap(), "12:00 \x1b[33mWARN\x1b[0m disk");
+        assert_eq!(levels.apply_feature("warnings are words").unwrap(), "warnings are words");
+
+        // the default theme keeps the colors of earlier versions
+        let mut markers = ShowWhitespace::new(true).with_theme(Theme::default());
+        assert_eq!(markers.apply_feature("a b").unwrap(), "a\x1b[90m·\x1b[0mb");
+    }
+
+    /// Tests that relative numbers count from 0 again at the start of every block of lines.
+    #[test]
+    fn relative_numbers_restart_every_block() {
+        let mut numbering = LineNumbering::relative(3, NumberFormat::default());
+        let numbers: Vec<String> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|line| numbering.apply_feature(line).unwrap().into_owned())
+            .collect();
+        assert_eq!(numbers, vec!["0 a", "1 b", "2 c", "0 d", "1 e"]);
+    }
+
+    /// Tests that the git gutter marks changed lines by their number in the input, and is reset for every input.
+    #[test]
+    fn git_markers_mark_changed_lines() {
+        use crate::git_markers::LineChange;
+
+        let changes = [(1, LineChange::Added), (3, LineChange::Modified), (4, LineChange::RemovedAbove)];
+        let mut markers = GitMarkers::with_changes(changes.into_iter().collect(), false);
+        let lines: Vec<String> = ["a", "b", "c", "d"].iter().map(|line| markers.apply_feature(line).unwrap().into_owned()).collect();
+        assert_eq!(lines, vec!["+ a", "  b", "~ c", "_ d"]);
+
+        // after a search dropping lines, the markers are still those of the input lines
+        let mut markers = GitMarkers::with_changes(changes.into_iter().collect(), false);
+        let context = LineContext { line_number: 3, ..LineContext::default() };
+        assert_eq!(markers.apply_feature_in_context("c", &context).unwrap(), vec!["~ c"]);
+        let mut features: Vec<Box<dyn ByteFeature>> =
+            vec![Box::new(LineWithGivenText::new("c", false)), Box::new(GitMarkers::with_changes(changes.into_iter().collect(), false))];
+        assert_eq!(process_input_ret(&b"a\nb\nc\nd\n"[..], &mut features).unwrap(), vec!["~ c"]);
+        assert!(Cli::try_parse_from(["ricat", "--git", "-s", "--search", "--text", "c", "a.txt"]).is_ok());
+
+        markers.begin_input(&InputSource::Stdin).unwrap();
+        assert_eq!(markers.apply_feature("a").unwrap(), "  a");
+    }
+
+    /// Tests that the blame column is aligned to the longest author, lines without blame are left alone,
+    /// and a line gets the blame of its number in the input, so lines squeezed away do not shift it.
+    #[test]
+    fn blame_prefixes_lines_with_their_commit() {
+        use crate::git_markers::BlameLine;
+
+        let blame_line = |commit: &str, author: &str, date: &str| BlameLine {
+            commit: commit.to_string(),
+            author: author.to_string(),
+            date: date.to_string(),
+        };
+        let mut blame = Blame::with_lines(
+            vec![
+                blame_line("1a2b3c4d", "Alice Smith", "2024-05-01"),
+                blame_line("00000000", "Not Committed Yet", ""),
+            ],
+            false,
+        );
+        let lines: Vec<String> = ["first", "second", "third"].iter().map(|line| blame.apply_feature(line).unwrap().into_owned()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "1a2b3c4d (Alice Smith       2024-05-01) first",
+                "00000000 (Not Committed Yet           ) second",
+                "third",
+            ]
+        );
+
+        // after a search dropping the first line, the second one still gets its own blame
+        let context = LineContext { line_number: 2, ..LineContext::default() };
+        let mut blame = Blame::with_lines(vec![blame_line("1a2b3c4d", "Alice", "2024-05-01"), blame_line("5e6f7a8b", "Bob", "2024-06-01")], false);
+        assert_eq!(blame.apply_feature_in_context("second", &context).unwrap(), vec!["5e6f7a8b (Bob   2024-06-01) second"]);
+        assert!(Cli::try_parse_from(["ricat", "--blame", "-s", "a.txt"]).is_ok());
+    }
+
+    /// Tests the unified diff of two texts, and that equal texts have no diff.
+    #[test]
+    fn unified_diff_of_two_texts() {
+        use crate::diff::unified_diff;
+
+        let old = "one\ntwo\nthree\nfour\n";
+        let new = "one\n2\nthree\nfour";
+        assert_eq!(
+            unified_diff(old, new, ("a.txt", "b.txt"), 1, None),
+            vec![
+                "--- a.txt",
+                "+++ b.txt",
+                "@@ -1,4 +1,4 @@",
+                " one",
+                "-two",
+                "+2",
+                " three",
+                "-four",
+                "+four",
+                "\\ No newline at end of file",
+            ]
+        );
+        assert!(unified_diff(old, old, ("a.txt", "a.txt"), 3, None).is_empty());
+    }
+
+    /// Tests that the side by side view pairs up the lines of both texts and marks their differences.
+    #[test]
+    fn side_by_side_pairs_lines() {
+        use crate::diff::side_by_side;
+
+        let rows = side_by_side("same\nold\ngone\n", "same\nnew\n", ("a", "b"), 13, None);
+        assert_eq!(rows, vec!["a     │ b", "same  │ same", "old   | new", "gone  <"]);
+
+        let rows = side_by_side("x\n", "x\nlonger line\n", ("a", "b"), 13, None);
+        assert_eq!(rows[2], "      > longe");
+    }
+
+    /// Tests that the inputs compared by `--diff` and `--side-by-side` may hold invalid UTF-8.
+    #[test]
+    fn compared_inputs_may_be_invalid_utf8() {
+        let old = std::env::temp_dir().join(format!("ricat_compare_old_{}.bin", std::process::id()));
+        let new = std::env::temp_dir().join(format!("ricat_compare_new_{}.bin", std::process::id()));
+        std::fs::write(&old, b"caf\xe9\n").unwrap();
+        std::fs::write(&new, b"cafe\n").unwrap();
+        let arguments =
+            Cli::try_parse_from(["ricat", "--diff", old.to_str().unwrap(), new.to_str().unwrap()]).unwrap();
+        let inputs = two_inputs(&arguments, "--diff").unwrap();
+        assert_eq!(inputs[0].1, "caf\u{FFFD}\n");
+        assert_eq!(inputs[1].1, "cafe\n");
+        std::fs::remove_file(old).unwrap();
+        std::fs::remove_file(new).unwrap();
+    }
+
+    /// Tests that interleaving joins the lines of the inputs, with empty fields for inputs which ended.
+    #[test]
+    fn interleave_joins_lines_of_inputs() {
+        use crate::line_reader::lossy_lines;
+
+        let mut inputs = [lossy_lines(&b"1\n2\n3\n"[..]), lossy_lines(&b"x\r\ny"[..])];
+        let mut lines = Vec::new();
+        while let Some(line) = next_interleaved_line(&mut inputs, ",").unwrap() {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["1,x", "2,y", "3,"]);
+    }
+
+    /// Tests cutting fields out of lines, and the parsing of the field list.
+    #[test]
+    fn field_extraction_cuts_selected_fields() {
+        let fields = parse_field_list("1,3-4,6-").unwrap();
+        let mut extraction = FieldExtraction::new(fields, ",", ";");
+        assert_eq!(extraction.apply_feature("a,b,c,d,e,f,g").unwrap(), "a;c;d;f;g");
+        assert_eq!(extraction.apply_feature("a,b").unwrap(), "a");
+        assert_eq!(extraction.apply_feature("no delimiter").unwrap(), "no delimiter");
+
+        assert_eq!(parse_field_list("-2").unwrap(), FieldList(vec![(1, 2)]));
+        assert!(parse_field_list("0").is_err());
+        assert!(parse_field_list("5-3").is_err());
+        assert!(Cli::try_parse_from(["ricat", "--fields", "1", "--field-delim", ""]).is_err());
+    }
+
+    /// Tests selecting columns with awk-like references, missing columns are empty.
+    #[test]
+    fn column_selection_with_awk_references() {
+        let mut selection = ColumnSelection::new(parse_column_list("$NF, $1 $(NF-1)").unwrap());
+        assert_eq!(selection.apply_feature("  drwxr-x  2 root   etc ").unwrap(), "etc drwxr-x root");
+
+        let mut selection = ColumnSelection::new(parse_column_list("$3 $1").unwrap());
+        assert_eq!(selection.apply_feature("a").unwrap(), " a");
+
+        assert!(parse_column_list("$x").is_err());
+        assert!(parse_column_list("1").is_err());
+    }
+
+    /// Tests dropping adjacent duplicates, and all duplicates up to the memory cap with the global mode.
+    #[test]
+    fn unique_lines_drop_duplicates() {
+        let input = ["a", "a", "b", "a", "b", "c", "c"];
+        let unique = |feature: &mut UniqueLines| -> Vec<String> {
+            input.iter().filter_map(|line| feature.apply_feature(line)).map(Cow::into_owned).collect()
+        };
+
+        assert_eq!(unique(&mut UniqueLines::new()), vec!["a", "b", "a", "b", "c"]);
+        assert_eq!(unique(&mut UniqueLines::global(1024)), vec!["a", "b", "c"]);
+        // only `a` fits into the cap, the duplicates of later lines are kept
+        assert_eq!(unique(&mut UniqueLines::global(1)), vec!["a", "b", "b", "c", "c"]);
+    }
+
+    /// Tests that reservoir sampling picks the requested number of lines in their order,
+    /// and keeps everything when there are fewer lines.
+    #[test]
+    fn reservoir_sample_keeps_order() {
+        use crate::sampling::{Random, Reservoir};
+
+        let mut reservoir = Reservoir::new(10, Random::with_seed(7));
+        (0..1000).for_each(|line| reservoir.offer(line));
+        let sample = reservoir.into_sample();
+        assert_eq!(sample.len(), 10);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut reservoir = Reservoir::new(10, Random::with_seed(7));
+        (0..3).for_each(|line| reservoir.offer(line));
+        assert_eq!(reservoir.into_sample(), vec![0, 1, 2]);
+    }
+
+    /// Tests that percentage sampling keeps about the given share of the lines.
+    #[test]
+    fn sample_percent_keeps_share_of_lines() {
+        use crate::sampling::Random;
+
+        let mut sample = SamplePercent::new(25.0, Random::with_seed(42));
+        let kept = (0..10_000).filter(|_| sample.apply_feature("line").is_some()).count();
+        assert!((2_200..2_800).contains(&kept), "kept {} lines", kept);
+        assert!(parse_percent("0").is_err());
+    }
+
+    /// Tests dropping lines at both ends of the input, starting over with the next input.
+    #[test]
+    fn skip_head_and_tail_drop_lines() {
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(SkipHead::new(1)), Box::new(SkipTail::new(2))];
+        let mut run = |lines: &[&str]| -> Vec<String> {
+            begin_input(&mut features, &InputSource::Stdin).unwrap();
+            lines.iter().flat_map(|line| apply_features(&mut features, line.to_string(), &LineContext::default()).unwrap()).collect()
+        };
+
+        assert_eq!(run(&["header", "a", "b", "c", "total", "end"]), vec!["a", "b", "c"]);
+        assert_eq!(run(&["header", "x", "total", "end"]), vec!["x"]);
+        assert!(run(&["header", "total"]).is_empty());
+    }
+
+    /// Tests that reversing a line keeps grapheme clusters whole, and reverses colored lines as their text.
+    #[test]
+    fn line_reversal_keeps_graphemes() {
+        let mut reversal = LineReversal::new();
+        assert_eq!(reversal.apply_feature("abc").unwrap(), "cba");
+        assert_eq!(reversal.apply_feature("noe\u{308}l").unwrap(), "le\u{308}on");
+        assert_eq!(apply_features(&mut [Box::new(LineReversal::new()) as Box<dyn ByteFeature>], "\x1b[31mred\x1b[0m".to_string(), &LineContext::default()).unwrap(), vec!["der"]);
+    }
+
+    /// Tests the wc-style counts and their report, with a total for several inputs.
+    #[test]
+    fn stats_report_counts_lines_words_bytes() {
+        use crate::stats::{stats_report, TextStats};
+
+        let mut first = TextStats::default();
+        ["hello world", "", "the longest line"].iter().for_each(|line| first.add_line(line.as_bytes(), "\n"));
+        let mut second = TextStats::default();
+        second.add_line(b"one", "\n");
+        assert_eq!(first, TextStats { lines: 3, words: 5, bytes: 30, max_line_length: 16 });
+
+        // like `wc`: a last line without a newline is no line, CRLF counts two bytes, bytes which are not UTF-8 are no word
+        let counts = |lines: &[(&[u8], &str)]| {
+            let mut stats = TextStats::default();
+            lines.iter().for_each(|(line, terminator)| stats.add_line(line, terminator));
+            (stats.lines, stats.words, stats.bytes)
+        };
+        assert_eq!(counts(&[(b"ab", "")]), (0, 1, 2));
+        assert_eq!(counts(&[(b"a b", "\r\n"), (b"c", "\r\n")]), (2, 3, 8));
+        assert_eq!(counts(&[(b"\xff\xfe", "\n")]), (1, 0, 3));
+
+        assert_eq!(
+            stats_report(&[("a.txt".to_string(), first), ("b.txt".to_string(), second)]),
+            vec![
+                "   lines    words    bytes max-line",
+                "       3        5       30       16 a.txt",
+                "       1        1        4        3 b.txt",
+                "       4        6       34       16 total",
+            ]
+        );
+    }
+
+    /// Tests that `--stats=summary` streams the content before the report, so `--max-memory` does not limit it.
+    #[test]
+    fn stats_summary_streams_the_content() {
+        let path = std::env::temp_dir().join(format!("ricat_stats_summary_{}.txt", std::process::id()));
+        std::fs::write(&path, "one two\nthree\n").unwrap();
+        let arguments = Cli::try_parse_from(["ricat", "--stats=summary", "--max-memory", "4", path.to_str().unwrap()]).unwrap();
+        let mut output = Vec::new();
+        handle_stats(StatsMode::Summary, &arguments, &mut [], &mut output).unwrap();
+        let report = format!("       2        3       14        7 {}\n", path.display());
+        assert_eq!(String::from_utf8(output).unwrap(), format!("one two\nthree\n   lines    words    bytes max-line\n{}", report));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Tests that the length histogram shows every bucket between the shortest and longest line.
+    #[test]
+    fn length_histogram_buckets_line_lengths() {
+        use crate::stats::LengthHistogram;
+
+        let mut histogram = LengthHistogram::new(5);
+        ["abc", "abcdef", "abcdefg", "0123456789abcdefghij"].iter().for_each(|line| histogram.add_line(line));
+        assert_eq!(
+            histogram.render(),
+            vec![
+                "  0-4  1  ####################",
+                "  5-9  2  ########################################",
+                "10-14  0",
+                "15-19  0",
+                "20-24  1  ####################",
+            ]
+        );
+        assert!(LengthHistogram::new(5).render().is_empty());
+    }
+
+    /// Tests that the search finishes with a summary footer, naming the files once there are several.
+    #[test]
+    fn search_summary_counts_matches() {
+        let mut search = LineWithGivenText::new("x", false).with_summary();
+        for (file_name, line_number, line) in [("a.txt", 1, "x"), ("a.txt", 2, "y"), ("a.txt", 3, "xx")] {
+            let context = LineContext { file_name, line_number, ..Default::default() };
+            search.apply_feature_in_context(line, &context).unwrap();
+        }
+        assert_eq!(search.finish(), vec!["2 matches across 1 file (lines: 1, 3)"]);
+
+        search.apply_feature_in_context("y x", &LineContext { file_name: "b.txt", line_number: 1, ..Default::default() }).unwrap();
+        assert_eq!(search.finish(), vec!["3 matches across 2 files (lines: a.txt:1, a.txt:3, b.txt:1)"]);
+        assert_eq!(LineWithGivenText::new("x", false).with_summary().finish(), vec!["0 matches across 0 files"]);
+
+        // only the lines the footer lists are kept, the other matches are counted
+        let mut search = LineWithGivenText::new("x", false).with_summary();
+        for line_number in 1..=12 {
+            search.apply_feature_in_context("x", &LineContext { file_name: "-", line_number, ..Default::default() }).unwrap();
+        }
+        assert_eq!(search.finish(), vec!["12 matches across 1 file (lines: 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, …)"]);
+
+        let mut search = LineWithGivenText::new("x", false).with_summary();
+        search.apply_feature_in_context("x", &LineContext { file_name: "-", line_number: 1, ..Default::default() }).unwrap();
+        assert_eq!(search.finish(), vec!["1 match across 1 file (lines: 1)"]);
+        assert!(LineWithGivenText::new("x", false).finish().is_empty());
+    }
+
+    /// Tests that the features get the input name, line number, byte offset and last line of every line.
+    #[test]
+    fn features_get_the_line_context() {
+        struct Context;
+        impl LineTextFeature for Context {
+            fn apply_feature<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+                Some(line.into())
+            }
+
+            fn apply_feature_in_context<'a>(&mut self, line: &'a str, context: &LineContext) -> Result<Vec<Cow<'a, str>>, RicatError> {
+                let LineContext { file_name, line_number, byte_offset, is_last } = context;
+                Ok(vec![format!("{}:{}:{}:{} {}", file_name, line_number, byte_offset, is_last, line).into()])
+            }
+        }
+
+        let mut output = Vec::new();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(SkipHead::new(1)), Box::new(Context)];
+        process_input_to(&b"a\nbc\r\nd\n"[..], "in.txt", &mut output, &mut features, OutputOptions::default()).unwrap();
+        assert_eq!(output, b"in.txt:2:2:false bc\nin.txt:3:6:true d\n");
+    }
+
+    /// Tests that the lines added by the features at the end of the input follow the output, untouched by the other features.
+    #[test]
+    fn finish_lines_follow_the_output() {
+        let mut features: Vec<Box<dyn ByteFeature>> =
+            vec![Box::new(LineWithGivenText::new("a", false).with_summary()), Box::new(LineNumbering::new())];
+        let lines = process_input_ret(&b"a\nb\nab\n"[..], &mut features).unwrap();
+        assert_eq!(lines, vec!["1 a", "2 ab", "2 matches across 1 file (lines: 1, 3)"]);
+    }
+
+    /// Tests that features enabled by name apply in the order of the registry, and that unknown names
+    /// or features missing their value are reported.
+    #[test]
+    fn features_are_enabled_by_name() {
+        let arguments = Cli::try_parse_from(["ricat", "--features", "dollar,numbering"]).unwrap();
+        let mut features = builtin_features().build(&arguments, &arguments.feature_names, &[]).unwrap();
+        assert_eq!(process_input_ret(&b"a\n"[..], &mut features).unwrap(), vec!["1 a$"]);
+
+        let error = builtin_features().build(&arguments, &["nope".to_string()], &[]).err().unwrap();
+        assert!(error.to_string().starts_with("Feature error: unknown feature `nope`, the features are: skip-head, "));
+        let error = builtin_features().build(&arguments, &["fields".to_string()], &[]).err().unwrap();
+        assert_eq!(error.to_string(), "Feature error: feature `fields` needs a value, enable it with --fields");
+    }
+
+    /// Tests that features whose options conflict are refused together when enabled by name.
+    #[test]
+    fn conflicting_features_are_refused_by_name() {
+        let arguments = Cli::try_parse_from(["ricat", "--features", "numbering,relative-numbering"]).unwrap();
+        let error = builtin_features().build(&arguments, &arguments.feature_names, &[]).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Feature error: features `numbering` and `relative-numbering` cannot be used together"
+        );
+
+        let arguments = Cli::try_parse_from(["ricat", "--relative-numbers", "--features", "numbering"]).unwrap();
+        let error = builtin_features().build(&arguments, &arguments.feature_names, &[]).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Feature error: features `numbering` and `relative-numbering` cannot be used together"
+        );
+    }
+
+    /// Tests that `--feature-order` swaps the places of the features it names and keeps the others in place.
+    #[test]
+    fn feature_order_reorders_named_features() {
+        let input = &b"a\n\n\nb\n"[..];
+        let arguments = Cli::try_parse_from(["ricat", "-s", "-n", "-d", "--search", "--text", "b"]).unwrap();
+        let mut features = builtin_features().build(&arguments, &[], &[]).unwrap();
+        assert_eq!(process_input_ret(input, &mut features).unwrap(), vec!["1 b$"]);
+
+        let order = ["numbering".to_string(), "search".to_string()];
+        let mut features = builtin_features().build(&arguments, &[], &order).unwrap();
+        assert_eq!(process_input_ret(input, &mut features).unwrap(), vec!["3 b$"]);
+    }
+
+    /// Tests that a plugin feature gets each line through the C interface, emits its lines and reports a failing status,
+    /// that an instance without state is refused, and that a library is only loaded once it is requested.
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn plugin_features_use_the_c_interface() {
+        use crate::plugins::{register_plugins, EmitLine, PluginApi, PluginFeature};
+        use std::ffi::c_void;
+
+        static STATE: u8 = 0;
+        unsafe extern "C" fn new() -> *mut c_void {
+            &STATE as *const u8 as *mut c_void
+        }
+        unsafe extern "C" fn no_state() -> *mut c_void {
+            std::ptr::null_mut()
+        }
+        unsafe extern "C" fn apply(_: *mut c_void, line: *const u8, len: usize, emit: EmitLine, sink: *mut c_void) -> i32 {
+            let line = std::slice::from_raw_parts(line, len);
+            match line {
+                b"drop" => 0,
+                b"fail" => 3,
+                _ => {
+                    emit(sink, line.as_ptr(), len);
+                    emit(sink, line.as_ptr(), len);
+                    0
+                }
+            }
+        }
+        unsafe extern "C" fn free(_: *mut c_void) {}
+
+        let mut feature = PluginFeature::new("twice", PluginApi { new, apply, free }, None).unwrap();
+        let context = LineContext::default();
+        assert_eq!(feature.apply_record(b"a\xff", &context).unwrap(), vec![b"a\xff".to_vec(), b"a\xff".to_vec()]);
+        assert!(feature.apply_record(b"drop", &context).unwrap().is_empty());
+        let error = feature.apply_record(b"fail", &context).err().unwrap();
+        assert_eq!(error.to_string(), "Feature error: plugin `twice` failed with status 3");
+        let error = PluginFeature::new("none", PluginApi { new: no_state, apply, free }, None).err().unwrap();
+        assert_eq!(error.to_string(), "Feature error: plugin `none` could not make a new instance");
+
+        let root = std::env::temp_dir().join(format!("ricat_plugins_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join(format!("libbroken.{}", std::env::consts::DLL_EXTENSION)), "not a library").unwrap();
+        let mut registry = builtin_features();
+        register_plugins(&mut registry, &root);
+        let arguments = Cli::try_parse_from(["ricat"]).unwrap();
+        assert!(registry.build(&arguments, &[], &[]).is_ok());
+        let error = registry.build(&arguments, &["broken".to_string()], &[]).err().unwrap();
+        assert!(error.to_string().contains("cannot be loaded"));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Tests that a WebAssembly plugin transforms lines in its memory, drops lines and reports failures,
+    /// that one looping forever runs out of fuel, and that a module is only compiled once it is requested.
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn wasm_plugins_transform_lines() {
+        use crate::wasm_plugins::{register_wasm_plugins, sandbox_engine, WasmFeature};
+
+        // uppercases ASCII letters in place, drops empty lines and fails on lines starting with `x`
+        let source = r#"(module
+            (memory (export "memory") 1)
+            (func (export "ricat_alloc") (param i32) (result i32) i32.const 1024)
+            (func (export "ricat_apply") (param $ptr i32) (param $len i32) (result i64)
+                (local $i i32) (local $byte i32)
+                (if (i32.eqz (local.get $len)) (then (return (i64.const -1))))
+                (if (i32.eq (i32.load8_u (local.get $ptr)) (i32.const 120)) (then (return (i64.const -2))))
+                (block $done (loop $next
+                    (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                    (local.set $byte (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+                    (if (i32.and (i32.ge_u (local.get $byte) (i32.const 97)) (i32.le_u (local.get $byte) (i32.const 122)))
+                        (then (i32.store8 (i32.add (local.get $ptr) (local.get $i)) (i32.sub (local.get $byte) (i32.const 32)))))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br $next)))
+                (i64.or (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32)) (i64.extend_i32_u (local.get $len)))))"#;
+        let engine = sandbox_engine().unwrap();
+        let module = wasmtime::Module::new(&engine, source).unwrap();
+        let mut feature = WasmFeature::new("upper", &engine, &module).unwrap();
+        let context = LineContext::default();
+        assert_eq!(feature.apply_record(b"ab\xffc", &context).unwrap(), vec![b"AB\xffC".to_vec()]);
+        assert!(feature.apply_record(b"", &context).unwrap().is_empty());
+        let error = feature.apply_record(b"xy", &context).err().unwrap();
+        assert_eq!(error.to_string(), "Feature error: plugin `upper` failed: status -2");
+
+        let endless = r#"(module
+            (memory (export "memory") 1)
+            (func (export "ricat_alloc") (param i32) (result i32) i32.const 0)
+            (func (export "ricat_apply") (param i32 i32) (result i64) (loop $forever (br $forever)) i64.const -1))"#;
+        let module = wasmtime::Module::new(&engine, endless).unwrap();
+        let mut feature = WasmFeature::new("endless", &engine, &module).unwrap();
+        assert!(feature.apply_record(b"line", &context).is_err());
+
+        let root = std::env::temp_dir().join(format!("ricat_wasm_plugins_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("broken.wasm"), "not a module").unwrap();
+        let mut registry = builtin_features();
+        register_wasm_plugins(&mut registry, &root);
+        let arguments = Cli::try_parse_from(["ricat"]).unwrap();
+        assert!(registry.build(&arguments, &[], &[]).is_ok());
+        let error = registry.build(&arguments, &["broken".to_string()], &[]).err().unwrap();
+        assert!(error.to_string().contains("broken.wasm cannot be loaded"));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Tests that a script transforms, splits and drops lines, keeps `state` across lines and reports failures.
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn script_feature_runs_per_line() {
+        use crate::scripting::parse_script;
+
+        let script = parse_script("state.n = (state.n ?? 0) + 1; if line == \"x\" { throw \"bad\" } else if line != \"\" { [state.n, line.to_uppercase()] }").unwrap();
+        let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(ScriptFeature::new(script))];
+        assert_eq!(process_input_ret(&b"a\n\nb\n"[..], &mut features).unwrap(), vec!["1", "A", "3", "B"]);
+        let error = process_input_ret(&b"a\nx\n"[..], &mut features).err().unwrap();
+        assert!(error.to_string().starts_with("Feature error: line 2: script failed: Runtime error: bad"));
+        assert!(parse_script("line +").is_err());
+    }
+
+    /// Tests that a pipeline built with `Ricat::builder()` runs its inputs through the features in order
+    /// into the writer, and reports inputs which cannot be opened.
+    #[test]
+    fn builder_runs_a_pipeline() {
+        let mut output = Vec::new();
+        Ricat::builder()
+            .reader("first", &b"a\n\n\nb\n"[..])
+            .reader("second", &b"c\n"[..])
+            .feature(CompressEmptyLines::new())
+            .feature(LineNumbering::new())
+            .paginate(false)
+            .write_to(&mut output)
+            .run()
+            .unwrap();
+        assert_eq!(output, b"1 a\n2 \n3 b\n4 c\n");
+
+        let error = Ricat::builder().source("no/such/file.txt").write_to(Vec::new()).run().err().unwrap();
+        assert!(matches!(error, RicatError::FileOpenError(_)));
+    }
+
+    /// Tests that the async pipeline processes the lines like the blocking one, keeping the line endings and
+    /// chunking or truncating long lines, and that its future can be sent to another thread with `Send` features.
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_processing_matches_blocking() {
+        use crate::line_reader::{LineLimit, OversizeMode};
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        let mut features: Vec<Box<dyn ByteFeature + Send>> = vec![Box::new(LineNumbering::new()), Box::new(DollarSymbolAtLast::new())];
+        let mut output = Vec::new();
+        let options = OutputOptions { keep_line_endings: true, ..OutputOptions::default() };
+        let future = process_input_async(&b"a\r\nb\nc"[..], "in", &mut output, &mut features, options);
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&future);
+
+        // reading from memory never waits, a single poll runs the whole future
+        let poll = std::pin::pin!(future).poll(&mut Context::from_waker(Waker::noop()));
+        assert!(matches!(poll, Poll::Ready(Ok(()))));
+        assert_eq!(output, b"1 a$\r\n2 b$\n3 c$");
+
+        for mode in [OversizeMode::Chunk, OversizeMode::Truncate] {
+            let input = "ab\r\nlong line\r\nwhole\nnext to last\nend";
+            let options = || OutputOptions {
+                keep_line_endings: true,
+                line_limit: Some(LineLimit { max_bytes: 5, mode }),
+                ..OutputOptions::default()
+            };
+            let mut features: Vec<Box<dyn ByteFeature + Send>> = vec![Box::new(LineNumbering::new())];
+            let mut output = Vec::new();
+            let future = process_input_async(input.as_bytes(), "in", &mut output, &mut features, options());
+            let poll = std::pin::pin!(future).poll(&mut Context::from_waker(Waker::noop()));
+            assert!(matches!(poll, Poll::Ready(Ok(()))));
+
+            let mut features: Vec<Box<dyn ByteFeature>> = vec![Box::new(LineNumbering::new())];
+            let mut blocking = Vec::new();
+            process_input_to(input.as_bytes(), "in", &mut blocking, &mut features, options()).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), String::from_utf8(blocking).unwrap());
+        }
+    }
+
+    /// Tests that files processed in parallel are written in the order given, that a file which cannot be read
+    /// is skipped, and with `--fail-fast` stops the output after the files before it. Only stateless features allow it.
+    #[test]
+    fn parallel_files_keep_their_order() {
+        use crate::parallel::{can_process_in_parallel, process_files_in_parallel, worth_processing_in_parallel};
+
+        let root = std::env::temp_dir().join(format!("ricat_parallel_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let mut sources = Vec::new();
+        for index in 0..8 {
+            let path = root.join(format!("{}.txt", index));
+            std::fs::write(&path, format!("{}a\n{}b\n", index, index).repeat(index * 100)).unwrap();
+            sources.push(InputSource::File(path.to_string_lossy().into_owned()));
+        }
+        let expected: String = (0..8).map(|index| format!("{}a$\n", index).repeat(index * 100)).collect();
+
+        let arguments = Cli::try_parse_from(["ricat", "-d", "--search", "--text", "a"]).unwrap();
+        let features = builtin_features().build(&arguments, &[], &[]).unwrap();
+        assert!(can_process_in_parallel(&features, 4));
+        let mut output = Vec::new();
+        process_files_in_parallel(&arguments, sources.clone(), 4, OutputOptions::default(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        sources.insert(3, InputSource::File(root.join("missing.txt").to_string_lossy().into_owned()));
+        let mut output = Vec::new();
+        process_files_in_parallel(&arguments, sources.clone(), 4, OutputOptions::default(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+        assert_eq!(arguments.failed_inputs.load(Ordering::Relaxed), 1);
+
+        let arguments = Cli::try_parse_from(["ricat", "--fail-fast", "-d", "--search", "--text", "a"]).unwrap();
+        let mut output = Vec::new();
+        let error = process_files_in_parallel(&arguments, sources.clone(), 4, OutputOptions::default(), &mut output).err().unwrap();
+        assert!(matches!(error, RicatError::FileOpenError(_)));
+        assert_eq!(String::from_utf8(output).unwrap(), expected[..expected.find("3a").unwrap()]);
+
+        let numbered = builtin_features().build(&Cli::try_parse_from(["ricat", "-n"]).unwrap(), &[], &[]).unwrap();
+        assert!(!can_process_in_parallel(&numbered, 4));
+
+        // only two or more regular files go through the threads, streams are read as they come
+        let inputs = |sources: &[InputSource]| sources.iter().cloned().map(Ok).collect::<Vec<_>>();
+        assert!(worth_processing_in_parallel(&inputs(&sources)));
+        assert!(!worth_processing_in_parallel(&inputs(&sources[..1])));
+        assert!(!worth_processing_in_parallel(&inputs(&[sources[0].clone(), InputSource::Stdin])));
+        #[cfg(unix)]
+        assert!(!worth_processing_in_parallel(&inputs(&[sources[0].clone(), InputSource::File("/dev/null".to_string())])));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Tests that a search in chunks cuts the input at line ends and writes the lines found in the order of the input,
+    /// with the byte offsets of the whole input, like reading it in one go
+    #[test]
+    fn chunked_search_keeps_the_order() {
+        use crate::parallel::{chunk_ranges, search_in_chunks};
+
+        let data: String = (0..2000).map(|index| format!("line {} {}\n", index, if index % 3 == 0 { "match" } else { "other" })).collect();
+        let chunks = chunk_ranges(data.as_bytes(), b'\n', 100);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| data.as_bytes()[chunk.end - 1] == b'\n'));
+        assert_eq!(chunks.last().unwrap().end, data.len());
+
+        let arguments = Cli::try_parse_from(["ricat", "--search", "--text", "match", "--show-offsets"]).unwrap();
+        let options = output_options(&arguments);
+        let mut expected = Vec::new();
+        let mut features = builtin_features().build(&arguments, &[], &[]).unwrap();
+        process_input_to(data.as_bytes(), "-", &mut expected, &mut features, options.clone()).unwrap();
+
+        let mut output = Vec::new();
+        search_in_chunks(&arguments, data.as_bytes(), &InputSource::Stdin, 100, 4, options, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), String::from_utf8(expected).unwrap());
+    }
+
+    /// Tests that lines are split right when the reads end in the middle of a line or between `\r` and `\n`,
+    /// and that a record handed back is read into again
+    #[test]
+    fn lines_split_across_reads() {
+        use crate::line_reader::{lossy_lines, LineEnding};
+
+        let input: &[u8] = b"first line\r\nsecond\n\nlast";
+        let mut lines = lossy_lines(std::io::BufReader::with_capacity(3, input));
+        let mut records = Vec::new();
+        while let Some(record) = lines.next_record() {
+            let (record, ending) = record.unwrap();
+            records.push((String::from_utf8(record.clone()).unwrap(), ending, lines.line_offset()));
+            lines.reuse(record);
+        }
+        assert_eq!(
+            records,
+            [
+                ("first line".to_string(), LineEnding::CrLf, 0),
+                ("second".to_string(), LineEnding::Lf, 12),
+                (String::new(), LineEnding::Lf, 19),
+                ("last".to_string(), LineEnding::None, 20),
+            ]
+        );
+    }
+
+    /// Tests that `-u` flushes the output after every line and every read of a copy, and that `--buffer-size`
+    /// sets the size of the reads, up to 1 GiB
+    #[test]
+    fn unbuffered_output_flushes_every_line() {
+        struct Flushes {
+            written: Vec<u8>,
+            flushed_at: Vec<usize>,
+        }
+        impl Write for Flushes {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.written.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.flushed_at.push(self.written.len());
+                Ok(())
+            }
+        }
+
+        assert!(Cli::try_parse_from(["ricat", "--buffer-size", "1073741824"]).is_ok());
+        assert!(Cli::try_parse_from(["ricat", "--buffer-size", "1073741825"]).is_err());
+        let arguments = Cli::try_parse_from(["ricat", "-u", "-d", "--buffer-size", "4"]).unwrap();
+        let mut output = Flushes { written: Vec::new(), flushed_at: Vec::new() };
+        let mut features = builtin_features().build(&arguments, &[], &[]).unwrap();
+        process_input_to(&b"ab\ncd\n"[..], "-", &mut output, &mut features, output_options(&arguments)).unwrap();
+        assert_eq!(output.written, b"ab$\ncd$\n");
+        assert_eq!(output.flushed_at, [4, 8]);
+
+        let mut output = Flushes { written: Vec::new(), flushed_at: Vec::new() };
+        copy_with_progress(&b"0123456789"[..], &mut output, buffering(&arguments), None).unwrap();
+        assert_eq!(output.flushed_at, [4, 8, 10]);
+    }
 }
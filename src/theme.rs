@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ansi::RESET;
+
+/// Style of a colored piece of the output, written as words like `bold red`, `bright-blue`, `#268bd2`,
+/// `on-yellow` or `none`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(try_from = "String", into = "String")]
+pub struct Style {
+    /// the words as written
+    spec: String,
+    /// escape sequence switching the style on, empty for a plain style
+    escape: String,
+}
+
+/// Names of the 8 basic colors, in the order of their codes
+const COLORS: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+impl Style {
+    /// Parses the words of the style: attributes (`bold`, `dim`, `italic`, `underline`, `reverse`,
+    /// `strikethrough`), a foreground color and an `on-` background color. A color is one of the 8 basic
+    /// colors, its `bright-` variant, `gray`, a number of the 256 colors or `#rrggbb`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut codes = Vec::new();
+        for word in spec.split_whitespace() {
+            let word = word.to_ascii_lowercase();
+            let code = match word.as_str() {
+                "none" => continue,
+                "bold" => "1".to_string(),
+                "dim" => "2".to_string(),
+                "italic" => "3".to_string(),
+                "underline" => "4".to_string(),
+                "reverse" => "7".to_string(),
+                "strikethrough" => "9".to_string(),
+                _ => match word.strip_prefix("on-") {
+                    Some(color) => color_code(color, 40),
+                    None => color_code(&word, 30),
+                }
+                .ok_or_else(|| {
+                    format!(
+                        "`{}` in `{}` is not a color or attribute, use e.g. `bold red`, `bright-blue`, `#268bd2` or `on-yellow`",
+                        word, spec
+                    )
+                })?,
+            };
+            codes.push(code);
+        }
+        let escape = if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", codes.join(";"))
+        };
+        Ok(Self {
+            spec: spec.trim().to_string(),
+            escape,
+        })
+    }
+
+    /// The text in the style, reset after it. A plain style leaves the text as it is.
+    pub fn paint(&self, text: &str) -> String {
+        if self.escape.is_empty() {
+            return text.to_string();
+        }
+        format!("{}{}{}", self.escape, text, RESET)
+    }
+
+    /// Whether the style leaves the text as it is
+    pub fn is_plain(&self) -> bool {
+        self.escape.is_empty()
+    }
+}
+
+/// Code of the color, `base` is 30 for the foreground and 40 for the background
+fn color_code(color: &str, base: u8) -> Option<String> {
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        return Some(format!(
+            "{};2;{};{};{}",
+            base + 8,
+            channel(0)?,
+            channel(2)?,
+            channel(4)?
+        ));
+    }
+    if let Ok(number) = color.parse::<u8>() {
+        return Some(format!("{};5;{}", base + 8, number));
+    }
+    let (color, base) = match color {
+        "gray" | "grey" => ("black", base + 60),
+        _ => match color.strip_prefix("bright-") {
+            Some(color) => (color, base + 60),
+            None => (color, base),
+        },
+    };
+    let index = COLORS.iter().position(|name| *name == color)?;
+    Some((base + index as u8).to_string())
+}
+
+impl TryFrom<String> for Style {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        Self::parse(&spec)
+    }
+}
+
+impl From<Style> for String {
+    fn from(style: Style) -> Self {
+        style.spec
+    }
+}
+
+/// Built-in color themes, `--theme` or `name` in the `[theme]` section
+#[derive(Deserialize, Serialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// the colors of earlier versions, line numbers, matches and the pager prompt left plain
+    #[default]
+    Default,
+    /// the Solarized palette, for terminals with true colors
+    Solarized,
+    /// bold, bright colors
+    HighContrast,
+    /// no colors, only bold, dim, underlined and reversed text
+    Monochrome,
+}
+
+/// Colors of the output on a terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// line numbers of `-n` and `--relative-numbers`
+    pub line_number: Style,
+    /// text found by `--search`
+    pub search_match: Style,
+    /// levels colored by `--log-levels`: `FATAL`, `CRITICAL` and `ERROR`
+    pub log_error: Style,
+    /// `WARN` and `WARNING`
+    pub log_warn: Style,
+    /// `INFO` and `NOTICE`
+    pub log_info: Style,
+    /// `DEBUG` and `TRACE`
+    pub log_debug: Style,
+    /// prompt of the pager
+    pub pager_status: Style,
+    /// added lines of `--diff`, `--side-by-side` and `--git`
+    pub added: Style,
+    /// removed lines of `--diff` and `--side-by-side`, lines removed above of `--git`
+    pub removed: Style,
+    /// modified lines of `--git`
+    pub modified: Style,
+    /// file names of the diffs and the header row of `--table`
+    pub heading: Style,
+    /// hunk headers of `--diff`
+    pub hunk: Style,
+    /// `--blame` annotations and `--show-whitespace` markers
+    pub dimmed: Style,
+    /// whitespace painted by `--highlight-trailing`
+    pub trailing_whitespace: Style,
+}
+
+impl Theme {
+    /// The colors of a built-in theme
+    pub fn builtin(name: ThemeName) -> Self {
+        let style = |spec: &str| Style::parse(spec).expect("built-in styles are valid");
+        match name {
+            ThemeName::Default => Self {
+                line_number: style("none"),
+                search_match: style("none"),
+                log_error: style("red"),
+                log_warn: style("yellow"),
+                log_info: style("green"),
+                log_debug: style("gray"),
+                pager_status: style("none"),
+                added: style("green"),
+                removed: style("red"),
+                modified: style("yellow"),
+                heading: style("bold"),
+                hunk: style("cyan"),
+                dimmed: style("gray"),
+                trailing_whitespace: style("on-red"),
+            },
+            ThemeName::Solarized => Self {
+                line_number: style("#586e75"),
+                search_match: style("bold #b58900"),
+                log_error: style("#dc322f"),
+                log_warn: style("#b58900"),
+                log_info: style("#859900"),
+                log_debug: style("#586e75"),
+                pager_status: style("#fdf6e3 on-#268bd2"),
+                added: style("#859900"),
+                removed: style("#dc322f"),
+                modified: style("#b58900"),
+                heading: style("bold #268bd2"),
+                hunk: style("#2aa198"),
+                dimmed: style("#586e75"),
+                trailing_whitespace: style("on-#dc322f"),
+            },
+            ThemeName::HighContrast => Self {
+                line_number: style("bright-yellow"),
+                search_match: style("bold black on-bright-yellow"),
+                log_error: style("bold bright-red"),
+                log_warn: style("bold bright-yellow"),
+                log_info: style("bold bright-green"),
+                log_debug: style("bright-cyan"),
+                pager_status: style("bold black on-bright-white"),
+                added: style("bold bright-green"),
+                removed: style("bold bright-red"),
+                modified: style("bold bright-yellow"),
+                heading: style("bold bright-white"),
+                hunk: style("bold bright-cyan"),
+                dimmed: style("white"),
+                trailing_whitespace: style("on-bright-red"),
+            },
+            ThemeName::Monochrome => Self {
+                line_number: style("dim"),
+                search_match: style("reverse"),
+                log_error: style("bold"),
+                log_warn: style("bold"),
+                log_info: style("none"),
+                log_debug: style("dim"),
+                pager_status: style("reverse"),
+                added: style("bold"),
+                removed: style("dim"),
+                modified: style("underline"),
+                heading: style("bold"),
+                hunk: style("underline"),
+                dimmed: style("dim"),
+                trailing_whitespace: style("reverse"),
+            },
+        }
+    }
+
+    /// The theme with the colors the `[theme]` section sets in place of its own
+    pub fn with_settings(mut self, settings: &ThemeSettings) -> Self {
+        let replace = |style: &mut Style, setting: &Option<Style>| {
+            if let Some(setting) = setting {
+                *style = setting.clone();
+            }
+        };
+        replace(&mut self.line_number, &settings.line_number);
+        replace(&mut self.search_match, &settings.search_match);
+        replace(&mut self.log_error, &settings.log_error);
+        replace(&mut self.log_warn, &settings.log_warn);
+        replace(&mut self.log_info, &settings.log_info);
+        replace(&mut self.log_debug, &settings.log_debug);
+        replace(&mut self.pager_status, &settings.pager_status);
+        replace(&mut self.added, &settings.added);
+        replace(&mut self.removed, &settings.removed);
+        replace(&mut self.modified, &settings.modified);
+        replace(&mut self.heading, &settings.heading);
+        replace(&mut self.hunk, &settings.hunk);
+        replace(&mut self.dimmed, &settings.dimmed);
+        replace(&mut self.trailing_whitespace, &settings.trailing_whitespace);
+        self
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin(ThemeName::Default)
+    }
+}
+
+/// Colors of the `[theme]` section: the built-in theme, and the colors replacing its own.
+/// The keys are those of `Theme`.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeSettings {
+    /// built-in theme, like `--theme`
+    pub name: Option<ThemeName>,
+    pub line_number: Option<Style>,
+    pub search_match: Option<Style>,
+    pub log_error: Option<Style>,
+    pub log_warn: Option<Style>,
+    pub log_info: Option<Style>,
+    pub log_debug: Option<Style>,
+    pub pager_status: Option<Style>,
+    pub added: Option<Style>,
+    pub removed: Option<Style>,
+    pub modified: Option<Style>,
+    pub heading: Option<Style>,
+    pub hunk: Option<Style>,
+    pub dimmed: Option<Style>,
+    pub trailing_whitespace: Option<Style>,
+}
+
+impl ThemeSettings {
+    /// Applies the settings of a profile over these, those it leaves out are kept
+    pub fn apply(&mut self, profile: ThemeSettings) {
+        let ThemeSettings {
+            name,
+            line_number,
+            search_match,
+            log_error,
+            log_warn,
+            log_info,
+            log_debug,
+            pager_status,
+            added,
+            removed,
+            modified,
+            heading,
+            hunk,
+            dimmed,
+            trailing_whitespace,
+        } = profile;
+        self.name = name.or(self.name);
+        let replace = |setting: &mut Option<Style>, profile: Option<Style>| {
+            if profile.is_some() {
+                *setting = profile;
+            }
+        };
+        replace(&mut self.line_number, line_number);
+        replace(&mut self.search_match, search_match);
+        replace(&mut self.log_error, log_error);
+        replace(&mut self.log_warn, log_warn);
+        replace(&mut self.log_info, log_info);
+        replace(&mut self.log_debug, log_debug);
+        replace(&mut self.pager_status, pager_status);
+        replace(&mut self.added, added);
+        replace(&mut self.removed, removed);
+        replace(&mut self.modified, modified);
+        replace(&mut self.heading, heading);
+        replace(&mut self.hunk, hunk);
+        replace(&mut self.dimmed, dimmed);
+        replace(&mut self.trailing_whitespace, trailing_whitespace);
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::{BufRead, BufReader, Read};
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+
+use crate::binary_file::SNIFF_SIZE;
+use crate::errors::RicatError;
+
+/// Character encoding of the input given with `--from-encoding`
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceEncoding {
+    /// UTF-16 little endian, as written by most Windows tools
+    #[value(name = "utf-16le")]
+    Utf16Le,
+    /// UTF-16 big endian
+    #[value(name = "utf-16be")]
+    Utf16Be,
+    /// ISO-8859-1 / Windows-1252
+    Latin1,
+    /// detect the encoding from the byte order mark or the content
+    Auto,
+}
+
+/// Wraps the reader in a decoder which transcodes the input to UTF-8.
+/// A byte order mark at the start always wins over the given encoding, and is removed.
+pub fn transcode(
+    reader: Box<dyn Read>,
+    encoding: SourceEncoding,
+) -> Result<Box<dyn Read>, RicatError> {
+    let mut reader = BufReader::with_capacity(SNIFF_SIZE, reader);
+    let encoding = match encoding {
+        SourceEncoding::Utf16Le => Some(UTF_16LE),
+        SourceEncoding::Utf16Be => Some(UTF_16BE),
+        SourceEncoding::Latin1 => Some(WINDOWS_1252),
+        SourceEncoding::Auto => detect_encoding(reader.fill_buf()?),
+    };
+
+    Ok(Box::new(
+        DecodeReaderBytesBuilder::new()
+            .encoding(encoding)
+            .bom_override(true)
+            .build(reader),
+    ))
+}
+
+/// Guesses the encoding of input without byte order mark from its first block.
+/// `None` means UTF-8 (or a byte order mark), which is passed through.
+pub fn detect_encoding(block: &[u8]) -> Option<&'static Encoding> {
+    if block.starts_with(&[0xef, 0xbb, 0xbf])
+        || block.starts_with(&[0xff, 0xfe])
+        || block.starts_with(&[0xfe, 0xff])
+    {
+        return None;
+    }
+
+    // mostly ASCII text in UTF-16 has a NUL in every other byte
+    let zeros_at = |parity: usize| {
+        block
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&byte| byte == 0)
+            .count()
+    };
+    let half = block.len() / 2;
+    if half > 0 && zeros_at(1) * 2 > half && zeros_at(0) * 8 < half {
+        return Some(UTF_16LE);
+    }
+    if half > 0 && zeros_at(0) * 2 > half && zeros_at(1) * 8 < half {
+        return Some(UTF_16BE);
+    }
+
+    match std::str::from_utf8(block) {
+        Ok(_) => None,
+        // a full block may end in the middle of a character, that is no reason to distrust it
+        Err(error) if error.error_len().is_none() && block.len() == SNIFF_SIZE => None,
+        Err(_) => Some(WINDOWS_1252),
+    }
+}
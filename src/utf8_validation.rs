@@ -0,0 +1,73 @@
+use std::io::{self, BufRead};
+
+/// Invalid UTF-8 sequence found by `--validate-utf8`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSequence {
+    /// Byte offset of the sequence from the start of the input
+    pub offset: u64,
+    /// Line number, starting at 1
+    pub line: u64,
+    /// Byte column within the line, starting at 1
+    pub column: usize,
+    /// The offending bytes
+    pub bytes: Vec<u8>,
+}
+
+impl InvalidSequence {
+    /// Report line for the input `name`, e.g. `log.txt:12:5: invalid UTF-8 at byte offset 301: ff fe`
+    pub fn report(&self, name: &str) -> String {
+        let bytes: Vec<String> = self
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!(
+            "{}:{}:{}: invalid UTF-8 at byte offset {}: {}",
+            name,
+            self.line,
+            self.column,
+            self.offset,
+            bytes.join(" ")
+        )
+    }
+}
+
+/// Scans the input line by line and calls `found` for every invalid UTF-8 sequence.
+/// Only one line is held in memory at a time, returns the number of invalid sequences.
+pub fn validate_utf8<R: BufRead>(
+    mut reader: R,
+    mut found: impl FnMut(InvalidSequence),
+) -> io::Result<u64> {
+    let mut buffer = Vec::new();
+    let mut line_start = 0;
+    let mut line = 0;
+    let mut count = 0;
+
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            return Ok(count);
+        }
+        line += 1;
+
+        let mut position = 0;
+        while position < buffer.len() {
+            let error = match std::str::from_utf8(&buffer[position..]) {
+                Ok(_) => break,
+                Err(error) => error,
+            };
+            let start = position + error.valid_up_to();
+            // a sequence cut off by the end of the input has no error length
+            let len = error.error_len().unwrap_or(buffer.len() - start);
+            found(InvalidSequence {
+                offset: line_start + start as u64,
+                line,
+                column: start + 1,
+                bytes: buffer[start..start + len].to_vec(),
+            });
+            count += 1;
+            position = start + len;
+        }
+        line_start += buffer.len() as u64;
+    }
+}
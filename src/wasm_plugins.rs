@@ -0,0 +1,214 @@
+#[cfg(feature = "wasm-plugins")]
+use std::borrow::Cow;
+#[cfg(feature = "wasm-plugins")]
+use std::cell::OnceCell;
+use std::path::Path;
+#[cfg(feature = "wasm-plugins")]
+use std::rc::Rc;
+
+#[cfg(feature = "wasm-plugins")]
+use wasmtime::{
+    Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+#[cfg(feature = "wasm-plugins")]
+use crate::errors::RicatError;
+use crate::feature_registry::FeatureRegistry;
+#[cfg(feature = "wasm-plugins")]
+use crate::{ByteFeature, LineContext};
+
+/// Result of `ricat_apply` for a line the plugin drops
+#[cfg(feature = "wasm-plugins")]
+pub const DROP_LINE: i64 = -1;
+
+/// Fuel of every call into a plugin, about as many WebAssembly instructions: a plugin which loops
+/// forever fails on that line instead of hanging ricat
+#[cfg(feature = "wasm-plugins")]
+pub const FUEL_PER_CALL: u64 = 1_000_000_000;
+
+/// Size the memory of a plugin instance can grow to
+#[cfg(feature = "wasm-plugins")]
+pub const MAX_PLUGIN_MEMORY: usize = 256 * 1024 * 1024;
+
+/// Engine running the plugins, every call is metered with `FUEL_PER_CALL`
+#[cfg(feature = "wasm-plugins")]
+pub fn sandbox_engine() -> Result<Engine, String> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(|error| error.to_string())
+}
+
+/// Instance of a WebAssembly plugin feature, with a store of its own so every instance keeps its own state.
+///
+/// A plugin module imports nothing, so it cannot reach the files, the network or the environment,
+/// and exports:
+///
+/// - `memory`
+/// - `ricat_alloc(len: i32) -> i32`, the address of `len` bytes for ricat to write the next line to
+/// - `ricat_apply(ptr: i32, len: i32) -> i64`, which transforms the line and returns `ptr << 32 | len`
+///   of the resulting line in its memory, `-1` to drop the line, or another negative value to fail
+///
+/// The engine has to come from `sandbox_engine`, the store of an instance is limited to `MAX_PLUGIN_MEMORY`.
+#[cfg(feature = "wasm-plugins")]
+pub struct WasmFeature {
+    name: String,
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    apply: TypedFunc<(i32, i32), i64>,
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl WasmFeature {
+    /// Instantiates the module, failing when it needs imports or lacks one of the exports
+    pub fn new(name: &str, engine: &Engine, module: &Module) -> Result<Self, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_PLUGIN_MEMORY)
+            .instances(1)
+            .build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+        // a start function is metered like the calls
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|error| error.to_string())?;
+        let instance = Instance::new(&mut store, module, &[]).map_err(|error| error.to_string())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| "it exports no `memory`".to_string())?;
+        let alloc = instance
+            .get_typed_func(&mut store, "ricat_alloc")
+            .map_err(|error| error.to_string())?;
+        let apply = instance
+            .get_typed_func(&mut store, "ricat_apply")
+            .map_err(|error| error.to_string())?;
+        Ok(Self {
+            name: name.to_string(),
+            store,
+            memory,
+            alloc,
+            apply,
+        })
+    }
+
+    fn failure(&self, reason: impl std::fmt::Display) -> RicatError {
+        RicatError::FeatureError(format!("plugin `{}` failed: {}", self.name, reason))
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+impl ByteFeature for WasmFeature {
+    fn apply_record<'a>(
+        &mut self,
+        record: &'a [u8],
+        _context: &LineContext,
+    ) -> Result<Vec<Cow<'a, [u8]>>, RicatError> {
+        let len = i32::try_from(record.len()).map_err(|_| self.failure("the line is too long"))?;
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|error| self.failure(error))?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|error| self.failure(error))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, record)
+            .map_err(|error| self.failure(error))?;
+
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|error| self.failure(error))?;
+        let result = self
+            .apply
+            .call(&mut self.store, (ptr, len))
+            .map_err(|error| self.failure(error))?;
+        match result {
+            DROP_LINE => Ok(Vec::new()),
+            status if status < 0 => Err(self.failure(format!("status {}", status))),
+            packed => {
+                let start = (packed >> 32) as usize;
+                let end = start + (packed & 0xffff_ffff) as usize;
+                self.memory
+                    .data(&self.store)
+                    .get(start..end)
+                    .map(|line| vec![Cow::Owned(line.to_vec())])
+                    .ok_or_else(|| self.failure("the line it returned is out of its memory"))
+            }
+        }
+    }
+}
+
+/// Registers every `.wasm` module in the directory as a feature named after the file, e.g. `todo.wasm`
+/// is enabled with `--features todo`. A module is only compiled once it is requested, and one which does not
+/// compile or instantiate stops ricat with its error then. Modules whose names are taken are skipped with a warning.
+#[cfg(feature = "wasm-plugins")]
+pub fn register_wasm_plugins(registry: &mut FeatureRegistry, directory: &Path) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension() == Some("wasm".as_ref()))
+        .collect();
+    paths.sort();
+
+    // shared by the plugins, created once the first of them is used
+    let engine = Rc::new(OnceCell::new());
+    for path in paths {
+        let Some(name) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if registry.names().contains(&name.as_str()) {
+            eprintln!(
+                "ricat: warning: plugin {} is not loaded, the feature `{}` exists already",
+                path.display(),
+                name
+            );
+            continue;
+        }
+        let (engine, module, feature_name) = (engine.clone(), OnceCell::new(), name.clone());
+        registry.register_by_name(&name, move |_| {
+            load_plugin(&feature_name, &path, &engine, &module).map_err(|error| {
+                RicatError::FeatureError(format!(
+                    "plugin {} cannot be loaded: {}",
+                    path.display(),
+                    error
+                ))
+            })
+        });
+    }
+}
+
+/// Instance of the plugin, its module compiled on the first use and kept in `module` for the next ones
+#[cfg(feature = "wasm-plugins")]
+fn load_plugin(
+    name: &str,
+    path: &Path,
+    engine: &OnceCell<Engine>,
+    module: &OnceCell<Module>,
+) -> Result<Box<dyn ByteFeature>, String> {
+    let engine = get_or_try_init(engine, sandbox_engine)?;
+    let module = get_or_try_init(module, || Module::from_file(engine, path))?;
+    let feature = WasmFeature::new(name, engine, module)?;
+    Ok(Box::new(feature))
+}
+
+/// The value of the cell, made by `init` when it has none yet
+#[cfg(feature = "wasm-plugins")]
+fn get_or_try_init<T, E: std::fmt::Display>(
+    cell: &OnceCell<T>,
+    init: impl FnOnce() -> Result<T, E>,
+) -> Result<&T, String> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = init().map_err(|error| error.to_string())?;
+    Ok(cell.get_or_init(|| value))
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+pub fn register_wasm_plugins(_registry: &mut FeatureRegistry, _directory: &Path) {}
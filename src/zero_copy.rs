@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io;
+
+use crate::progress::Progress;
+
+/// Most bytes one system call copies, the progress bar advances after every call
+#[cfg(target_os = "linux")]
+const COPY_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Output the kernel can copy files to directly: the standard output or the `-o` file,
+/// when nothing transforms the output on the way (compression, encryption, the typewriter)
+pub struct DirectOutput {
+    #[cfg(target_os = "linux")]
+    file: File,
+}
+
+impl DirectOutput {
+    /// The standard output, whatever it is: a file (`ricat a > b`), a pipe, a socket or a terminal
+    #[cfg(target_os = "linux")]
+    pub fn stdout() -> Option<Self> {
+        use std::os::fd::AsFd;
+
+        let fd = io::stdout().as_fd().try_clone_to_owned().ok()?;
+        Some(Self {
+            file: File::from(fd),
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn stdout() -> Option<Self> {
+        None
+    }
+
+    /// The output file, written at the position it shares with `file`
+    #[cfg(target_os = "linux")]
+    pub fn file(file: &File) -> Option<Self> {
+        file.try_clone().ok().map(|file| Self { file })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn file(_file: &File) -> Option<Self> {
+        None
+    }
+}
+
+/// Copies the whole file to the output without reading it into ricat: `copy_file_range` between files,
+/// `sendfile` otherwise (pipes, sockets, files opened with `--append`). Anything written to the output
+/// before has to be flushed first.
+///
+/// Returns `false`, with nothing copied, when the kernel cannot copy between the two, or the file
+/// is not a regular one; it is then copied through a buffer as usual.
+#[cfg(target_os = "linux")]
+pub fn copy_file_direct(
+    source: &File,
+    output: &DirectOutput,
+    mut progress: Option<&mut Progress>,
+) -> io::Result<bool> {
+    use std::os::fd::AsRawFd;
+    use std::ptr::null_mut;
+
+    // zero-length `/proc` files have content the kernel does not copy
+    let metadata = source.metadata()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return Ok(false);
+    }
+
+    // the size when the copy starts, a file appended to while it is copied (`ricat a >> a`) is not followed
+    let (input, out) = (source.as_raw_fd(), output.file.as_raw_fd());
+    let mut use_sendfile = false;
+    let mut remaining = metadata.len();
+    while remaining > 0 {
+        let chunk = remaining.min(COPY_CHUNK_SIZE as u64) as usize;
+        let result = if use_sendfile {
+            unsafe { libc::sendfile(out, input, null_mut(), chunk) }
+        } else {
+            unsafe { libc::copy_file_range(input, null_mut(), out, null_mut(), chunk, 0) }
+        };
+        let copied_before = remaining < metadata.len();
+        match result {
+            // the file was truncated while being copied
+            0 if copied_before => break,
+            0 => {}
+            copied if copied > 0 => {
+                remaining -= copied as u64;
+                if let Some(progress) = progress.as_mut() {
+                    progress.advance(copied as usize);
+                }
+                continue;
+            }
+            _ => {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                // once part of the file is out, falling back would write it twice
+                if copied_before {
+                    return Err(error);
+                }
+            }
+        }
+        // nothing copied yet: another file system, an output `copy_file_range` cannot write to, an old kernel
+        if use_sendfile {
+            return Ok(false);
+        }
+        use_sendfile = true;
+    }
+    Ok(true)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn copy_file_direct(
+    _source: &File,
+    _output: &DirectOutput,
+    _progress: Option<&mut Progress>,
+) -> io::Result<bool> {
+    Ok(false)
+}